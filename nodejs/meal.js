@@ -40,16 +40,7 @@ function Json() {
 
   this.name_list = (type) => {
     if(type == "摂取基準") {
-      this.data["name_list"] = this.data["name_list"].concat([
-        "食品番号", "クラス", "食品名", "価格", "重量", "可食量", "廃棄率", "エネルギー", "たんぱく質",
-        "脂質", "飽和脂肪酸", "多価不飽和脂肪酸", "炭水化物", "食物繊維総量", 
-        "レチノール活性当量", "ビタミンD", "α-トコフェロール", 
-        "ビタミンK", "ビタミンB1", "ビタミンB2", "ナイアシン", 
-        "ビタミンB6", "ビタミンB12", "葉酸", "パントテン酸", 
-        "ビオチン", "ビタミンC", "ナトリウム", "カリウム", 
-        "カルシウム", "マグネシウム", "リン", "鉄", "亜鉛", "銅", 
-        "マンガン", "ヨウ素", "セレン", "クロム", "モリブデン"
-      ])
+      this.data["name_list"].push("preset:kijun")
     }
     return this
   }
@@ -2,43 +2,49 @@ use std::fs::File;
 use std::io::BufReader;
 use std::iter::Iterator;
 
+use chrono::{Local, NaiveDate};
 use clap::ArgMatches;
 
 use crate::food_table::FoodTable;
-use crate::parse_json::parse_json;
-use crate::kijun::Kijun;
+use crate::pantry::{Pantry, get_pantry_path};
+use crate::parse_json::{parse_json, ParsedFood};
 use crate::combination::Combination;
+use crate::output::{Format, Layout, print_report_list};
+use crate::preset::PresetList;
+use crate::restriction::TagTable;
+
+// 期限が近い食材1つあたりの達成率への加点
+const EXPIRY_BONUS: f32 = 5.0;
+
+// パントリーにある食材だけを候補にする。重量はパントリーにある重量までにする
+// JSONに食材がなければパントリーの食材をすべて候補にする
+fn get_pantry_food_table(parsed_food_list: &[ParsedFood], food_table: &FoodTable,
+                         pantry: &Pantry, today: NaiveDate) -> Result<FoodTable, String> {
+    let weight_list: Vec<(String, f32)> = if parsed_food_list.is_empty() {
+        pantry.get_number_list(today).into_iter().map(|number| (number, 100.0)).collect()
+    } else {
+        parsed_food_list.iter()
+            .map(|parsed_food| (parsed_food.number.to_string(), parsed_food.weight.unwrap_or(100.0)))
+            .collect()
+    };
 
-fn color(text: &str, style: &str) -> String {
-    let mut colored_text = String::new();
-    let unset_style = "\x1b[0m";
-
-    for c in style.chars() {
-        let color_style = match c {
-            'd' => "\x1b[30m",
-            'r' => "\x1b[31m",
-            'g' => "\x1b[32m",
-            'y' => "\x1b[33m",
-            'b' => "\x1b[34m",
-            'm' => "\x1b[35m",
-            'c' => "\x1b[36m",
-            'w' => "\x1b[37m",
-            'D' => "\x1b[90m",
-            'R' => "\x1b[91m",
-            'G' => "\x1b[92m",
-            'Y' => "\x1b[93m",
-            'B' => "\x1b[94m",
-            'M' => "\x1b[95m",
-            'C' => "\x1b[96m",
-            'W' => "\x1b[97m",
-            '+' => "\x1b[1m",
-            _ => continue
-        };
+    let mut pantry_food_table = FoodTable::new();
+    for (number, weight) in weight_list {
+        let weight = weight.min(pantry.get_weight(&number, today));
+        if weight <= 0.0 {
+            continue
+        }
+        match food_table.get(&number) {
+            Some(food) => pantry_food_table.add(food.change_weight(weight).unwrap()),
+            None => return Err(format!("{}番の食材はありません。JSONの値が間違っています", &number))
+        }
+    }
 
-        colored_text += color_style;
+    if pantry_food_table.iter().next().is_none() {
+        return Err("パントリーに使える食材がありません".to_string())
     }
 
-    colored_text + text + unset_style
+    Ok(pantry_food_table)
 }
 
 pub fn automatic_selection(matches: &ArgMatches) -> Result<(), String> {
@@ -46,6 +52,7 @@ pub fn automatic_selection(matches: &ArgMatches) -> Result<(), String> {
         Some(path) => path,
         None => return Err("ファイルを指定してください".to_string())
     };
+    let format = Format::from_str(matches.value_of("format").unwrap_or("text"))?;
 
     let file = match File::open(path) {
         Ok(file) => file,
@@ -57,58 +64,133 @@ pub fn automatic_selection(matches: &ArgMatches) -> Result<(), String> {
         Err(e) => return Err(e)
     };
 
-    let food_table_path = "/home/jibuntu/programming_language/rust/project/meal/data/foods.json";
-    let food_table = match FoodTable::from_json(food_table_path) {
-        Ok(food_table) => food_table,
-        Err(e) => return Err(e.to_string())
-    };
+    let food_table = crate::read_food_table()?;
 
-    let mut inputted_food_table = FoodTable::new();
-    for parsed_food in parsed_data.foods {
-        let mut food = match food_table.get(&parsed_food.number) {
-            Some(food) => food.clone(),
-            None => return Err(format!("{}番の食材はありません。JSONの値が間違っています", &parsed_food.number))
+    let today = Local::now().date_naive();
+    let pantry = if matches.is_present("pantry") {
+        let pantry_path = match matches.value_of("pantry-file") {
+            Some(path) => std::path::PathBuf::from(path),
+            None => get_pantry_path().ok_or_else(|| "パントリーのファイルの場所がわかりません。--pantry-fileで指定してください".to_string())?
         };
+        Some(Pantry::read(&pantry_path)?)
+    } else {
+        None
+    };
 
-        food = food.change_weight(parsed_food.weight.unwrap_or(100.0)).unwrap();
-        inputted_food_table.add(food);
+    let mut inputted_food_table = match &pantry {
+        Some(pantry) => get_pantry_food_table(&parsed_data.foods, &food_table, pantry, today)?,
+        None => {
+            let mut inputted_food_table = FoodTable::new();
+            for parsed_food in &parsed_data.foods {
+                let food = match food_table.get(&parsed_food.number) {
+                    Some(food) => food.change_weight(parsed_food.weight.unwrap_or(100.0)).unwrap(),
+                    None => return Err(format!("{}番の食材はありません。JSONの値が間違っています", &parsed_food.number))
+                };
+
+                inputted_food_table.add(food);
+            }
+            inputted_food_table
+        }
+    };
+    inputted_food_table.set_trace_mode(parsed_data.options.trace);
+
+    // body.restrictionsに当てはまる食品は候補から除く
+    let restriction_list = &parsed_data.body.restrictions;
+    if !restriction_list.is_empty() {
+        let count = inputted_food_table.iter().count();
+        inputted_food_table = TagTable::load()?.filter(&inputted_food_table, restriction_list);
+        let excluded_count = count - inputted_food_table.iter().count();
+        if excluded_count != 0 && format == Format::Text {
+            println!("制限に当てはまる{}個の食品を候補から除きました", excluded_count);
+        }
     }
 
-    let list: Vec<_> = parsed_data.name_list.iter().map(|name| name.as_str()).collect();
+    let name_list = PresetList::load()?.expand(&parsed_data.name_list)?;
+    let list: Vec<_> = name_list.iter().map(|name| name.as_str()).collect();
 
-    let kijun = Kijun::new(parsed_data.body.age,
-                           parsed_data.body.weight,
-                           parsed_data.body.height,
-                           parsed_data.body.gender,
-                           parsed_data.body.pal,
-                           parsed_data.body.days.unwrap_or(1));
+    let kijun = parsed_data.body.get_kijun(parsed_data.body.days.unwrap_or(1))?;
 
-    let mut food_table_list: Vec<(f32, FoodTable)> = Vec::new();
+    let mut food_table_list: Vec<(f32, f32, FoodTable)> = Vec::new();
     let list_of_length_of_combination = parsed_data.comb.unwrap_or(vec![5]);
 
     for length_of_combination in list_of_length_of_combination {
         let keys: Vec<_> = inputted_food_table.iter().map(|(key, _)| key.to_string()).collect();
+        // パントリーの食材が少ない場合は、あるだけの組み合わせにする
+        let length_of_combination = match &pantry {
+            Some(_) => length_of_combination.min(keys.len()),
+            None => length_of_combination
+        };
         let comb = Combination::new(keys, length_of_combination);
 
         for key_list in comb.iter() {
             let keys: Vec<&str> = key_list.iter().map(|key| key.as_str()).collect();
             let ft = inputted_food_table.get_list(&keys);
             let percentage = ft.percentage_of_kijun(&kijun).unwrap();
-            food_table_list.push((percentage, ft));
+            // 期限が近い食材を含む組み合わせを優先する
+            let bonus = match &pantry {
+                Some(pantry) => keys.iter()
+                    .map(|key| pantry.get_expiry_score(key, today) * EXPIRY_BONUS)
+                    .sum(),
+                None => 0.0
+            };
+            food_table_list.push((percentage, bonus, ft));
         }
     }
 
-    food_table_list.sort_by(|(p, _), (p2, _)| p2.partial_cmp(&p).unwrap());
+    food_table_list.sort_by(|(p, b, _), (p2, b2, _)| (p2 + b2).partial_cmp(&(p + b)).unwrap());
 
-    for (index, (percentage, ft)) in food_table_list.iter().take(5).enumerate() {
-        println!("{}", color(&format!("[{}] 摂取基準の達成率: {}", index+1, percentage), "g+"));
-        ft.print_with_sum_and_kijun(&list, &kijun);
-        println!();
+    let mut report_list = Vec::new();
+    for (index, (percentage, bonus, ft)) in food_table_list.iter().take(5).enumerate() {
+        let mut report = ft.get_report_with_sum_and_kijun(&list, &kijun);
+        let mut title = format!("[{}] 摂取基準の達成率: {}", index+1, percentage);
+        if *bonus > 0.0 {
+            title += &format!(" (期限が近い食材の加点: {:.1})", bonus);
+        }
+        report.set_title(&title);
+        report.set_layout(Layout::from_terminal(matches.is_present("transpose")));
+        report_list.push(report);
     }
 
-    println!("[automatic selection]");
+    print_report_list(&report_list, format);
+
+    if format == Format::Text {
+        println!("[automatic selection]");
+    }
 
     Ok(())
 }
 
 
+
+
+#[test]
+fn test_automatic_selection_get_pantry_food_table() {
+    use crate::food::nutrient::Nutrient;
+
+    let food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
+    let today = crate::diary::parse_date("2020-01-01").unwrap();
+    let pantry = Pantry::from_json(&serde_json::json!({"items": [
+        {"number": "01001", "weight": 40},
+        {"number": "01002", "weight": 300},
+        {"number": "01003", "weight": 100, "expiry": "2019-12-31"}
+    ]})).unwrap();
+    let get_weight_list = |ft: &FoodTable| -> Vec<(String, f32)> {
+        ft.iter().map(|(key, food)| {
+            (key.to_string(), *food.get_nutrient(Nutrient::Weight).get_number().unwrap())
+        }).collect()
+    };
+
+    // JSONに食材がなければパントリーの食材をすべて候補にする。期限切れは除く
+    let ft = get_pantry_food_table(&[], &food_table, &pantry, today).unwrap();
+    assert_eq!(get_weight_list(&ft), vec![("01001".to_string(), 40.0), ("01002".to_string(), 100.0)]);
+
+    // パントリーにない食材は候補にしない
+    let parsed_food_list = vec![
+        ParsedFood { number: "01002".to_string(), weight: Some(150.0), price: None, class: None, include_refuse: false },
+        ParsedFood { number: "01004".to_string(), weight: None, price: None, class: None, include_refuse: false },
+    ];
+    let ft = get_pantry_food_table(&parsed_food_list, &food_table, &pantry, today).unwrap();
+    assert_eq!(get_weight_list(&ft), vec![("01002".to_string(), 150.0)]);
+
+    assert!(get_pantry_food_table(&[], &food_table, &Pantry::new(), today).is_err());
+}
@@ -0,0 +1,186 @@
+use crate::food::Food;
+use crate::food::food_data::TraceMode;
+use crate::food::nutrient::Nutrient;
+use crate::kijun::Kijun;
+use crate::query::{parse_per, split_amount};
+
+// NRF9.2で摂取を勧める栄養素
+const NRF_ENCOURAGED_LIST: [Nutrient; 9] = [
+    Nutrient::Protein, Nutrient::Fibtg, Nutrient::VitaRae, Nutrient::Vitc,
+    Nutrient::Tocpha, Nutrient::Ca, Nutrient::Fe, Nutrient::K, Nutrient::Mg,
+];
+
+// NRF9.2で制限する栄養素。NRF9.3の添加糖類は成分表にないので使わない
+const NRF_LIMITED_LIST: [Nutrient; 2] = [Nutrient::Fasat, Nutrient::Na];
+
+// 栄養密度の列
+// "ビタミンC per 100kcal": 100kcalあたりのビタミンC
+// "ビタミンC% per 100kcal": 100kcalあたりのビタミンCの摂取基準に対する割合
+// "NRF9.2": Nutrient Rich Food Index
+#[derive(Debug, PartialEq)]
+pub enum Density {
+    Per { nutrient: Nutrient, amount: f32, base: Nutrient },
+    KijunPer { nutrient: Nutrient, amount: f32, base: Nutrient },
+    Nrf,
+}
+
+// 食品の値を摂取基準の値に対する割合(%)にする
+fn get_kijun_ratio(kijun: &Kijun, nutrient: Nutrient, value: f32) -> Option<f32> {
+    let name = nutrient.get_name();
+    let kijun_value = kijun.get(name)?;
+    let value = nutrient.get_unit().convert(value, Kijun::get_unit(name)?)?;
+    let reference = kijun_value.get_reference();
+    if reference <= 0.0 {
+        return None
+    }
+
+    Some(value / reference * 100.0)
+}
+
+// baseの項目のamountあたりの値
+fn get_per(food: &Food, nutrient: Nutrient, amount: f32, base: Nutrient, mode: TraceMode) -> Option<f32> {
+    let value = food.get_nutrient(nutrient).get_number_with(mode)?;
+    let base = food.get_nutrient(base).get_number_with(mode)?;
+    if base <= 0.0 {
+        return None
+    }
+
+    Some(value / base * amount)
+}
+
+impl Density {
+    pub fn from_name(name: &str) -> Option<Density> {
+        if name.eq_ignore_ascii_case("NRF9.2") {
+            return Some(Density::Nrf)
+        }
+
+        let (name, per) = name.split_once(" per ")?;
+        let (amount, unit) = split_amount(per.trim());
+        let (amount, base) = parse_per(amount, unit.trim()).ok()?;
+
+        match name.trim().strip_suffix('%') {
+            Some(name) => Some(Density::KijunPer {
+                nutrient: Nutrient::from_name(name.trim())?, amount, base
+            }),
+            None => Some(Density::Per {
+                nutrient: Nutrient::from_name(name.trim())?, amount, base
+            }),
+        }
+    }
+
+    // 計算できない場合はNone。摂取基準が必要な値でkijunがない場合もNone
+    pub fn calculate(&self, food: &Food, kijun: Option<&Kijun>, mode: TraceMode) -> Option<f32> {
+        match self {
+            Density::Per { nutrient, amount, base } => {
+                get_per(food, *nutrient, *amount, *base, mode)
+            },
+            Density::KijunPer { nutrient, amount, base } => {
+                let value = get_per(food, *nutrient, *amount, *base, mode)?;
+                get_kijun_ratio(kijun?, *nutrient, value)
+            },
+            Density::Nrf => {
+                let kijun = kijun?;
+                // 100kcalあたりで計算する
+                let per = |nutrient| get_per(food, nutrient, 100.0, Nutrient::EnercKcal, mode);
+                // エネルギーがない食品は計算できない
+                per(Nutrient::EnercKcal)?;
+
+                let mut score = 0.0;
+                for nutrient in NRF_ENCOURAGED_LIST.iter() {
+                    let ratio = per(*nutrient).and_then(|v| get_kijun_ratio(kijun, *nutrient, v));
+                    score += ratio.unwrap_or(0.0).min(100.0);
+                }
+                for nutrient in NRF_LIMITED_LIST.iter() {
+                    let ratio = per(*nutrient).and_then(|v| get_kijun_ratio(kijun, *nutrient, v));
+                    score -= ratio.unwrap_or(0.0);
+                }
+
+                Some(score)
+            }
+        }
+    }
+
+    // 表計算ソフトの式。cellは項目の値のセルの位置
+    // NRF9.2は100kcalあたりの値で重量を変えても変わらないので式にしない
+    pub fn to_formula(&self, kijun: Option<&Kijun>, cell: &dyn Fn(Nutrient) -> String) -> Option<String> {
+        match self {
+            Density::Per { nutrient, amount, base } => {
+                Some(format!("={}/{}*{}", cell(*nutrient), cell(*base), amount))
+            },
+            Density::KijunPer { nutrient, amount, base } => {
+                // 摂取基準に対する割合は値に比例する
+                let rate = get_kijun_ratio(kijun?, *nutrient, 1.0)?;
+                Some(format!("={}/{}*{}*{}", cell(*nutrient), cell(*base), amount, rate))
+            },
+            Density::Nrf => None
+        }
+    }
+
+    // 計算に使う項目
+    pub fn get_nutrient_list(&self) -> Vec<Nutrient> {
+        match self {
+            Density::Per { nutrient, base, .. } | Density::KijunPer { nutrient, base, .. } => {
+                vec![*nutrient, *base]
+            },
+            Density::Nrf => Vec::new()
+        }
+    }
+}
+
+
+#[cfg(test)]
+use crate::food::food_data::FoodData;
+#[cfg(test)]
+use crate::kijun::{Gender, PAL};
+
+#[test]
+fn test_density_from_name() {
+    assert_eq!(Density::from_name("ビタミンC per 100kcal"),
+               Some(Density::Per { nutrient: Nutrient::Vitc, amount: 100.0, base: Nutrient::EnercKcal }));
+    assert_eq!(Density::from_name("たんぱく質% per 100 円"),
+               Some(Density::KijunPer { nutrient: Nutrient::Protein, amount: 100.0, base: Nutrient::Price }));
+    assert_eq!(Density::from_name("nrf9.2"), Some(Density::Nrf));
+    assert_eq!(Density::from_name("ビタミンC"), None);
+    assert_eq!(Density::from_name("ないもの per 100kcal"), None);
+    assert_eq!(Density::from_name("ビタミンC per 100個"), None);
+}
+
+#[test]
+fn test_density_calculate() {
+    let mut food = Food::new();
+    food.set("エネルギー", FoodData::Number(200.0));
+    food.set("価格", FoodData::Number(50.0));
+    food.set("たんぱく質", FoodData::Number(20.0));
+    food.set("ビタミンC", FoodData::Number(10.0));
+    food.set("ナトリウム", FoodData::Number(100.0));
+    let kijun = Kijun::new(20, 60.0, 170.0, Gender::Male, PAL::Moderate, 1);
+
+    let calculate = |name: &str, kijun| {
+        Density::from_name(name).unwrap().calculate(&food, kijun, TraceMode::Zero)
+    };
+    assert_eq!(calculate("たんぱく質 per 100kcal", None), Some(10.0));
+    assert_eq!(calculate("たんぱく質 per 100円", None), Some(40.0));
+    assert_eq!(calculate("ビタミンC per 100g", None), None);
+    assert_eq!(calculate("ビタミンC% per 100kcal", None), None);
+    // ビタミンCの推奨量は100mg
+    assert_eq!(calculate("ビタミンC% per 100kcal", Some(&kijun)), Some(5.0));
+    assert!(calculate("NRF9.2", Some(&kijun)).unwrap() > 0.0);
+    assert_eq!(calculate("NRF9.2", None), None);
+}
+
+#[test]
+fn test_density_to_formula() {
+    let cell = |nutrient: Nutrient| match nutrient {
+        Nutrient::Vitc => "B2".to_string(),
+        _ => "C2".to_string()
+    };
+    let kijun = Kijun::new(20, 60.0, 170.0, Gender::Male, PAL::Moderate, 1);
+    let to_formula = |name: &str, kijun| Density::from_name(name).unwrap().to_formula(kijun, &cell);
+    assert_eq!(to_formula("ビタミンC per 100kcal", None), Some("=B2/C2*100".to_string()));
+    // ビタミンCの推奨量は100mg
+    assert_eq!(to_formula("ビタミンC% per 100kcal", Some(&kijun)), Some("=B2/C2*100*1".to_string()));
+    assert_eq!(to_formula("ビタミンC% per 100kcal", None), None);
+    assert_eq!(to_formula("NRF9.2", Some(&kijun)), None);
+    assert_eq!(Density::from_name("ビタミンC per 100kcal").unwrap().get_nutrient_list(),
+               vec![Nutrient::Vitc, Nutrient::EnercKcal]);
+}
@@ -0,0 +1,456 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde_json::{Value, Map};
+
+use crate::food::food_data::FoodData;
+use crate::food::nutrient::Nutrient;
+use crate::food_table::{FoodTable, get_kijun_percentage};
+use crate::kijun::{Kijun, KEY_LIST};
+use crate::output::{number_to_json, OutputCell, OutputTable, Report, RowKind};
+use crate::search::SearchEngine;
+
+// これより摂取基準に対する割合が低い日は不足とする
+const LOW_PERCENTAGE: f32 = 80.0;
+// 期間の前半と後半の平均の差がこれより大きければ改善か悪化とする
+const TREND_THRESHOLD: f32 = 10.0;
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// 食事記録の1行
+#[derive(Debug, PartialEq)]
+pub struct DiaryEntry {
+    pub date: NaiveDate,
+    pub meal: Option<String>,
+    pub number: String,
+    pub weight: f32,
+}
+
+pub fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    match NaiveDate::parse_from_str(text, DATE_FORMAT) {
+        Ok(date) => Ok(date),
+        Err(_) => Err(format!("{} は日付にできません。2020-01-31のように指定してください", text))
+    }
+}
+
+impl DiaryEntry {
+    pub fn to_json(&self) -> Value {
+        let mut obj = Map::new();
+        obj.insert("date".to_string(), Value::String(self.date.format(DATE_FORMAT).to_string()));
+        if let Some(meal) = &self.meal {
+            obj.insert("meal".to_string(), Value::String(meal.to_string()));
+        }
+        obj.insert("number".to_string(), Value::String(self.number.to_string()));
+        obj.insert("weight".to_string(), number_to_json(self.weight));
+
+        Value::Object(obj)
+    }
+
+    pub fn from_json(value: &Value) -> Result<DiaryEntry, String> {
+        let date = match value.get("date") {
+            Some(Value::String(date)) => parse_date(date)?,
+            _ => return Err("dateがありません".to_string())
+        };
+        let meal = match value.get("meal") {
+            Some(Value::String(meal)) => Some(meal.to_string()),
+            _ => None
+        };
+        let number = match value.get("number") {
+            Some(Value::String(number)) => number.to_string(),
+            _ => return Err("numberがありません".to_string())
+        };
+        let weight = match value.get("weight").and_then(|w| w.as_f64()) {
+            Some(weight) => weight as f32,
+            None => return Err("weightがありません".to_string())
+        };
+
+        Ok(DiaryEntry { date, meal, number, weight })
+    }
+}
+
+// 記録するファイルの場所。MEAL_DIARYがあればそれを使い、なければ~/.local/share/meal/diary.jsonl
+pub fn get_diary_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("MEAL_DIARY") {
+        return Some(PathBuf::from(path))
+    }
+
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".local").join("share").join("meal").join("diary.jsonl"))
+}
+
+// 1行に1つのJSONを追記する
+pub fn append(path: &Path, entry: &DiaryEntry) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => file,
+        Err(e) => return Err(format!("{}: {}", path.display(), e))
+    };
+    writeln!(file, "{}", entry.to_json()).map_err(|e| e.to_string())
+}
+
+// ファイルがなければ空にする
+pub fn read(path: &Path) -> Result<Vec<DiaryEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new())
+    }
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => return Err(format!("{}: {}", path.display(), e))
+    };
+
+    let mut entry_list = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue
+        }
+        let value: Value = serde_json::from_str(&line)
+            .map_err(|e| format!("{}の{}行目: {}", path.display(), index + 1, e))?;
+        let entry = DiaryEntry::from_json(&value)
+            .map_err(|e| format!("{}の{}行目: {}", path.display(), index + 1, e))?;
+        entry_list.push(entry);
+    }
+
+    Ok(entry_list)
+}
+
+// 食品番号か食品名から食品番号を探す。食品名の場合は最も関連度の高い食品にする
+pub fn find_food(foods: &FoodTable, text: &str) -> Result<String, String> {
+    if foods.get(text).is_some() {
+        return Ok(text.to_string())
+    }
+
+    let text_list: Vec<_> = text.split_whitespace().collect();
+    match SearchEngine::new(foods).search(&text_list, true).into_iter().next() {
+        Some(number) => Ok(number),
+        None => Err(format!("{} に一致する食品はありません", text))
+    }
+}
+
+pub fn get_food_name(foods: &FoodTable, number: &str) -> String {
+    match foods.get(number).map(|food| food.get_nutrient(Nutrient::Name)) {
+        Some(FoodData::String(name)) => name.to_string(),
+        _ => "-".to_string()
+    }
+}
+
+// 期間内の記録の表
+pub fn get_entry_table(entry_list: &[DiaryEntry], foods: &FoodTable) -> OutputTable {
+    let mut table = OutputTable::new(
+        ["日付", "食事", "食品番号", "食品名", "重量"].iter().map(|c| c.to_string()).collect(),
+        ["", "", "", "", "g"].iter().map(|u| u.to_string()).collect(),
+        false);
+
+    for entry in entry_list {
+        table.add_row(RowKind::Food, vec![
+            OutputCell::new(&entry.date.format(DATE_FORMAT).to_string(), ""),
+            OutputCell::new(entry.meal.as_deref().unwrap_or("-"), ""),
+            OutputCell::new(&entry.number, ""),
+            OutputCell::new(&get_food_name(foods, &entry.number), ""),
+            OutputCell::with_value(&entry.weight.to_string(), "", number_to_json(entry.weight)),
+        ]);
+    }
+
+    table
+}
+
+// 1日分の摂取基準に対する割合
+struct DayResult {
+    date: NaiveDate,
+    // 摂取基準の達成率
+    achievement: Option<f32>,
+    // KEY_LISTの順の割合
+    percentage_list: Vec<Option<f32>>,
+}
+
+fn get_day_result_list(entry_list: &[DiaryEntry], foods: &FoodTable, kijun: &Kijun,
+                       from: NaiveDate, to: NaiveDate) -> Vec<DayResult> {
+    let mut date_list: Vec<_> = entry_list.iter()
+        .map(|entry| entry.date)
+        .filter(|date| from <= *date && *date <= to)
+        .collect();
+    date_list.sort();
+    date_list.dedup();
+
+    date_list.into_iter().map(|date| {
+        let mut food_table = FoodTable::new();
+        for entry in entry_list.iter().filter(|entry| entry.date == date) {
+            if let Some(food) = foods.get(&entry.number).and_then(|f| f.change_weight(entry.weight)) {
+                food_table.add(food);
+            }
+        }
+
+        let sum = food_table.get_sum();
+        DayResult {
+            date,
+            achievement: food_table.percentage_of_kijun(kijun),
+            percentage_list: KEY_LIST.iter()
+                .map(|name| get_kijun_percentage(&sum, name, kijun).ok())
+                .collect(),
+        }
+    }).collect()
+}
+
+fn format_percentage(value: Option<f32>, style: &'static str) -> OutputCell {
+    match value {
+        Some(value) => OutputCell::with_value(&format!("{:.0}", value), style, number_to_json(value)),
+        None => OutputCell::with_value("-", style, Value::Null)
+    }
+}
+
+fn average(value_list: &[f32]) -> Option<f32> {
+    if value_list.is_empty() {
+        None
+    } else {
+        Some(value_list.iter().sum::<f32>() / value_list.len() as f32)
+    }
+}
+
+fn get_percentage_table(first_column: &str) -> OutputTable {
+    let mut column_list = vec![first_column.to_string(), "日数".to_string(), "達成率".to_string()];
+    column_list.extend(KEY_LIST.iter().map(|name| name.to_string()));
+    let mut unit_list = vec![String::new(), String::new(), "%".to_string()];
+    unit_list.extend(KEY_LIST.iter().map(|_| "%".to_string()));
+
+    OutputTable::new(column_list, unit_list, true)
+}
+
+// 日ごとの表
+fn get_daily_table(day_result_list: &[DayResult]) -> OutputTable {
+    let mut table = get_percentage_table("日付");
+
+    for day in day_result_list {
+        let mut row = vec![
+            OutputCell::new(&day.date.format(DATE_FORMAT).to_string(), ""),
+            OutputCell::with_value("1", "", Value::from(1)),
+            format_percentage(day.achievement, "g+"),
+        ];
+        for percentage in &day.percentage_list {
+            let style = match percentage {
+                Some(p) if *p < LOW_PERCENTAGE => "r",
+                _ => ""
+            };
+            row.push(format_percentage(*percentage, style));
+        }
+        table.add_row(RowKind::Day, row);
+    }
+
+    table
+}
+
+// 月曜日から始まる週ごとの、記録した日の平均の表
+fn get_weekly_table(day_result_list: &[DayResult]) -> OutputTable {
+    let mut table = get_percentage_table("週");
+
+    let mut week_list: Vec<(NaiveDate, Vec<&DayResult>)> = Vec::new();
+    for day in day_result_list {
+        let monday = day.date - Duration::days(day.date.weekday().num_days_from_monday() as i64);
+        match week_list.last_mut() {
+            Some((start, list)) if *start == monday => list.push(day),
+            _ => week_list.push((monday, vec![day]))
+        }
+    }
+
+    for (monday, list) in week_list {
+        let achievement_list: Vec<_> = list.iter().filter_map(|day| day.achievement).collect();
+        let mut row = vec![
+            OutputCell::new(&format!("{}〜", monday.format(DATE_FORMAT)), ""),
+            OutputCell::with_value(&list.len().to_string(), "", Value::from(list.len())),
+            format_percentage(average(&achievement_list), "g+"),
+        ];
+        for index in 0..KEY_LIST.len() {
+            let value_list: Vec<_> = list.iter().filter_map(|day| day.percentage_list[index]).collect();
+            let value = average(&value_list);
+            let style = match value {
+                Some(p) if p < LOW_PERCENTAGE => "r",
+                _ => ""
+            };
+            row.push(format_percentage(value, style));
+        }
+        table.add_row(RowKind::Week, row);
+    }
+
+    table
+}
+
+// 記録した日の半分以上で不足している栄養素と、その傾向の表
+fn get_low_nutrient_table(day_result_list: &[DayResult]) -> OutputTable {
+    let mut table = OutputTable::new(
+        ["栄養素", "平均", "不足の日数", "傾向"].iter().map(|c| c.to_string()).collect(),
+        ["", "%", "", ""].iter().map(|u| u.to_string()).collect(),
+        false);
+
+    for (index, name) in KEY_LIST.iter().enumerate() {
+        let value_list: Vec<_> = day_result_list.iter()
+            .filter_map(|day| day.percentage_list[index])
+            .collect();
+        let low_count = value_list.iter().filter(|value| **value < LOW_PERCENTAGE).count();
+        if value_list.is_empty() || low_count * 2 < value_list.len() {
+            continue
+        }
+
+        // 期間の前半と後半の平均を比べる
+        let half = value_list.len() / 2;
+        let trend = match (average(&value_list[..half]), average(&value_list[value_list.len() - half..])) {
+            (Some(first), Some(last)) if last - first > TREND_THRESHOLD => OutputCell::new("改善", "g"),
+            (Some(first), Some(last)) if first - last > TREND_THRESHOLD => OutputCell::new("悪化", "r"),
+            (Some(_), Some(_)) => OutputCell::new("横ばい", ""),
+            _ => OutputCell::new("-", "")
+        };
+
+        table.add_row(RowKind::Nutrient, vec![
+            OutputCell::new(name, ""),
+            format_percentage(average(&value_list), "r"),
+            OutputCell::new(&format!("{}/{}", low_count, value_list.len()), ""),
+            trend,
+        ]);
+    }
+
+    table
+}
+
+// (toで終わる連続した日数, 最も長い連続した日数)
+fn get_streak(date_list: &[NaiveDate], to: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut length = 0;
+    let mut last: Option<NaiveDate> = None;
+
+    for date in date_list {
+        length = match last {
+            Some(last) if *date - last == Duration::days(1) => length + 1,
+            _ => 1
+        };
+        longest = longest.max(length);
+        last = Some(*date);
+    }
+
+    let current = if last == Some(to) { length } else { 0 };
+    (current, longest)
+}
+
+// 期間内の記録を摂取基準と比べる。kijunは1日分にする
+pub fn get_report(entry_list: &[DiaryEntry], foods: &FoodTable, kijun: &Kijun,
+                  from: NaiveDate, to: NaiveDate) -> Report {
+    let day_result_list = get_day_result_list(entry_list, foods, kijun, from, to);
+    let mut report = Report::new(Some(&format!("{} 〜 {}", from.format(DATE_FORMAT), to.format(DATE_FORMAT))));
+
+    let day_count = (to - from).num_days() + 1;
+    report.add_value("logged_days",
+                     &format!("記録した日数: {}/{}日", day_result_list.len(), day_count),
+                     day_result_list.len() as f32);
+
+    let date_list: Vec<_> = day_result_list.iter().map(|day| day.date).collect();
+    let (current, longest) = get_streak(&date_list, to);
+    report.add_value("logging_streak",
+                     &format!("記録の連続日数: {}日（最長{}日）", current, longest),
+                     current as f32);
+
+    let achieved_date_list: Vec<_> = day_result_list.iter()
+        .filter(|day| day.achievement.is_some_and(|a| a >= LOW_PERCENTAGE))
+        .map(|day| day.date)
+        .collect();
+    let (current, longest) = get_streak(&achieved_date_list, to);
+    report.add_value("achievement_streak",
+                     &format!("達成率{}%以上の連続日数: {}日（最長{}日）", LOW_PERCENTAGE, current, longest),
+                     current as f32);
+
+    report.add_table("daily", Some("日ごとの摂取基準に対する割合"), get_daily_table(&day_result_list));
+    report.add_table("weekly", Some("週ごとの平均"), get_weekly_table(&day_result_list));
+    report.add_table("low_nutrients",
+                     Some(&format!("記録した日の半分以上で{}%に満たない栄養素", LOW_PERCENTAGE)),
+                     get_low_nutrient_table(&day_result_list));
+
+    report
+}
+
+
+#[cfg(test)]
+use crate::kijun::{Gender, PAL};
+
+#[cfg(test)]
+fn get_test_entry(date: &str, number: &str, weight: f32) -> DiaryEntry {
+    DiaryEntry { date: parse_date(date).unwrap(), meal: None, number: number.to_string(), weight }
+}
+
+#[test]
+fn test_diary_entry_json() {
+    let entry = DiaryEntry {
+        date: parse_date("2020-01-31").unwrap(),
+        meal: Some("朝食".to_string()),
+        number: "01001".to_string(),
+        weight: 50.0,
+    };
+    let value = entry.to_json();
+    assert_eq!(value["date"], "2020-01-31");
+    assert_eq!(DiaryEntry::from_json(&value).unwrap(), entry);
+    assert!(parse_date("2020/01/31").is_err());
+    assert!(DiaryEntry::from_json(&serde_json::json!({"date": "2020-01-31"})).is_err());
+
+    let entry = DiaryEntry { weight: 12.3, ..entry };
+    assert_eq!(entry.to_json()["weight"].to_string(), "12.3");
+}
+
+#[test]
+fn test_diary_append_and_read() {
+    let path = std::env::temp_dir().join("meal_test_diary").join("diary.jsonl");
+    let _ = std::fs::remove_file(&path);
+    assert!(read(&path).unwrap().is_empty());
+
+    append(&path, &get_test_entry("2020-01-01", "01001", 50.0)).unwrap();
+    append(&path, &get_test_entry("2020-01-02", "01002", 80.0)).unwrap();
+    let entry_list = read(&path).unwrap();
+    assert_eq!(entry_list.len(), 2);
+    assert_eq!(entry_list[1].number, "01002");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_diary_find_food() {
+    let foods = FoodTable::from_json("./test/test_foods.json").unwrap();
+    assert_eq!(find_food(&foods, "01001"), Ok("01001".to_string()));
+    assert_eq!(find_food(&foods, "あわもち"), Ok("01003".to_string()));
+    assert!(find_food(&foods, "存在しない食品").is_err());
+}
+
+#[test]
+fn test_diary_get_streak() {
+    let date_list: Vec<_> = ["2020-01-01", "2020-01-02", "2020-01-03", "2020-01-05", "2020-01-06"]
+        .iter().map(|date| parse_date(date).unwrap()).collect();
+    assert_eq!(get_streak(&date_list, parse_date("2020-01-06").unwrap()), (2, 3));
+    assert_eq!(get_streak(&date_list, parse_date("2020-01-07").unwrap()), (0, 3));
+    assert_eq!(get_streak(&[], parse_date("2020-01-07").unwrap()), (0, 0));
+}
+
+#[test]
+fn test_diary_get_report() {
+    let foods = FoodTable::from_json("./test/test_foods.json").unwrap();
+    let kijun = Kijun::new(20, 50.0, 160.0, Gender::Male, PAL::Low, 1);
+    let entry_list = vec![
+        get_test_entry("2020-01-05", "01001", 50.0),
+        get_test_entry("2020-01-06", "01002", 80.0),
+        get_test_entry("2020-01-06", "01001", 30.0),
+        // 期間の外
+        get_test_entry("2020-01-10", "01001", 30.0),
+    ];
+    let report = get_report(&entry_list, &foods, &kijun,
+                            parse_date("2020-01-01").unwrap(), parse_date("2020-01-07").unwrap());
+
+    let daily = report.get_table("daily").unwrap();
+    assert_eq!(daily.row_list.len(), 2);
+    assert_eq!(daily.row_list[1].cell_list[0].text, "2020-01-06");
+    // 2020-01-05は日曜日なので週が分かれる
+    let weekly = report.get_table("weekly").unwrap();
+    assert_eq!(weekly.row_list.len(), 2);
+    assert_eq!(weekly.row_list[1].cell_list[0].text, "2020-01-06〜");
+    // 少しの量しか食べていないのでエネルギーは不足している
+    let low = report.get_table("low_nutrients").unwrap();
+    assert_eq!(low.row_list[0].cell_list[0].text, "エネルギー");
+    assert_eq!(low.row_list[0].cell_list[2].text, "2/2");
+}
@@ -0,0 +1,228 @@
+use crate::food::nutrient::Nutrient;
+use crate::food_table::FoodTable;
+use crate::kijun::{Kijun, KijunValue};
+use crate::output::{number_to_json, OutputCell, OutputTable, RowKind};
+
+// 食塩相当量(g)をナトリウム(mg)にする係数
+const SALT_TO_SODIUM: f32 = 1000.0 / 2.54;
+// 食事ごとの炭水化物が平均からこの割合(%)より離れていれば偏っているとする
+const CARBOHYDRATE_DEVIATION: f32 = 20.0;
+
+// 食事療法のプロファイル。摂取基準の一部の項目を変更する
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DietProfile {
+    // 慢性腎臓病
+    Ckd,
+    // 糖尿病
+    Diabetes,
+    // 高血圧
+    Hypertension,
+}
+
+const PROFILE_LIST: [DietProfile; 3] = [DietProfile::Ckd, DietProfile::Diabetes, DietProfile::Hypertension];
+
+impl DietProfile {
+    pub fn from_str(text: &str) -> Result<DietProfile, String> {
+        match PROFILE_LIST.iter().find(|profile| profile.to_str() == text) {
+            Some(profile) => Ok(*profile),
+            None => Err(format!("{} というプロファイルはありません。ckd, diabetes, hypertension のいずれかにしてください", text))
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            DietProfile::Ckd => "ckd",
+            DietProfile::Diabetes => "diabetes",
+            DietProfile::Hypertension => "hypertension",
+        }
+    }
+
+    pub fn get_description(self) -> &'static str {
+        match self {
+            DietProfile::Ckd => "慢性腎臓病: たんぱく質、カリウム、リン、食塩を制限する",
+            DietProfile::Diabetes => "糖尿病: 炭水化物をエネルギーの50~60%にし、食物繊維を増やす。\
+                                      炭水化物は毎食に均等に配分する（foodsのclassを食事として比べる）",
+            DietProfile::Hypertension => "高血圧: 食塩相当量を6g未満にし、カリウムを増やす",
+        }
+    }
+
+    // 変更する項目と1日分の値。エネルギーや体重から計算する値はkijunから求める
+    fn get_target_list(self, kijun: &Kijun) -> Vec<(&'static str, KijunValue)> {
+        // 1日分のエネルギー
+        let energy = kijun.get("エネルギー").map(|energy| energy.get_reference() / kijun.days as f32);
+
+        let mut target_list = Vec::new();
+        match self {
+            DietProfile::Ckd => {
+                // ステージ3以降を想定して、体重1kgあたり0.6~0.8g
+                target_list.push(("たんぱく質", KijunValue::Range((0.6 * kijun.weight, 0.8 * kijun.weight))));
+                target_list.push(("カリウム", KijunValue::Less(2000.0)));
+                target_list.push(("リン", KijunValue::Less(800.0)));
+                target_list.push(("食塩相当量", KijunValue::Range((3.0, 6.0))));
+                target_list.push(("ナトリウム", KijunValue::Range((3.0 * SALT_TO_SODIUM, 6.0 * SALT_TO_SODIUM))));
+            },
+            DietProfile::Diabetes => {
+                if let Some(energy) = energy {
+                    target_list.push(("炭水化物", KijunValue::Range((energy * 0.5 / 4.0, energy * 0.6 / 4.0))));
+                    target_list.push(("飽和脂肪酸", KijunValue::Less(energy * 0.07 / 9.0)));
+                }
+                target_list.push(("食物繊維総量", KijunValue::More(20.0)));
+            },
+            DietProfile::Hypertension => {
+                target_list.push(("食塩相当量", KijunValue::Less(6.0)));
+                target_list.push(("ナトリウム", KijunValue::Less(6.0 * SALT_TO_SODIUM)));
+                target_list.push(("カリウム", KijunValue::More(3500.0)));
+            },
+        }
+
+        target_list
+    }
+
+    // プロファイルの値で摂取基準を変更する
+    pub fn apply(self, kijun: &mut Kijun) {
+        for (key, kijun_value) in self.get_target_list(kijun) {
+            kijun.set_override(key, kijun_value, self.to_str());
+        }
+    }
+
+    // 摂取基準の値では確かめられない項目の表。確かめる項目がなければNone
+    pub fn get_check_table(self, food_table: &FoodTable) -> Option<OutputTable> {
+        match self {
+            DietProfile::Diabetes => get_carbohydrate_distribution_table(food_table),
+            _ => None
+        }
+    }
+
+    // プロファイルの一覧の表
+    pub fn get_list_table() -> OutputTable {
+        let mut table = OutputTable::new(
+            ["名前", "説明"].iter().map(|c| c.to_string()).collect(),
+            vec![String::new(); 2],
+            false);
+        for profile in PROFILE_LIST.iter() {
+            table.add_row(RowKind::Food, vec![
+                OutputCell::new(profile.to_str(), ""),
+                OutputCell::new(profile.get_description(), ""),
+            ]);
+        }
+
+        table
+    }
+}
+
+// 食事（クラス）ごとの炭水化物と、食事の平均に対する割合。食事が2つ以上なければNone
+fn get_carbohydrate_distribution_table(food_table: &FoodTable) -> Option<OutputTable> {
+    let mut class_list: Vec<_> = food_table.split_by_class().into_iter()
+        .map(|(class_name, class_food_table)| {
+            let sum = class_food_table.get_sum();
+            let carbohydrate = sum.get_nutrient(Nutrient::Carbohydrate).get_number().copied().unwrap_or(0.0);
+            (class_name, carbohydrate)
+        }).collect();
+    if class_list.len() < 2 {
+        return None
+    }
+    // 出力が毎回同じになるようにクラス名の順にする
+    class_list.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let average = class_list.iter().map(|(_, carbohydrate)| carbohydrate).sum::<f32>() / class_list.len() as f32;
+    let mut table = OutputTable::new(
+        ["食事", "炭水化物", "平均に対する割合", "判定"].iter().map(|c| c.to_string()).collect(),
+        vec![String::new(), "g".to_string(), "%".to_string(), String::new()],
+        false);
+    for (class_name, carbohydrate) in class_list {
+        let (ratio, judge) = if average > 0.0 {
+            let ratio = carbohydrate / average * 100.0;
+            let judge = if (ratio - 100.0).abs() > CARBOHYDRATE_DEVIATION { "偏り" } else { "適正" };
+            (OutputCell::with_value(&format!("{:.0}", ratio), "", number_to_json(ratio)), judge)
+        } else {
+            (OutputCell::with_value("-", "", serde_json::Value::Null), "-")
+        };
+        table.add_row(RowKind::ClassShare, vec![
+            OutputCell::new(&class_name, ""),
+            OutputCell::with_value(&format!("{:.1}", carbohydrate), "", number_to_json(carbohydrate)),
+            ratio,
+            OutputCell::new(judge, if judge == "偏り" { "r+" } else { "" }),
+        ]);
+    }
+
+    Some(table)
+}
+
+
+#[cfg(test)]
+fn get_test_kijun() -> Kijun {
+    use crate::kijun::{Gender, PAL};
+    Kijun::new(50, 60.0, 165.0, Gender::Male, PAL::Low, 1)
+}
+
+#[test]
+fn test_diet_profile_from_str() {
+    assert_eq!(DietProfile::from_str("ckd").unwrap(), DietProfile::Ckd);
+    assert!(DietProfile::from_str("none").is_err());
+}
+
+#[test]
+fn test_diet_profile_get_list_table() {
+    let table = DietProfile::get_list_table();
+    assert_eq!(table.row_list.len(), PROFILE_LIST.len());
+    assert_eq!(table.to_json()["rows"][0]["values"]["名前"], PROFILE_LIST[0].to_str());
+}
+
+#[test]
+fn test_diet_profile_apply() {
+    let mut kijun = get_test_kijun();
+    DietProfile::Ckd.apply(&mut kijun);
+    assert_eq!(kijun.get("カリウム").unwrap().to_string(), "- 2000");
+    assert_eq!(kijun.get("たんぱく質").unwrap().to_string(), "36 ~ 48");
+    assert_eq!(kijun.get_override("カリウム").unwrap().0, "ckd");
+
+    // 後のプロファイルの値で上書きする
+    DietProfile::Hypertension.apply(&mut kijun);
+    assert_eq!(kijun.get("食塩相当量").unwrap().to_string(), "- 6");
+    assert_eq!(kijun.get_override("食塩相当量").unwrap().0, "hypertension");
+
+    let mut kijun = get_test_kijun();
+    let energy = kijun.get("エネルギー").unwrap().get_reference();
+    DietProfile::Diabetes.apply(&mut kijun);
+    match kijun.get("炭水化物").unwrap() {
+        KijunValue::Range((min, max)) => {
+            assert_eq!(*min, energy * 0.5 / 4.0);
+            assert_eq!(*max, energy * 0.6 / 4.0);
+        },
+        _ => panic!("炭水化物は範囲にする")
+    }
+}
+
+#[test]
+fn test_diet_profile_get_check_table() {
+    use crate::food::Food;
+    use crate::food::food_data::FoodData;
+
+    let get_food = |number: &str, class: &str, carbohydrate: f32| {
+        let mut food = Food::new();
+        food.set("食品番号", FoodData::String(number.to_string()));
+        food.set("クラス", FoodData::String(class.to_string()));
+        food.set("炭水化物", FoodData::Number(carbohydrate));
+        food
+    };
+    let mut food_table = FoodTable::new();
+    food_table.add(get_food("01001", "朝食", 30.0));
+    food_table.add(get_food("01002", "昼食", 55.0));
+    food_table.add(get_food("01003", "夕食", 45.0));
+    food_table.add(get_food("01004", "夕食", 20.0));
+
+    let table = DietProfile::Diabetes.get_check_table(&food_table).unwrap();
+    let row_list: Vec<Vec<_>> = table.row_list.iter()
+        .map(|row| row.cell_list.iter().map(|cell| cell.text.as_str()).collect()).collect();
+    // 平均は50g
+    assert_eq!(row_list, vec![
+        vec!["夕食", "65.0", "130", "偏り"],
+        vec!["昼食", "55.0", "110", "適正"],
+        vec!["朝食", "30.0", "60", "偏り"],
+    ]);
+    assert!(DietProfile::Ckd.get_check_table(&food_table).is_none());
+
+    // 食事が1つだけなら比べない
+    let food_table = food_table.get_list(&["01001"]);
+    assert!(DietProfile::Diabetes.get_check_table(&food_table).is_none());
+}
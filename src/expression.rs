@@ -0,0 +1,348 @@
+use crate::food::Food;
+use crate::food::food_data::TraceMode;
+use crate::food::nutrient::{Nutrient, NUTRIENT_LIST};
+use crate::query::{parse_per, split_amount};
+
+// name_listで使う計算式の列
+// 例: "P比=たんぱく質*4/エネルギー*100"
+//     "Na/K"
+//     "食塩相当量/1000kcal"（1000kcalはエネルギーを1000で割った値になる）
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Number(f32),
+    // "1000kcal"のような量。基準の項目の値を量で割った値になる
+    Amount(f32, Nutrient),
+    Name(Nutrient),
+    Operator(char),
+    Open,
+    Close,
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Number(f32),
+    Amount(f32, Nutrient),
+    Name(Nutrient),
+    Negative(Box<Node>),
+    Binary(char, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Expression {
+    label: Option<String>,
+    node: Node,
+}
+
+// textの先頭に一致する項目名のうち一番長いもの
+// α-トコフェロールのように-を含む名前があるので、演算子より先に調べる
+fn match_name(text: &str) -> Option<(usize, Nutrient)> {
+    let mut result: Option<(usize, Nutrient)> = None;
+
+    for nutrient in NUTRIENT_LIST.iter() {
+        let names = std::iter::once(nutrient.get_name()).chain(nutrient.get_aliases().iter().copied());
+        for name in names {
+            let is_longer = match result {
+                Some((len, _)) => len < name.len(),
+                None => true
+            };
+            if is_longer && text.starts_with(name) {
+                result = Some((name.len(), *nutrient));
+            }
+        }
+    }
+
+    result
+}
+
+fn get_nutrient(name: &str) -> Result<Nutrient, String> {
+    match Nutrient::from_name(name) {
+        Some(nutrient) => Ok(nutrient),
+        None => Err(format!("{}という項目はありません", name))
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut token_list = Vec::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue
+        }
+
+        if let Some((len, nutrient)) = match_name(rest) {
+            token_list.push(Token::Name(nutrient));
+            rest = &rest[len..];
+            continue
+        }
+
+        match c {
+            '+' | '-' | '*' | '/' => {
+                token_list.push(Token::Operator(c));
+                rest = &rest[1..];
+            },
+            '(' | '（' => {
+                token_list.push(Token::Open);
+                rest = &rest[c.len_utf8()..];
+            },
+            ')' | '）' => {
+                token_list.push(Token::Close);
+                rest = &rest[c.len_utf8()..];
+            },
+            '0'..='9' | '.' => {
+                let (amount, after) = split_amount(rest);
+                let num = match amount.parse::<f32>() {
+                    Ok(num) => num,
+                    Err(_) => return Err(format!("{}は数値として読み込めません", amount))
+                };
+                // 数値の直後に単位があれば量として扱う
+                let unit_len = after.find(|c: char| !(c.is_ascii_alphabetic() || c == '円'))
+                    .unwrap_or(after.len());
+                let unit = &after[..unit_len];
+                if unit.is_empty() {
+                    token_list.push(Token::Number(num));
+                } else {
+                    let (amount, base) = parse_per(amount, unit)?;
+                    token_list.push(Token::Amount(amount, base));
+                }
+                rest = &after[unit_len..];
+            },
+            // 項目名は"..."か「...」で囲むこともできる
+            '"' | '「' => {
+                let end = if c == '"' { '"' } else { '」' };
+                let inner = &rest[c.len_utf8()..];
+                let close = match inner.find(end) {
+                    Some(close) => close,
+                    None => return Err(format!("{}が閉じられていません", c))
+                };
+                token_list.push(Token::Name(get_nutrient(&inner[..close])?));
+                rest = &inner[close + end.len_utf8()..];
+            },
+            // NaやKのような英語名とタグ名
+            _ => {
+                let len = rest.find(|c: char| c.is_whitespace() || "+-*/()（）".contains(c))
+                    .unwrap_or(rest.len());
+                token_list.push(Token::Name(get_nutrient(&rest[..len])?));
+                rest = &rest[len..];
+            }
+        }
+    }
+
+    Ok(token_list)
+}
+
+struct Parser {
+    token_list: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.token_list.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.token_list.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_expr(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_term()?;
+        while let Some(Token::Operator(op)) = self.peek() {
+            let op = *op;
+            if op != '+' && op != '-' {
+                break
+            }
+            self.next();
+            node = Node::Binary(op, Box::new(node), Box::new(self.parse_term()?));
+        }
+        Ok(node)
+    }
+
+    fn parse_term(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_factor()?;
+        while let Some(Token::Operator(op)) = self.peek() {
+            let op = *op;
+            if op != '*' && op != '/' {
+                break
+            }
+            self.next();
+            node = Node::Binary(op, Box::new(node), Box::new(self.parse_factor()?));
+        }
+        Ok(node)
+    }
+
+    fn parse_factor(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Number(num)) => Ok(Node::Number(num)),
+            Some(Token::Amount(amount, base)) => Ok(Node::Amount(amount, base)),
+            Some(Token::Name(nutrient)) => Ok(Node::Name(nutrient)),
+            Some(Token::Operator('-')) => Ok(Node::Negative(Box::new(self.parse_factor()?))),
+            Some(Token::Open) => {
+                let node = self.parse_expr()?;
+                if self.next() != Some(Token::Close) {
+                    return Err(")が足りません".to_string())
+                }
+                Ok(node)
+            },
+            Some(token) => Err(format!("{:?}の位置には値が必要です", token)),
+            None => Err("式が途中で終わっています".to_string())
+        }
+    }
+}
+
+impl Node {
+    fn evaluate(&self, food: &Food, mode: TraceMode) -> Option<f32> {
+        match self {
+            Node::Number(num) => Some(*num),
+            Node::Amount(amount, base) => {
+                Some(food.get_nutrient(*base).get_number_with(mode)? / amount)
+            },
+            Node::Name(nutrient) => food.get_nutrient(*nutrient).get_number_with(mode),
+            Node::Negative(node) => Some(-node.evaluate(food, mode)?),
+            Node::Binary(op, left, right) => {
+                let left = left.evaluate(food, mode)?;
+                let right = right.evaluate(food, mode)?;
+                match op {
+                    '+' => Some(left + right),
+                    '-' => Some(left - right),
+                    '*' => Some(left * right),
+                    _ if right == 0.0 => None,
+                    _ => Some(left / right),
+                }
+            }
+        }
+    }
+
+    // 表計算ソフトの式。cellは項目の値のセルの位置
+    fn to_formula(&self, cell: &dyn Fn(Nutrient) -> String) -> String {
+        match self {
+            Node::Number(num) => num.to_string(),
+            Node::Amount(amount, base) => format!("({}/{})", cell(*base), amount),
+            Node::Name(nutrient) => cell(*nutrient),
+            Node::Negative(node) => format!("-{}", node.to_formula(cell)),
+            Node::Binary(op, left, right) => {
+                format!("({}{}{})", left.to_formula(cell), op, right.to_formula(cell))
+            }
+        }
+    }
+
+    fn add_nutrient_list(&self, nutrient_list: &mut Vec<Nutrient>) {
+        match self {
+            Node::Number(_) => (),
+            Node::Amount(_, nutrient) | Node::Name(nutrient) => {
+                if !nutrient_list.contains(nutrient) {
+                    nutrient_list.push(*nutrient);
+                }
+            },
+            Node::Negative(node) => node.add_nutrient_list(nutrient_list),
+            Node::Binary(_, left, right) => {
+                left.add_nutrient_list(nutrient_list);
+                right.add_nutrient_list(nutrient_list);
+            }
+        }
+    }
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Expression, String> {
+        // "P比=..."のように=の前は列の名前にする
+        let (label, text) = match text.split_once('=') {
+            Some((label, text)) if !label.trim().is_empty() => (Some(label.trim().to_string()), text),
+            _ => (None, text)
+        };
+
+        let token_list = tokenize(text)?;
+        if token_list.is_empty() {
+            return Err("式が空です".to_string())
+        }
+
+        let mut parser = Parser { token_list, position: 0 };
+        let node = parser.parse_expr()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("{:?}以降の式が読み込めません", token))
+        }
+
+        Ok(Expression { label, node })
+    }
+
+    pub fn get_label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    // 値がない項目を使っている場合や0で割る場合はNone
+    pub fn evaluate(&self, food: &Food, mode: TraceMode) -> Option<f32> {
+        self.node.evaluate(food, mode)
+    }
+
+    // 表計算ソフトの式。0で割る場合はエラーになる
+    pub fn to_formula(&self, cell: &dyn Fn(Nutrient) -> String) -> String {
+        format!("={}", self.node.to_formula(cell))
+    }
+
+    // 式で使っている項目
+    pub fn get_nutrient_list(&self) -> Vec<Nutrient> {
+        let mut nutrient_list = Vec::new();
+        self.node.add_nutrient_list(&mut nutrient_list);
+        nutrient_list
+    }
+}
+
+
+#[cfg(test)]
+use crate::food::food_data::FoodData;
+
+#[test]
+fn test_expression_parse() {
+    let expression = Expression::parse("P比=たんぱく質*4/エネルギー*100").unwrap();
+    assert_eq!(expression.get_label(), Some("P比"));
+    assert_eq!(Expression::parse("Na/K").unwrap().node, Node::Binary('/',
+        Box::new(Node::Name(Nutrient::Na)), Box::new(Node::Name(Nutrient::K))));
+    assert_eq!(Expression::parse("食塩相当量/1000kcal").unwrap().node, Node::Binary('/',
+        Box::new(Node::Name(Nutrient::NaclEq)), Box::new(Node::Amount(1000.0, Nutrient::EnercKcal))));
+    // -を含む名前
+    assert_eq!(Expression::parse("α-トコフェロール-1").unwrap().node, Node::Binary('-',
+        Box::new(Node::Name(Nutrient::Tocpha)), Box::new(Node::Number(1.0))));
+    assert_eq!(Expression::parse("エネルギー（kJ)").unwrap().node, Node::Name(Nutrient::Enerc));
+
+    assert!(Expression::parse("").is_err());
+    assert!(Expression::parse("ないもの/2").is_err());
+    assert!(Expression::parse("(脂質+1").is_err());
+    assert!(Expression::parse("脂質 1").is_err());
+    assert!(Expression::parse("脂質/100個").is_err());
+}
+
+#[test]
+fn test_expression_evaluate() {
+    let mut food = Food::new();
+    food.set("エネルギー", FoodData::Number(200.0));
+    food.set("たんぱく質", FoodData::Number(10.0));
+    food.set("ナトリウム", FoodData::Number(100.0));
+    food.set("カリウム", FoodData::Number(400.0));
+    food.set("食塩相当量", FoodData::Number(0.5));
+    food.set("脂質", FoodData::Trace(0.05));
+
+    let evaluate = |text: &str| Expression::parse(text).unwrap().evaluate(&food, TraceMode::Zero);
+    assert_eq!(evaluate("P比=たんぱく質*4/エネルギー*100"), Some(20.0));
+    assert_eq!(evaluate("Na/K"), Some(0.25));
+    assert_eq!(evaluate("食塩相当量/1000kcal"), Some(2.5));
+    assert_eq!(evaluate("-(たんぱく質 + 2) * 2"), Some(-24.0));
+    assert_eq!(evaluate("たんぱく質/脂質"), None);
+    assert_eq!(evaluate("食物繊維総量+1"), None);
+}
+
+#[test]
+fn test_expression_to_formula() {
+    let cell = |nutrient: Nutrient| match nutrient {
+        Nutrient::Protein => "B2".to_string(),
+        _ => "C2".to_string()
+    };
+    let expression = Expression::parse("P比=たんぱく質*4/エネルギー*100").unwrap();
+    assert_eq!(expression.to_formula(&cell), "=(((B2*4)/C2)*100)");
+    assert_eq!(expression.get_nutrient_list(), vec![Nutrient::Protein, Nutrient::EnercKcal]);
+    let expression = Expression::parse("-たんぱく質/1000kcal").unwrap();
+    assert_eq!(expression.to_formula(&cell), "=(-B2/(C2/1000))");
+}
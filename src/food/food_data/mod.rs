@@ -1,15 +1,55 @@
 use std::str::FromStr;
 
+use serde_json::{Map, Value};
+
+use crate::output::number_to_json;
+use crate::unit::Unit;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum FoodData {
     Number(f32),
     EstimatedNumber(f32),
+    LowerBound(f32),          // 不明な値を含む合計。この値以上になる
+    EstimatedLowerBound(f32), // 推定値と不明な値を含む合計
+    Trace(f32),               // Tr。値は閾値で、含まれる量はこの値未満になる
+    EstimatedTrace(f32),      // (Tr)
     String(String),
     None,
 }
 
+// Trの値をどのように扱うか
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TraceMode {
+    Zero,      // 0として扱う
+    Half,      // 閾値の半分として扱う
+    Threshold, // 閾値として扱う
+}
+
+impl TraceMode {
+    pub fn from_str(mode: &str) -> Result<TraceMode, String> {
+        match mode {
+            "zero" => Ok(TraceMode::Zero),
+            "half" => Ok(TraceMode::Half),
+            "threshold" => Ok(TraceMode::Threshold),
+            _ => Err("traceの値は \"zero\", \"half\", \"threshold\" \
+                      のいずれかの文字列にしてください".to_string())
+        }
+    }
+}
+
 impl FoodData {
     pub fn from_str(data: &str) -> FoodData {
+        FoodData::from_str_with_threshold(data, 0.0)
+    }
+
+    // Trの閾値を指定して変換する
+    pub fn from_str_with_threshold(data: &str, threshold: f32) -> FoodData {
+        match data {
+            "Tr" => return FoodData::Trace(threshold),
+            "(Tr)" => return FoodData::EstimatedTrace(threshold),
+            _ => ()
+        }
+
         match f32::from_str(data) {
             Ok(num) => FoodData::Number(num),
             Err(_) => {
@@ -26,20 +66,64 @@ impl FoodData {
         match self {
             FoodData::Number(num) => Some(num),
             FoodData::EstimatedNumber(num) => Some(num),
+            FoodData::LowerBound(num) => Some(num),
+            FoodData::EstimatedLowerBound(num) => Some(num),
             _ => None
         }
     }
 
+    // Trの値をmodeに従って数値にする
+    pub fn get_number_with(&self, mode: TraceMode) -> Option<f32> {
+        match self {
+            FoodData::Trace(threshold) |
+            FoodData::EstimatedTrace(threshold) => match mode {
+                TraceMode::Zero => Some(0.0),
+                TraceMode::Half => Some(*threshold / 2.0),
+                TraceMode::Threshold => Some(*threshold),
+            },
+            _ => self.get_number().copied()
+        }
+    }
+
+    // 推定値を含んでいるか
+    pub fn is_estimated(&self) -> bool {
+        matches!(self, FoodData::EstimatedNumber(_) |
+                       FoodData::EstimatedLowerBound(_) |
+                       FoodData::EstimatedTrace(_))
+    }
+
+    // 不明な値を含んでいるか
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, FoodData::LowerBound(_) |
+                       FoodData::EstimatedLowerBound(_) |
+                       FoodData::String(_) |
+                       FoodData::None)
+    }
+
     pub fn rate(&self, rate: f32) -> FoodData {
         match self {
             FoodData::Number(num) => FoodData::Number(*num * rate),
             FoodData::EstimatedNumber(num) => {
                 FoodData::EstimatedNumber(*num * rate)
             },
+            FoodData::LowerBound(num) => FoodData::LowerBound(*num * rate),
+            FoodData::EstimatedLowerBound(num) => {
+                FoodData::EstimatedLowerBound(*num * rate)
+            },
+            // 閾値も重量に比例する
+            FoodData::Trace(threshold) => FoodData::Trace(*threshold * rate),
+            FoodData::EstimatedTrace(threshold) => {
+                FoodData::EstimatedTrace(*threshold * rate)
+            },
             _ => return self.clone(),
         }
     }
 
+    // fromの単位の値をtoの単位に変換する。変換できない単位の場合はNone
+    pub fn convert(&self, from: Unit, to: Unit) -> Option<FoodData> {
+        Some(self.rate(from.get_rate(to)?))
+    }
+
     pub fn to_string(&self) -> String {
         match self {
             FoodData::Number(number) => {
@@ -60,21 +144,68 @@ impl FoodData {
                     "(".to_string() + &num.to_string() + ")"
                 }
             }
+            FoodData::LowerBound(number) => {
+                "≥".to_string() + &FoodData::Number(*number).to_string()
+            },
+            FoodData::EstimatedLowerBound(number) => {
+                "≥".to_string() + &FoodData::EstimatedNumber(*number).to_string()
+            },
+            FoodData::Trace(_) => "Tr".to_string(),
+            FoodData::EstimatedTrace(_) => "(Tr)".to_string(),
             FoodData::String(string) => string.clone(),
             _ => "-".to_string()
         }
     }
 
-    pub fn add(&self, food_data: &FoodData) -> FoodData {
-        let left = self.get_number().unwrap_or(&0.0);
+    // JSONの値。数値は行によって型が変わらないように、Trや下限値と同じくオブジェクトにする
+    // kindはnumber, lower_bound, traceのいずれか。値がない場合はnull
+    pub fn to_json(&self) -> Value {
+        let (kind, key, num) = match self {
+            FoodData::Number(num) | FoodData::EstimatedNumber(num) => ("number", "value", *num),
+            FoodData::LowerBound(num) | FoodData::EstimatedLowerBound(num) => ("lower_bound", "min", *num),
+            // 含まれる量は閾値未満になる
+            FoodData::Trace(threshold) | FoodData::EstimatedTrace(threshold) => ("trace", "max", *threshold),
+            FoodData::String(string) => return Value::String(string.to_string()),
+            FoodData::None => return Value::Null,
+        };
+
+        let mut obj = Map::new();
+        obj.insert("kind".to_string(), Value::String(kind.to_string()));
+        obj.insert(key.to_string(), number_to_json(num));
+        obj.insert("estimated".to_string(), Value::Bool(self.is_estimated()));
+        Value::Object(obj)
+    }
 
-        let right = food_data.get_number().unwrap_or(&0.0);
+    // Trはmodeに従って足し、Noneは0として足すが、
+    // 推定値や不明な値が含まれていることは結果に残す
+    // 不明な値（Noneや"-"）を含む場合は下限値になる
+    pub fn add(&self, food_data: &FoodData, mode: TraceMode) -> FoodData {
+        let left = self.get_number_with(mode).unwrap_or(0.0);
+        let right = food_data.get_number_with(mode).unwrap_or(0.0);
+        let num = left + right;
 
-        FoodData::Number(*left + *right)
+        let is_estimated = self.is_estimated() || food_data.is_estimated();
+        let is_unknown = self.is_unknown() || food_data.is_unknown();
+
+        match (is_estimated, is_unknown) {
+            (false, false) => FoodData::Number(num),
+            (true, false) => FoodData::EstimatedNumber(num),
+            (false, true) => FoodData::LowerBound(num),
+            (true, true) => FoodData::EstimatedLowerBound(num),
+        }
     }
 }
 
 
+#[test]
+fn test_food_data_to_json() {
+    assert_eq!(FoodData::Number(12.3).to_json().to_string(), r#"{"estimated":false,"kind":"number","value":12.3}"#);
+    assert_eq!(FoodData::EstimatedLowerBound(5.0).to_json().to_string(), r#"{"estimated":true,"kind":"lower_bound","min":5.0}"#);
+    assert_eq!(FoodData::Trace(0.05).to_json().to_string(), r#"{"estimated":false,"kind":"trace","max":0.05}"#);
+    assert_eq!(FoodData::String("01001".to_string()).to_json(), Value::String("01001".to_string()));
+    assert_eq!(FoodData::None.to_json(), Value::Null);
+}
+
 #[test]
 fn test_food_data_from_str() {
     let food_data = FoodData::from_str("100");
@@ -96,7 +227,13 @@ fn test_food_data_from_str() {
     assert_eq!(food_data, FoodData::EstimatedNumber(0.0));
 
     let food_data = FoodData::from_str("(Tr)");
-    assert_eq!(food_data, FoodData::String("(Tr)".to_string()));
+    assert_eq!(food_data, FoodData::EstimatedTrace(0.0));
+
+    let food_data = FoodData::from_str_with_threshold("Tr", 0.05);
+    assert_eq!(food_data, FoodData::Trace(0.05));
+
+    let food_data = FoodData::from_str("-");
+    assert_eq!(food_data, FoodData::String("-".to_string()));
 }
 
 #[test]
@@ -109,6 +246,21 @@ fn test_food_data_rate() {
 
     let food_data = FoodData::EstimatedNumber(100.0);
     assert_eq!(food_data.rate(2.0), FoodData::EstimatedNumber(200.0));
+
+    let food_data = FoodData::Trace(0.5);
+    assert_eq!(food_data.rate(2.0), FoodData::Trace(1.0));
+}
+
+#[test]
+fn test_food_data_convert() {
+    let food_data = FoodData::Number(2.5);
+    assert_eq!(food_data.convert(Unit::Milligram, Unit::Microgram), Some(FoodData::Number(2500.0)));
+
+    let food_data = FoodData::EstimatedNumber(100.0);
+    assert_eq!(food_data.convert(Unit::Kcal, Unit::Kj), Some(FoodData::EstimatedNumber(418.4)));
+
+    let food_data = FoodData::Number(100.0);
+    assert_eq!(food_data.convert(Unit::Kcal, Unit::Gram), None);
 }
 
 #[test]
@@ -139,18 +291,48 @@ fn test_food_data_to_string() {
 fn test_food_data_add() {
     let left = FoodData::Number(20.0);
     let right = FoodData::Number(20.0);
-    assert_eq!(left.add(&right), FoodData::Number(40.0));
+    assert_eq!(left.add(&right, TraceMode::Zero), FoodData::Number(40.0));
 
 
     let left = FoodData::Number(20.0);
     let right = FoodData::EstimatedNumber(20.0);
-    assert_eq!(left.add(&right), FoodData::Number(40.0));
+    assert_eq!(left.add(&right, TraceMode::Zero), FoodData::EstimatedNumber(40.0));
 
     let left = FoodData::Number(20.0);
     let right = FoodData::String(String::new());
-    assert_eq!(left.add(&right), FoodData::Number(20.0));
+    assert_eq!(left.add(&right, TraceMode::Zero), FoodData::LowerBound(20.0));
 
     let left = FoodData::None;
     let right = FoodData::String(String::new());
-    assert_eq!(left.add(&right), FoodData::Number(0.0));
-}
\ No newline at end of file
+    assert_eq!(left.add(&right, TraceMode::Zero), FoodData::LowerBound(0.0));
+
+    let left = FoodData::Number(20.0);
+    let right = FoodData::Trace(0.5);
+    assert_eq!(left.add(&right, TraceMode::Zero), FoodData::Number(20.0));
+
+    let left = FoodData::Number(20.0);
+    let right = FoodData::EstimatedTrace(0.5);
+    assert_eq!(left.add(&right, TraceMode::Zero), FoodData::EstimatedNumber(20.0));
+
+    let left = FoodData::LowerBound(20.0);
+    let right = FoodData::EstimatedNumber(10.0);
+    assert_eq!(left.add(&right, TraceMode::Zero), FoodData::EstimatedLowerBound(30.0));
+}
+
+#[test]
+fn test_food_data_lower_bound_to_string() {
+    assert_eq!(&FoodData::LowerBound(20.0).to_string(), "≥20");
+    assert_eq!(&FoodData::EstimatedLowerBound(20.0).to_string(), "≥(20)");
+}
+#[test]
+fn test_food_data_add_trace_mode() {
+    let left = FoodData::Number(20.0);
+    let right = FoodData::Trace(0.5);
+    assert_eq!(left.add(&right, TraceMode::Zero), FoodData::Number(20.0));
+    assert_eq!(left.add(&right, TraceMode::Half), FoodData::Number(20.25));
+    assert_eq!(left.add(&right, TraceMode::Threshold), FoodData::Number(20.5));
+
+    let left = FoodData::Trace(0.5);
+    let right = FoodData::EstimatedTrace(0.5);
+    assert_eq!(left.add(&right, TraceMode::Threshold), FoodData::EstimatedNumber(1.0));
+}
@@ -0,0 +1,219 @@
+use crate::unit::Unit;
+
+// 食品の項目の一覧
+// 項目を追加する場合はnutrients!の表に１行追加する
+// (列挙子, 名前, [別名], 英語名, INFOODSのタグ名, 単位, 最小記載量)
+macro_rules! nutrients {
+    ($(($variant:ident, $name:expr, [$($alias:expr),*], $english:expr, $tagname:expr, $unit:expr, $minimum:expr)),* $(,)?) => {
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Nutrient {
+            $($variant),*
+        }
+
+        pub const NUTRIENT_COUNT: usize = [$(stringify!($variant)),*].len();
+
+        pub const NUTRIENT_LIST: [Nutrient; NUTRIENT_COUNT] = [$(Nutrient::$variant),*];
+
+        impl Nutrient {
+            pub fn get_name(self) -> &'static str {
+                match self {
+                    $(Nutrient::$variant => $name),*
+                }
+            }
+
+            pub fn get_aliases(self) -> &'static [&'static str] {
+                match self {
+                    $(Nutrient::$variant => &[$($alias),*]),*
+                }
+            }
+
+            pub fn get_english_name(self) -> &'static str {
+                match self {
+                    $(Nutrient::$variant => $english),*
+                }
+            }
+
+            pub fn get_tagname(self) -> Option<&'static str> {
+                match self {
+                    $(Nutrient::$variant => $tagname),*
+                }
+            }
+
+            pub fn get_unit(self) -> Unit {
+                match self {
+                    $(Nutrient::$variant => $unit),*
+                }
+            }
+
+            // 成分表の最小記載量
+            pub fn get_minimum(self) -> Option<f32> {
+                match self {
+                    $(Nutrient::$variant => $minimum),*
+                }
+            }
+        }
+    };
+}
+
+nutrients! {
+    (Class, "クラス", [], "class", None, Unit::None, None),
+    (Group, "食品群", [], "group", None, Unit::None, None),
+    (Number, "食品番号", [], "number", None, Unit::None, None),
+    (Index, "索引番号", [], "index", None, Unit::None, None),
+    (Name, "食品名", [], "name", None, Unit::None, None),
+    (Price, "価格", [], "price", None, Unit::Yen, None),
+    (Weight, "重量", [], "weight", None, Unit::Gram, None),
+    (Edible, "可食量", [], "edible", None, Unit::Gram, None),
+    (Refuse, "廃棄率", [], "refuse", Some("REFUSE"), Unit::Percent, None),
+    (EnercKcal, "エネルギー", ["エネルギー（kcal）"], "energy", Some("ENERC_KCAL"), Unit::Kcal, Some(1.0)),
+    (Enerc, "エネルギー（kJ)", [], "energy_kj", Some("ENERC"), Unit::Kj, Some(1.0)),
+    (Water, "水分", [], "water", Some("WATER"), Unit::Gram, Some(0.1)),
+    (Protein, "たんぱく質", [], "protein", Some("PROT-"), Unit::Gram, Some(0.1)),
+    (Protcaa, "アミノ酸組成によるたんぱく質", [], "protein_amino_acid", Some("PROTCAA"), Unit::Gram, Some(0.1)),
+    (Lipid, "脂質", ["lipid"], "fat", Some("FAT-"), Unit::Gram, Some(0.1)),
+    (Fatnlea, "トリアシルグリセロール当量", [], "triacylglycerol", Some("FATNLEA"), Unit::Gram, Some(0.1)),
+    (Fasat, "飽和脂肪酸", [], "saturated_fatty_acid", Some("FASAT"), Unit::Gram, Some(0.01)),
+    (Fams, "一価不飽和脂肪酸", [], "monounsaturated_fatty_acid", Some("FAMS"), Unit::Gram, Some(0.01)),
+    (Fapu, "多価不飽和脂肪酸", [], "polyunsaturated_fatty_acid", Some("FAPU"), Unit::Gram, Some(0.01)),
+    (Chole, "コレステロール", [], "cholesterol", Some("CHOLE"), Unit::Milligram, Some(1.0)),
+    (Carbohydrate, "炭水化物", [], "carbohydrate", Some("CHOCDF-"), Unit::Gram, Some(0.1)),
+    (Choavlm, "利用可能炭水化物（単糖当量）", [], "available_carbohydrate", Some("CHOAVLM"), Unit::Gram, Some(0.1)),
+    (Fibsol, "水溶性食物繊維", [], "soluble_fiber", Some("FIBSOL"), Unit::Gram, Some(0.1)),
+    (Fibins, "不溶性食物繊維", [], "insoluble_fiber", Some("FIBINS"), Unit::Gram, Some(0.1)),
+    (Fibtg, "食物繊維総量", ["食物繊維"], "fiber", Some("FIBTG"), Unit::Gram, Some(0.1)),
+    (Ash, "灰分", ["灰   分"], "ash", Some("ASH"), Unit::Gram, Some(0.1)),
+    (Na, "ナトリウム", [], "sodium", Some("NA"), Unit::Milligram, Some(1.0)),
+    (K, "カリウム", [], "potassium", Some("K"), Unit::Milligram, Some(1.0)),
+    (Ca, "カルシウム", [], "calcium", Some("CA"), Unit::Milligram, Some(1.0)),
+    (Mg, "マグネシウム", [], "magnesium", Some("MG"), Unit::Milligram, Some(1.0)),
+    (P, "リン", [], "phosphorus", Some("P"), Unit::Milligram, Some(1.0)),
+    (Fe, "鉄", [], "iron", Some("FE"), Unit::Milligram, Some(0.1)),
+    (Zn, "亜鉛", [], "zinc", Some("ZN"), Unit::Milligram, Some(0.1)),
+    (Cu, "銅", [], "copper", Some("CU"), Unit::Milligram, Some(0.01)),
+    (Mn, "マンガン", [], "manganese", Some("MN"), Unit::Milligram, Some(0.01)),
+    (Id, "ヨウ素", [], "iodine", Some("ID"), Unit::Microgram, Some(1.0)),
+    (Se, "セレン", [], "selenium", Some("SE"), Unit::Microgram, Some(1.0)),
+    (Cr, "クロム", [], "chromium", Some("CR"), Unit::Microgram, Some(1.0)),
+    (Mo, "モリブデン", [], "molybdenum", Some("MO"), Unit::Microgram, Some(1.0)),
+    (Retol, "レチノール", [], "retinol", Some("RETOL"), Unit::Microgram, Some(1.0)),
+    (Carta, "α-カロテン", [], "alpha_carotene", Some("CARTA"), Unit::Microgram, Some(1.0)),
+    (Cartb, "β-カロテン", [], "beta_carotene", Some("CARTB"), Unit::Microgram, Some(1.0)),
+    (Crypxb, "β-クリプトキサンチン", [], "beta_cryptoxanthin", Some("CRYPXB"), Unit::Microgram, Some(1.0)),
+    (Cartbeq, "β-カロテン当量", [], "beta_carotene_equivalent", Some("CARTBEQ"), Unit::Microgram, Some(1.0)),
+    (VitaRae, "レチノール活性当量", ["ビタミンA", "vitamin_a"], "retinol_activity_equivalent", Some("VITA_RAE"), Unit::Microgram, Some(1.0)),
+    (Vitd, "ビタミンD", [], "vitamin_d", Some("VITD"), Unit::Microgram, Some(0.1)),
+    (Tocpha, "α-トコフェロール", ["ビタミンE", "vitamin_e"], "alpha_tocopherol", Some("TOCPHA"), Unit::Milligram, Some(0.1)),
+    (Tocphb, "β-トコフェロール", [], "beta_tocopherol", Some("TOCPHB"), Unit::Milligram, Some(0.1)),
+    (Tocphg, "γ-トコフェロール", [], "gamma_tocopherol", Some("TOCPHG"), Unit::Milligram, Some(0.1)),
+    (Tocphd, "δ-トコフェロール", [], "delta_tocopherol", Some("TOCPHD"), Unit::Milligram, Some(0.1)),
+    (Vitk, "ビタミンK", [], "vitamin_k", Some("VITK"), Unit::Microgram, Some(1.0)),
+    (Thiahcl, "ビタミンB1", [], "vitamin_b1", Some("THIAHCL"), Unit::Milligram, Some(0.01)),
+    (Ribf, "ビタミンB2", [], "vitamin_b2", Some("RIBF"), Unit::Milligram, Some(0.01)),
+    (Nia, "ナイアシン", [], "niacin", Some("NIA"), Unit::Milligram, Some(0.1)),
+    (Vitb6a, "ビタミンB6", [], "vitamin_b6", Some("VITB6A"), Unit::Milligram, Some(0.01)),
+    (Vitb12, "ビタミンB12", [], "vitamin_b12", Some("VITB12"), Unit::Microgram, Some(0.1)),
+    (Fol, "葉酸", [], "folate", Some("FOL"), Unit::Microgram, Some(1.0)),
+    (Pantac, "パントテン酸", [], "pantothenic_acid", Some("PANTAC"), Unit::Milligram, Some(0.01)),
+    (Biot, "ビオチン", [], "biotin", Some("BIOT"), Unit::Microgram, Some(0.1)),
+    (Vitc, "ビタミンC", [], "vitamin_c", Some("VITC"), Unit::Milligram, Some(1.0)),
+    (NaclEq, "食塩相当量", [], "salt_equivalent", Some("NACL_EQ"), Unit::Gram, Some(0.1)),
+    (Alc, "アルコール", [], "alcohol", Some("ALC"), Unit::Gram, Some(0.1)),
+    (Nitra, "硝酸イオン", [], "nitrate", Some("NO3"), Unit::Gram, Some(0.1)),
+    (Thebrn, "テオブロミン", [], "theobromine", Some("THEBRN"), Unit::Gram, Some(0.1)),
+    (Caffn, "カフェイン", [], "caffeine", Some("CAFFN"), Unit::Gram, Some(0.1)),
+    (Tan, "タンニン", [], "tannin", Some("TAN"), Unit::Gram, Some(0.1)),
+    (Polyphent, "ポリフェノール", [], "polyphenol", Some("POLYPHENT"), Unit::Gram, Some(0.1)),
+    (Aceac, "酢酸", [], "acetic_acid", Some("ACEAC"), Unit::Gram, Some(0.1)),
+    (CookingOil, "調理油", [], "cooking_oil", None, Unit::Gram, Some(0.1)),
+    (Oa, "有機酸", [], "organic_acid", Some("OA"), Unit::Gram, Some(0.1)),
+    (YieldPer, "重量変化率", [], "yield", None, Unit::Percent, None),
+    (Remark, "備考", [], "remark", None, Unit::None, None),
+}
+
+// 英語名の比較では大文字と小文字、空白と-と_の違いを無視する
+fn eq_english_name(name: &str, english_name: &str) -> bool {
+    let normalize = |c: char| match c {
+        ' ' | '-' => '_',
+        c => c.to_ascii_lowercase()
+    };
+
+    name.chars().map(normalize).eq(english_name.chars())
+}
+
+impl Nutrient {
+    // 名前、別名、英語名、INFOODSのタグ名から取得する。タグ名は大文字と小文字を区別しない
+    pub fn from_name(name: &str) -> Option<Nutrient> {
+        NUTRIENT_LIST.iter().copied().find(|nutrient| {
+            nutrient.get_name() == name ||
+                nutrient.get_aliases().iter().any(|alias| {
+                    *alias == name || eq_english_name(name, alias)
+                }) ||
+                nutrient.get_tagname().is_some_and(|tagname| tagname.eq_ignore_ascii_case(name)) ||
+                eq_english_name(name, nutrient.get_english_name())
+        })
+    }
+
+    // Trの閾値。Trは最小記載量の半分未満の値なので、その半分を閾値にする
+    pub fn get_trace_threshold(self) -> Option<f32> {
+        self.get_minimum().map(|minimum| minimum / 2.0)
+    }
+
+    // 重量に比例して変わる値か
+    pub fn is_scalable(self) -> bool {
+        !matches!(self, Nutrient::Class | Nutrient::Group | Nutrient::Number |
+                        Nutrient::Index | Nutrient::Name |
+                        Nutrient::Refuse | Nutrient::YieldPer | Nutrient::Remark)
+    }
+
+    // 成分表のJSON(data/foods.json)の列にある項目か。列はNUTRIENT_LISTと同じ順番にする
+    pub fn is_in_food_json(self) -> bool {
+        !matches!(self, Nutrient::Class | Nutrient::Price | Nutrient::Weight | Nutrient::Edible)
+    }
+
+    // 合計を求める値か
+    pub fn is_summable(self) -> bool {
+        !matches!(self, Nutrient::Class | Nutrient::Group | Nutrient::Number |
+                        Nutrient::Index | Nutrient::Name | Nutrient::Refuse |
+                        Nutrient::Remark)
+    }
+}
+
+
+#[test]
+fn test_nutrient_from_name() {
+    assert_eq!(Nutrient::from_name("たんぱく質"), Some(Nutrient::Protein));
+    assert_eq!(Nutrient::from_name("エネルギー（kcal）"), Some(Nutrient::EnercKcal));
+    assert_eq!(Nutrient::from_name("灰   分"), Some(Nutrient::Ash));
+    assert_eq!(Nutrient::from_name("ENERC_KCAL"), Some(Nutrient::EnercKcal));
+    assert_eq!(Nutrient::from_name("PROT-"), Some(Nutrient::Protein));
+    assert_eq!(Nutrient::from_name("FAT-"), Some(Nutrient::Lipid));
+    assert_eq!(Nutrient::from_name("vitamin_c"), Some(Nutrient::Vitc));
+    assert_eq!(Nutrient::from_name("Vitamin B12"), Some(Nutrient::Vitb12));
+    assert_eq!(Nutrient::from_name("Vitamin A"), Some(Nutrient::VitaRae));
+    assert_eq!(Nutrient::from_name("ビタミンA"), Some(Nutrient::VitaRae));
+    assert_eq!(Nutrient::from_name("Na"), Some(Nutrient::Na));
+    assert_eq!(Nutrient::from_name("food"), None);
+}
+
+#[test]
+fn test_nutrient_list() {
+    for (index, nutrient) in NUTRIENT_LIST.iter().enumerate() {
+        assert_eq!(*nutrient as usize, index);
+        assert_eq!(Nutrient::from_name(nutrient.get_name()), Some(*nutrient));
+    }
+}
+
+#[test]
+fn test_nutrient_get_unit() {
+    assert_eq!(Nutrient::EnercKcal.get_unit(), Unit::Kcal);
+    assert_eq!(Nutrient::Vitb12.get_unit(), Unit::Microgram);
+    assert_eq!(Nutrient::Na.get_unit(), Unit::Milligram);
+    assert_eq!(Nutrient::Protein.get_unit(), Unit::Gram);
+    assert_eq!(Nutrient::Name.get_unit(), Unit::None);
+}
+
+#[test]
+fn test_nutrient_get_trace_threshold() {
+    assert_eq!(Nutrient::Thiahcl.get_trace_threshold(), Some(0.005));
+    assert_eq!(Nutrient::Name.get_trace_threshold(), None);
+}
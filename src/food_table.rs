@@ -4,15 +4,20 @@ use std::fs::File;
 use std::io::BufReader;
 use std::slice::Iter;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use serde_json::Value;
-use prettytable::{Table, Row, Cell};
 
-use crate::food::KEY_LIST as FOOD_KEY_LIST;
 use crate::food::Food;
-use crate::food::food_data::FoodData;
+use crate::food::food_data::{FoodData, TraceMode};
+use crate::food::nutrient::{Nutrient, NUTRIENT_LIST};
+use crate::search::SearchEngine;
+use crate::query::Query;
+use crate::density::Density;
+use crate::expression::Expression;
+use crate::output::{Format, OutputCell, OutputTable, Report, RowKind, number_to_json, percentage_to_json};
 use crate::kijun::KEY_LIST as KIJUN_KEY_LIST;
-use crate::kijun::{Kijun, KijunValue};
+use crate::kijun::{Kijun, KijunValue, PFC_KEY_LIST};
 
 macro_rules! value_or_error {
     ($option:expr, $error:expr) => {
@@ -23,54 +28,208 @@ macro_rules! value_or_error {
     };
 }
 
-fn color(text: &str, style: &str) -> String {
-    let mut colored_text = String::new();
-    let unset_style = "\x1b[0m";
-
-    for c in style.chars() {
-        let color_style = match c {
-            'd' => "\x1b[30m",
-            'r' => "\x1b[31m",
-            'g' => "\x1b[32m",
-            'y' => "\x1b[33m",
-            'b' => "\x1b[34m",
-            'm' => "\x1b[35m",
-            'c' => "\x1b[36m",
-            'w' => "\x1b[37m",
-            'D' => "\x1b[90m",
-            'R' => "\x1b[91m",
-            'G' => "\x1b[92m",
-            'Y' => "\x1b[93m",
-            'B' => "\x1b[94m",
-            'M' => "\x1b[95m",
-            'C' => "\x1b[96m",
-            'W' => "\x1b[97m",
-            '+' => "\x1b[1m",
-            _ => continue
-        };
+// 食品はRcで共有するので、get_listや検索の結果を作るときに食品をコピーしない
+pub struct FoodTable {
+    food_list: Vec<(String, Rc<Food>)>,
+    // 食品番号からfood_listの位置を引く
+    number_index: HashMap<String, usize>,
+    // 索引番号からfood_listの位置を引く
+    index_number_index: HashMap<String, usize>,
+    trace_mode: TraceMode,
+    // 摂取基準に対する栄養密度の列を計算するときに使う
+    kijun: Option<Rc<Kijun>>,
+}
 
-        colored_text += color_style;
+// 索引番号は"1.0"のように保存されているので"1"でも引けるようにする
+fn normalize_index_number(index_number: &str) -> String {
+    match index_number.parse::<f32>() {
+        Ok(num) if num.fract() == 0.0 => format!("{}", num as u32),
+        _ => index_number.to_string()
     }
+}
 
-    colored_text + text + unset_style
+// 食品の項目にない、計算して求める列
+enum Computed {
+    Density(Density),
+    Expression(Expression),
 }
 
-pub struct FoodTable {
-    food_list: Vec<(String, Food)>,
+impl Computed {
+    fn from_name(name: &str) -> Option<Computed> {
+        if Nutrient::from_name(name).is_some() {
+            return None
+        }
+
+        match Density::from_name(name) {
+            Some(density) => Some(Computed::Density(density)),
+            None => Expression::parse(name).ok().map(Computed::Expression)
+        }
+    }
+
+    fn calculate(&self, food: &Food, kijun: Option<&Kijun>, mode: TraceMode) -> Option<f32> {
+        match self {
+            Computed::Density(density) => density.calculate(food, kijun, mode),
+            Computed::Expression(expression) => expression.evaluate(food, mode)
+        }
+    }
+
+    fn to_formula(&self, kijun: Option<&Kijun>, cell: &dyn Fn(Nutrient) -> String) -> Option<String> {
+        match self {
+            Computed::Density(density) => density.to_formula(kijun, cell),
+            Computed::Expression(expression) => Some(expression.to_formula(cell))
+        }
+    }
+
+    fn get_nutrient_list(&self) -> Vec<Nutrient> {
+        match self {
+            Computed::Density(density) => density.get_nutrient_list(),
+            Computed::Expression(expression) => expression.get_nutrient_list()
+        }
+    }
+}
+
+// 計算した値を表示する文字列
+fn format_computed_value(value: f32) -> String {
+    if value.abs() < 10.0 {
+        format!("{:.2}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+// 表の見出しにする列の名前。計算式の列で名前が付いている場合は、その名前にする
+pub fn get_column_label(name: &str) -> String {
+    match Expression::parse(name) {
+        Ok(expression) if Nutrient::from_name(name).is_none() => {
+            expression.get_label().unwrap_or(name).to_string()
+        },
+        _ => name.to_string()
+    }
+}
+
+// 列の単位。単位がない場合は空の文字列
+pub fn get_column_unit(name: &str) -> String {
+    match Nutrient::from_name(name) {
+        Some(nutrient) => nutrient.get_unit().to_str().to_string(),
+        None => String::new()
+    }
+}
+
+// 計算して求める列で使う項目。計算して求める列でなければ空
+pub fn get_computed_nutrient_list(name: &str) -> Vec<Nutrient> {
+    match Computed::from_name(name) {
+        Some(computed) => computed.get_nutrient_list(),
+        None => Vec::new()
+    }
+}
+
+// 食品の値の摂取基準に対する割合(%)。計算できない場合は表示する文字列
+pub fn get_kijun_percentage(food: &Food, name: &str, kijun: &Kijun) -> Result<f32, String> {
+    let (food_data, kijun_value) = match (food.get(name), kijun.get(name)) {
+        (Some(food_data), Some(kijun_value)) if food_data.get_number().is_some() => (food_data, kijun_value),
+        _ => return Err("-".to_string())
+    };
+
+    // 食品の値を摂取基準の単位に合わせる
+    let converted = match (Nutrient::from_name(name), Kijun::get_unit(name)) {
+        (Some(nutrient), Some(unit)) => food_data.convert(nutrient.get_unit(), unit),
+        _ => Some(food_data.clone())
+    };
+    match converted.as_ref().and_then(|d| d.get_number()) {
+        Some(num) => Ok(kijun_value.get_percentage(*num)),
+        None => Err("単位不一致".to_string())
+    }
+}
+
+// 食事療法などで変更した摂取基準の表。変更した項目がなければNone
+pub fn get_kijun_override_table(kijun: &Kijun) -> Option<OutputTable> {
+    let key_list = kijun.get_overridden_key_list();
+    if key_list.is_empty() {
+        return None
+    }
+
+    let mut table = OutputTable::new(
+        ["項目", "単位", "標準", "変更後", "理由"].iter().map(|c| c.to_string()).collect(),
+        vec![String::new(); 5],
+        false);
+    for key in key_list {
+        let (source, original) = kijun.get_override(key).unwrap();
+        let unit = Kijun::get_unit(key).map(|unit| unit.to_str().to_string()).unwrap_or_default();
+        let get_cell = |value: Option<&KijunValue>, style| match value {
+            Some(value) => OutputCell::with_value(&value.to_string(), style, value.to_json()),
+            None => OutputCell::with_value("-", style, Value::Null)
+        };
+        table.add_row(RowKind::Kijun, vec![
+            OutputCell::new(key, ""),
+            OutputCell::new(&unit, ""),
+            get_cell(original, ""),
+            get_cell(kijun.get(key), "m+"),
+            OutputCell::new(source, ""),
+        ]);
+    }
+
+    Some(table)
 }
 
 impl FoodTable {
     pub fn new() -> FoodTable {
         FoodTable {
             food_list: Vec::new(),
+            number_index: HashMap::new(),
+            index_number_index: HashMap::new(),
+            trace_mode: TraceMode::Zero,
+            kijun: None,
         }
     }
 
+    // 設定を引き継いだ空のFoodTableを作成する
+    fn new_with_setting(&self) -> FoodTable {
+        let mut food_table = FoodTable::new();
+        food_table.trace_mode = self.trace_mode;
+        food_table.kijun = self.kijun.clone();
+        food_table
+    }
+
+    // 合計やソートでTrをどのように扱うかを設定する
+    pub fn set_trace_mode(&mut self, mode: TraceMode) {
+        self.trace_mode = mode;
+    }
+
+    pub fn set_kijun(&mut self, kijun: Rc<Kijun>) {
+        self.kijun = Some(kijun);
+    }
+
     pub fn add(&mut self, food: Food) {
-        let food_number = food.get("食品番号").unwrap().to_string();
+        self.add_rc(Rc::new(food));
+    }
+
+    // 他のFoodTableの食品を共有して追加する
+    pub fn add_rc(&mut self, food: Rc<Food>) {
+        let food_number = food.get_nutrient(Nutrient::Number).to_string();
+        self.insert_index(&food_number, &food, self.food_list.len());
         self.food_list.push((food_number, food));
     }
 
+    // 同じ番号の食品が複数ある場合は先に追加した方を引く
+    fn insert_index(&mut self, food_number: &str, food: &Food, position: usize) {
+        self.number_index.entry(food_number.to_string()).or_insert(position);
+        if let FoodData::String(index_number) = food.get_nutrient(Nutrient::Index) {
+            self.index_number_index.entry(normalize_index_number(index_number))
+                .or_insert(position);
+        }
+    }
+
+    // 並び替えた後に位置を作り直す
+    fn rebuild_index(&mut self) {
+        self.number_index.clear();
+        self.index_number_index.clear();
+        let food_list = std::mem::take(&mut self.food_list);
+        for (position, (food_number, food)) in food_list.iter().enumerate() {
+            self.insert_index(food_number, food, position);
+        }
+        self.food_list = food_list;
+    }
+
     pub fn from_json(path: &str) -> Result<FoodTable, String> {
         let mut food_table = FoodTable::new();
         let file = match File::open(path) {
@@ -89,53 +248,95 @@ impl FoodTable {
         let food_list = value_or_error!(foods.as_array(), "foods属性の値は配列にしてください");
 
 
+        let column_list: Vec<Nutrient> = NUTRIENT_LIST.iter().copied()
+            .filter(|nutrient| nutrient.is_in_food_json())
+            .collect();
         for food_data in food_list {
             let value_list = value_or_error!(food_data.as_array(), "foods属性の配列の値は配列にしてください");
+            if value_list.len() != column_list.len() {
+                return Err(format!("foods属性の配列の値は{}個の値の配列にしてください", column_list.len()))
+            }
             let mut food = Food::new();
 
-            // stringの部分をセット
-            for (value, key) in value_list[0..4].iter().zip(FOOD_KEY_LIST[1..5].iter()) {
+            for (value, nutrient) in value_list.iter().zip(column_list.iter()) {
                 let data = value_or_error!(value.as_str(), "foods属性の値が読み込めません");
-                food.set(key, FoodData::String(data.to_string()));
+                food.set_str(nutrient.get_name(), data);
             }
 
-            // それ以降をセット
-            let len = FOOD_KEY_LIST.len() - 1;
-            for (value, key) in value_list[4..len].iter().zip(FOOD_KEY_LIST[5..len+1].iter()) {
-                let data = value_or_error!(value.as_str(), "foods属性の値が読み込めません");
-                food.set(key, FoodData::from_str(data));
+            food.set_nutrient(Nutrient::Price, FoodData::String("-".to_string()));
+            food.set_nutrient(Nutrient::Weight, FoodData::Number(100.0));
+            if let Some(refuse) = food.get_nutrient(Nutrient::Refuse).get_number() {
+                let edible = 100.0 - *refuse;
+                food.set_nutrient(Nutrient::Edible, FoodData::Number(edible));
             }
-
-            food.set("価格", FoodData::String("-".to_string()));
-            food.set("重量", FoodData::Number(100.0));
-            if let Some(refuse) = food.refuse.get_number() {
-                food.set("可食量", FoodData::Number(100.0 - *refuse));
-            }            
             food_table.add(food);
         }
 
         Ok(food_table)
     }
 
-    pub fn iter(&self) -> Iter<(String, Food)> {
+    // 価格のデータ(data/price/prices.json)から100gあたりの価格を読み込む
+    // {"foods": [["01001", "食品名", "25.2"], ...]}。価格が"-"の食品はそのままにする
+    pub fn set_price_from_json(&mut self, path: &str) -> Result<(), String> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => return Err(format!("{}: {}", path, e))
+        };
+
+        let data: Value = match serde_json::from_reader(BufReader::new(file)) {
+            Ok(data) => data,
+            Err(e) => return Err(format!("{}: {}", path, e))
+        };
+
+        let price_list = value_or_error!(data.get("foods").and_then(|foods| foods.as_array()),
+                                         "価格のデータのfoods属性の値は配列にしてください");
+        for price_data in price_list {
+            let (number, price) = match price_data.as_array().map(|value| value.as_slice()) {
+                Some([Value::String(number), _, Value::String(price)]) => (number, price),
+                _ => return Err("価格のデータの値は[食品番号, 食品名, 価格]の配列にしてください".to_string())
+            };
+            let price = match price.parse::<f32>() {
+                Ok(price) => price,
+                Err(_) => continue
+            };
+
+            let position = match self.number_index.get(number) {
+                Some(position) => *position,
+                None => continue
+            };
+            let food = &mut self.food_list[position].1;
+            // 重量に合わせる
+            let weight = food.get_nutrient(Nutrient::Weight).get_number().copied().unwrap_or(100.0);
+            Rc::make_mut(food).set_nutrient(Nutrient::Price, FoodData::Number(price * weight / 100.0));
+        }
+
+        Ok(())
+    }
+
+    pub fn iter(&self) -> Iter<'_, (String, Rc<Food>)> {
         self.food_list.iter()
     }
 
     pub fn get(&self, search_key: &str) -> Option<&Food> {
-        for (key, food) in &self.food_list {
-            if key == search_key {
-                return  Some(food);
-            }
-        }
-        None
+        self.get_rc(search_key).map(|food| food.as_ref())
+    }
+
+    pub fn get_rc(&self, search_key: &str) -> Option<&Rc<Food>> {
+        let position = self.number_index.get(search_key)?;
+        Some(&self.food_list[*position].1)
+    }
+
+    pub fn get_by_index_number(&self, index_number: &str) -> Option<&Food> {
+        let position = self.index_number_index.get(&normalize_index_number(index_number))?;
+        Some(&self.food_list[*position].1)
     }
 
     pub fn get_list(&self, keys: &[&str]) -> FoodTable {
-        let mut food_table = FoodTable::new();
+        let mut food_table = self.new_with_setting();
 
         for key in keys {
-            if let Some(food) = self.get(key) {
-                food_table.add(food.clone())
+            if let Some(food) = self.get_rc(key) {
+                food_table.add_rc(Rc::clone(food))
             }
         }
 
@@ -143,10 +344,14 @@ impl FoodTable {
     }
 
     pub fn get_sum(&self) -> Food {
-        let mut sum = Food::new();
+        if self.food_list.is_empty() {
+            return Food::new();
+        }
+
+        let mut sum = Food::zero();
 
         for (_num, food) in &self.food_list {
-            sum = sum.add(&food);
+            sum.add(food, self.trace_mode);
         }
 
         sum
@@ -155,7 +360,7 @@ impl FoodTable {
     pub fn set_weight(&mut self, weight: f32) {
         for (_, food) in &mut self.food_list {
             if let Some(new_food) = food.change_weight(weight) {
-                *food = new_food;
+                *Rc::make_mut(food) = new_food;
             }
         }
     }
@@ -164,18 +369,18 @@ impl FoodTable {
         let mut class_list = HashMap::new();
         
         for (_, food) in &self.food_list {
-            let class_name = if let FoodData::String(s) = food.get("クラス").unwrap() {
+            let class_name = if let FoodData::String(s) = food.get_nutrient(Nutrient::Class) {
                 s
             } else {
                 continue
             };
             
             if !class_list.contains_key(class_name) {
-                let mut food_table = FoodTable::new();
-                food_table.add(food.clone());
+                let mut food_table = self.new_with_setting();
+                food_table.add_rc(Rc::clone(food));
                 class_list.insert(class_name.to_string(), food_table);
             } else {
-                class_list.get_mut(class_name).unwrap().add(food.clone())
+                class_list.get_mut(class_name).unwrap().add_rc(Rc::clone(food))
             }
         }
 
@@ -183,13 +388,13 @@ impl FoodTable {
     }
 
     pub fn search(&self, text: &str) -> FoodTable {
-        let mut food_table = FoodTable::new();
+        let mut food_table = self.new_with_setting();
 
         for (_key, food) in &self.food_list {
             match food.get("食品名") {
                 Some(name) => {
                     if name.to_string().find(text).is_some() {
-                        food_table.add(food.clone());
+                        food_table.add_rc(Rc::clone(food));
                     }
                 }
                 None => ()
@@ -200,14 +405,14 @@ impl FoodTable {
     }
 
     pub fn search_or(&self, text_list: &[&str]) -> FoodTable {
-        let mut food_table = FoodTable::new();
+        let mut food_table = self.new_with_setting();
 
         for (_key, food) in &self.food_list {
             match food.get("食品名") {
                 Some(name) => {
                     for text in text_list {
                         if name.to_string().find(text).is_some() {
-                            food_table.add(food.clone());
+                            food_table.add_rc(Rc::clone(food));
                             break
                         }
                     }
@@ -220,14 +425,14 @@ impl FoodTable {
     }
 
     pub fn search_and(&self, text_list: &[&str]) -> FoodTable {
-        let mut food_table = FoodTable::new();
+        let mut food_table = self.new_with_setting();
 
         for (_key, food) in &self.food_list {
             match food.get("食品名") {
                 Some(name) => {
                     let is_match = text_list.iter().all(|text| name.to_string().find(text).is_some());
                     if is_match {
-                        food_table.add(food.clone());
+                        food_table.add_rc(Rc::clone(food));
                     }
                 }
                 None => ()
@@ -237,86 +442,157 @@ impl FoodTable {
         food_table
     }
 
-    pub fn sort_ascending_order(&mut self, name: &str) {
-        self.food_list.sort_by(|(_, food_a), (_, food_b)| {
-            let a = food_a.get(name).unwrap_or(&FoodData::None).get_number().unwrap_or(&0.0);
-            let b = food_b.get(name).unwrap_or(&FoodData::None).get_number().unwrap_or(&0.0);
+    // ひらがなとカタカナ、全角と半角、誤字、別名を区別せずに検索して関連度の高い順に並べる
+    pub fn fuzzy_search(&self, text_list: &[&str], is_and: bool) -> FoodTable {
+        let engine = SearchEngine::new(self);
+        let keys = engine.search(text_list, is_and);
+        let keys: Vec<_> = keys.iter().map(|key| key.as_str()).collect();
 
-            a.partial_cmp(b).unwrap()
-        })
+        self.get_list(&keys)
+    }
+
+    // 条件式に合う食品だけを取り出す。順番はそのまま
+    pub fn filter(&self, query: &Query) -> FoodTable {
+        let mut food_table = self.new_with_setting();
+
+        for (_key, food) in &self.food_list {
+            if query.matches(food, self.trace_mode) {
+                food_table.add_rc(Rc::clone(food));
+            }
+        }
+
+        food_table
+    }
+
+    pub fn sort_ascending_order(&mut self, name: &str) {
+        self.sort_by_value(name, false)
     }
 
     pub fn sort_descending_order(&mut self, name: &str) {
+        self.sort_by_value(name, true)
+    }
+
+    // 項目の値か、栄養密度や計算式の値でソートする。値がないものは0として扱う
+    fn sort_by_value(&mut self, name: &str, is_descending: bool) {
+        let nutrient = Nutrient::from_name(name);
+        let computed = Computed::from_name(name);
+        if nutrient.is_none() && computed.is_none() {
+            return
+        }
+
+        let mode = self.trace_mode;
+        let kijun = self.kijun.clone();
+        let get_value = |food: &Food| {
+            let value = match (nutrient, &computed) {
+                (Some(nutrient), _) => food.get_nutrient(nutrient).get_number_with(mode),
+                (None, Some(computed)) => computed.calculate(food, kijun.as_deref(), mode),
+                _ => None
+            };
+            value.unwrap_or(0.0)
+        };
+
         self.food_list.sort_by(|(_, food_a), (_, food_b)| {
-            let a = food_a.get(name).unwrap_or(&FoodData::None).get_number().unwrap_or(&0.0);
-            let b = food_b.get(name).unwrap_or(&FoodData::None).get_number().unwrap_or(&0.0);
+            let a = get_value(food_a);
+            let b = get_value(food_b);
 
-            b.partial_cmp(a).unwrap()
-        })
+            if is_descending {
+                b.partial_cmp(&a).unwrap()
+            } else {
+                a.partial_cmp(&b).unwrap()
+            }
+        });
+        self.rebuild_index();
     }
 
-    pub fn get_table(&self, name_list: &[&str]) -> Table {
-        let mut table = Table::new();
-        table.set_format(*prettytable::format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+    // 表に表示する値。項目にない名前は栄養密度か計算式として計算する
+    fn get_data_text(&self, food: &Food, name: &str, computed: Option<&Computed>) -> String {
+        if let Some(food_data) = food.get(name) {
+            return food_data.to_string()
+        }
 
+        let value = computed.and_then(|c| c.calculate(food, self.kijun.as_deref(), self.trace_mode));
+        match value {
+            Some(value) => format_computed_value(value),
+            None => "-".to_string()
+        }
+    }
 
-        let header: Vec<_> = name_list.iter().map(|n| {
-            let name = n.chars()
-                .map(|c| c.to_string() + "\n").collect::<String>();
-            let mut cell = Cell::new(&name);
-            cell.align(prettytable::format::Alignment::CENTER);
-            cell
+    // 表のセル。JSONでは項目の値はFoodDataの種類に、計算した値は数値にする
+    fn get_data_cell(&self, food: &Food, name: &str, computed: Option<&Computed>, style: &'static str) -> OutputCell {
+        if let Some(food_data) = food.get(name) {
+            return OutputCell::with_value(&food_data.to_string(), style, food_data.to_json())
+        }
 
-        }).collect();
+        let value = computed.and_then(|c| c.calculate(food, self.kijun.as_deref(), self.trace_mode));
+        match value {
+            Some(value) => OutputCell::with_value(&format_computed_value(value), style, number_to_json(value)),
+            None => OutputCell::with_value("-", style, Value::Null)
+        }
+    }
 
-        table.set_titles(Row::new(header));
+    // 数値にできる値は数値で、できない値は表示する文字列で返す
+    pub fn get_number_or_text(&self, food: &Food, name: &str) -> Result<f32, String> {
+        if let Some(food_data) = food.get(name) {
+            return match food_data.get_number_with(self.trace_mode) {
+                Some(num) => Ok(num),
+                None => Err(food_data.to_string())
+            }
+        }
+
+        let computed = Computed::from_name(name);
+        match computed.and_then(|c| c.calculate(food, self.kijun.as_deref(), self.trace_mode)) {
+            Some(value) => Ok(value),
+            None => Err("-".to_string())
+        }
+    }
 
+    // 計算して求める列を表計算ソフトの式にする。cellは項目の値のセルの位置
+    // 式にできない場合はNone
+    pub fn get_formula(&self, name: &str, cell: &dyn Fn(Nutrient) -> String) -> Option<String> {
+        Computed::from_name(name)?.to_formula(self.kijun.as_deref(), cell)
+    }
+
+    pub fn get_table(&self, name_list: &[&str]) -> OutputTable {
+        let mut table = OutputTable::new(
+            name_list.iter().map(|n| get_column_label(n)).collect(),
+            name_list.iter().map(|n| get_column_unit(n)).collect(),
+            true);
+
+        let computed_list: Vec<_> = name_list.iter().map(|n| Computed::from_name(n)).collect();
         for (_, food) in &self.food_list {
-            let mut row = Vec::new();
-            for (food_data, name)
-                in food.get_list(name_list).iter().zip(name_list.iter()) {
-                let mut cell = match food_data {
-                    Some(food_data) => Cell::new(&food_data.to_string()),
-                    None => Cell::new("-")
-                };
-                if *name == "食品名" {
-                    cell.align(prettytable::format::Alignment::LEFT);
-                } else {
-                    cell.align(prettytable::format::Alignment::RIGHT);
-                }
-                row.push(cell);
-            }
-            table.add_row(Row::new(row));
+            let row = name_list.iter().zip(computed_list.iter()).map(|(name, computed)| {
+                self.get_data_cell(food, name, computed.as_ref(), "")
+            }).collect();
+            table.add_row(RowKind::Food, row);
         }
 
         table
     }
 
-    pub fn add_sum_to_table(&self, table: &mut Table, name_list: &[&str]) {
+    pub fn add_sum_to_table(&self, table: &mut OutputTable, name_list: &[&str]) {
         // 合計を追加する
         let mut row = Vec::new();
         let sum_food = self.get_sum();
-        let sum = sum_food.get_list(name_list);
-        for (name, food_data) in name_list.iter().zip(sum.iter()) {
+        for name in name_list {
             if *name == "食品名" {
-                row.push(Cell::new(&color("合計", "y+")));
+                row.push(OutputCell::new("合計", "y+"));
             } else {
-                let food_data = food_data.unwrap_or(&FoodData::None);
-                let mut cell = Cell::new(&color(&food_data.to_string(), "y+"));
-
-                cell.align(prettytable::format::Alignment::RIGHT);
-                row.push(cell);
+                // 計算式の列は各行の値の合計ではなく、合計の値から計算する
+                let computed = Computed::from_name(name);
+                row.push(self.get_data_cell(&sum_food, name, computed.as_ref(), "y+"));
             }
         }
 
-        table.add_row(Row::new(row));
+        table.add_row(RowKind::Sum, row);
     }
 
-    pub fn add_percentage_of_classes_to_table(&self, table: &mut Table, name_list: &[&str]) {
+    pub fn add_percentage_of_classes_to_table(&self, table: &mut OutputTable, name_list: &[&str]) {
         // クラスごとの割合を追加する
         let sum_food = self.get_sum();
         let sum = sum_food.get_list(name_list);
-        let class_list = self.split_by_class();
+        let mut class_list: Vec<_> = self.split_by_class().into_iter().collect();
+        // 出力が毎回同じになるようにクラス名の順にする
+        class_list.sort_by(|(a, _), (b, _)| a.cmp(b));
 
         for (class_name, class_food_table) in class_list {
             let sum_food = class_food_table.get_sum();
@@ -325,77 +601,87 @@ impl FoodTable {
 
             for (name, (class_food_data, food_data)) in name_list.iter().zip(class_sum.iter().zip(sum.iter())) {
                 if *name == "食品名" {
-                    row.push(Cell::new(&color(&format!("{}の割合", class_name), "w+")));
+                    row.push(OutputCell::new(&format!("{}の割合", class_name), "w+"));
                 } else {
-                    let data = match (class_food_data.unwrap().get_number(), food_data.unwrap().get_number()) {
+                    let class_food_data = class_food_data.unwrap_or(&FoodData::None);
+                    let food_data = food_data.unwrap_or(&FoodData::None);
+                    let cell = match (class_food_data.get_number(), food_data.get_number()) {
                         (Some(class_num), Some(num)) => {
                             let per = (*class_num / *num) * 100.0;
-                            format!("{:.0}%", per)
+                            let is_estimated = class_food_data.is_estimated() || food_data.is_estimated();
+                            OutputCell::with_value(&format!("{:.0}%", per), "w+", percentage_to_json(per, is_estimated))
                         }
-                        _ => "-".to_string()
+                        _ => OutputCell::with_value("-", "w+", Value::Null)
                     };
-                    let mut cell = Cell::new(&color(&data, "w+"));
-                    //let mut cell = Cell::new(&data);
-                    cell.align(prettytable::format::Alignment::RIGHT);
-                    row.push(cell)
+                    row.push(cell);
                 }
             }
-            table.add_row(Row::new(row));
+            table.add_row(RowKind::ClassShare, row);
         }
     }
 
-    pub fn add_kijun_to_table(&self, table: &mut Table, name_list: &[&str], kijun: &Kijun) {
+    pub fn add_kijun_to_table(&self, table: &mut OutputTable, name_list: &[&str], kijun: &Kijun) {
         // 摂取基準を追加する
         let mut row = Vec::new();
         let kijun_values = kijun.get_list(name_list);
         for (name, value) in name_list.iter().zip(kijun_values.iter()) {
             if *name == "食品名" {
-                row.push(Cell::new(&color(&format!("摂取基準値（{}日分）", kijun.days), "c+")));
+                row.push(OutputCell::new(&format!("摂取基準値（{}日分）", kijun.days), "c+"));
             } else {
-                let data = match value {
-                    Some(v) => v.to_string(),
-                    None => "-".to_string()
+                // 摂取基準の値を列の単位に合わせる
+                let converted = match (value, Nutrient::from_name(name), Kijun::get_unit(name)) {
+                    (Some(v), Some(nutrient), Some(unit)) => v.convert(unit, nutrient.get_unit()).ok_or("単位不一致"),
+                    (Some(v), _, _) => Ok(**v),
+                    (None, _, _) => Err("-")
                 };
-                let mut cell = Cell::new(&color(&data, "c+"));
-                cell.align(prettytable::format::Alignment::RIGHT);
-                row.push(cell);
+                let is_overridden = kijun.get_override(name).is_some();
+                let (data, json) = match converted {
+                    Ok(v) => {
+                        let mut json = v.to_json();
+                        if let Value::Object(obj) = &mut json {
+                            obj.insert("overridden".to_string(), Value::Bool(is_overridden));
+                        }
+                        (v.to_string(), json)
+                    },
+                    Err("-") => ("-".to_string(), Value::Null),
+                    Err(text) => (text.to_string(), Value::String(text.to_string()))
+                };
+                // 食事療法などで変更した値には*を付ける
+                match is_overridden {
+                    true => row.push(OutputCell::with_value(&format!("*{}", data), "m+", json)),
+                    false => row.push(OutputCell::with_value(&data, "c+", json))
+                }
             }
         }
 
-        table.add_row(Row::new(row));
+        table.add_row(RowKind::Kijun, row);
     }
 
     pub fn add_kijun_percentage_to_table(&self,
-                                         table: &mut Table,
+                                         table: &mut OutputTable,
                                          name_list: &[&str],
                                          kijun: &Kijun) {
         // 摂取基準に対する割合を追加する
         let mut row = Vec::new();
         let sum_food = self.get_sum();
-        let sum = sum_food.get_list(name_list);
-        let kijun_values = kijun.get_list(name_list);
-        let iter = name_list.iter().zip(kijun_values.iter()).zip(sum);
-        for ((name, kijun_value), food_data) in iter {
+        for name in name_list {
             if *name == "食品名" {
-                row.push(Cell::new(&color(&format!("摂取基準に対する割合（{}日分）", kijun.days), "g+")));
+                row.push(OutputCell::new(&format!("摂取基準に対する割合（{}日分）", kijun.days), "g+"));
             } else {
-                let data = match food_data.unwrap_or(&FoodData::None).get_number() {
-                    Some(num) => match kijun_value {
-                        Some(kijun_value) => {
-                            let per = kijun_value.get_percentage(*num);
-                            format!("{:.0}%", per)
-                        },
-                        None => "-".to_string()
+                let cell = match get_kijun_percentage(&sum_food, name, kijun) {
+                    Ok(percentage) => {
+                        let is_estimated = sum_food.get(name).is_some_and(|d| d.is_estimated());
+                        OutputCell::with_value(&format!("{:.0}%", percentage), "g+",
+                                               percentage_to_json(percentage, is_estimated))
                     },
-                    _ => "-".to_string()
+                    Err(text) if text == "-" => OutputCell::with_value(&text, "g+", Value::Null),
+                    Err(text) => OutputCell::new(&text, "g+")
                 };
-                let mut cell = Cell::new(&color(&data, "g+"));
-                cell.align(prettytable::format::Alignment::RIGHT);
                 row.push(cell);
             }
         }
 
-        table.add_row(Row::new(row));
+        table.add_row(RowKind::Percentage, row);
     }
 
 //    pub fn get_percentage_of_animal_protein(&self) -> usize {
@@ -425,48 +711,143 @@ impl FoodTable {
 //        "test get_status()".to_string()
 //    }
     
-    pub fn print(&self, name_list: &[&str]) {
-        let table = self.get_table(name_list);
-
-        table.printstd();
+    pub fn get_report(&self, name_list: &[&str]) -> Report {
+        let mut report = Report::new(None);
+        report.add_table("foods", None, self.get_table(name_list));
+        report
     }
 
-    pub fn print_with_sum(&self, name_list: &[&str]) {
+    pub fn get_report_with_sum(&self, name_list: &[&str]) -> Report {
         let mut table = self.get_table(name_list);
         self.add_sum_to_table(&mut table, name_list);
         self.add_percentage_of_classes_to_table(&mut table, name_list);
 
-        table.printstd();
+        let mut report = Report::new(None);
+        report.add_table("foods", None, table);
+        report
     }
 
-    pub fn print_with_sum_and_kijun(&self, name_list: &[&str], kijun: &Kijun) {
+    // 合計、摂取基準、摂取基準に対する割合、クラスごとの割合、
+    // 摂取基準の達成率、エネルギー産生栄養素バランスを含む
+    pub fn get_report_with_sum_and_kijun(&self, name_list: &[&str], kijun: &Kijun) -> Report {
         let mut table = self.get_table(name_list);
         self.add_sum_to_table(&mut table, name_list);
-        self.add_kijun_to_table(&mut table, name_list, &kijun);
-        self.add_kijun_percentage_to_table(&mut table, name_list, &kijun);
+        self.add_kijun_to_table(&mut table, name_list, kijun);
+        self.add_kijun_percentage_to_table(&mut table, name_list, kijun);
         self.add_percentage_of_classes_to_table(&mut table, name_list);
-        let percentage = self.percentage_of_kijun(&kijun).unwrap_or(-1.0);
-        println!("{}", color(&format!("摂取基準の達成率（{}日分）: {:.2}%", kijun.days, percentage), "g+"));
-        table.printstd();
+
+        let mut report = Report::new(None);
+        let percentage = self.percentage_of_kijun(kijun).unwrap_or(-1.0);
+        report.add_value("achievement",
+                         &format!("摂取基準の達成率（{}日分）: {:.2}%", kijun.days, percentage),
+                         percentage);
+        report.add_table("foods", None, table);
+        if let Some(pfc_table) = self.get_pfc_table(kijun) {
+            report.add_table("pfc_balance", Some("エネルギー産生栄養素バランス"), pfc_table);
+        }
+        if let Some(override_table) = get_kijun_override_table(kijun) {
+            report.add_table("kijun_overrides", Some("*は変更した摂取基準"), override_table);
+        }
+        report
     }
 
-    pub fn percentage_of_kijun(&self, kijun: &Kijun) -> Option<f32> {
-        let kijun_data_list = kijun.get_list(&KIJUN_KEY_LIST);
+    pub fn print(&self, name_list: &[&str], format: Format) {
+        self.get_report(name_list).print(format);
+    }
+
+    pub fn print_with_sum(&self, name_list: &[&str], format: Format) {
+        self.get_report_with_sum(name_list).print(format);
+    }
+
+    pub fn print_with_sum_and_kijun(&self, name_list: &[&str], kijun: &Kijun, format: Format) {
+        self.get_report_with_sum_and_kijun(name_list, kijun).print(format);
+    }
+
+    // 合計のエネルギーに占めるたんぱく質、脂質、飽和脂肪酸、炭水化物の割合
+    // エネルギーの合計がない場合はNone。値がない項目は含めない
+    pub fn get_pfc_balance(&self, age: usize) -> Option<Vec<PfcBalance>> {
         let sum = self.get_sum();
-        let sum_value_list = sum.get_list(&KIJUN_KEY_LIST);
-        let mut sum_percentage = 0.0;
+        let energy = sum.get_nutrient(Nutrient::EnercKcal).get_number_with(self.trace_mode)?;
+        if energy <= 0.0 {
+            return None
+        }
+
+        let mut balance_list = Vec::new();
+        for name in PFC_KEY_LIST.iter() {
+            let gram = match sum.get(name).and_then(|d| d.get_number_with(self.trace_mode)) {
+                Some(gram) => gram,
+                None => continue
+            };
+            let energy_per_gram = Kijun::get_energy_per_gram(name)?;
+
+            balance_list.push(PfcBalance {
+                name,
+                gram,
+                ratio: gram * energy_per_gram / energy * 100.0,
+                target: Kijun::get_energy_ratio(age, name).ok(),
+            });
+        }
+
+        Some(balance_list)
+    }
+
+    pub fn get_pfc_table(&self, kijun: &Kijun) -> Option<OutputTable> {
+        let balance_list = self.get_pfc_balance(kijun.age)?;
 
-        for (kijun_data, sum_value) in kijun_data_list.iter().zip(sum_value_list.iter()) {
-            if let &None = kijun_data { return None }
-            let kijun_data = kijun_data.unwrap();
+        let mut table = OutputTable::new(
+            ["項目", "量", "エネルギー比", "目標量", "判定"].iter().map(|c| c.to_string()).collect(),
+            ["", "g", "%", "%", ""].iter().map(|u| u.to_string()).collect(),
+            false);
+
+        for balance in balance_list {
+            let target = match balance.target {
+                Some(target) => OutputCell::with_value(&target.to_string(), "", target.to_json()),
+                None => OutputCell::with_value("-", "", Value::Null)
+            };
+            let judgement = match balance.judge() {
+                "適正" => OutputCell::new("適正", "g+"),
+                "-" => OutputCell::new("-", ""),
+                judgement => OutputCell::new(judgement, "r+")
+            };
+
+            table.add_row(RowKind::Pfc, vec![
+                OutputCell::new(balance.name, ""),
+                OutputCell::with_value(&format!("{:.1}", balance.gram), "", number_to_json(balance.gram)),
+                OutputCell::with_value(&format!("{:.1}", balance.ratio), "", number_to_json(balance.ratio)),
+                target,
+                judgement,
+            ]);
+        }
+
+        Some(table)
+    }
 
-            if let &None = sum_value { return None }
-            let sum_value = sum_value.unwrap();
+    pub fn percentage_of_kijun(&self, kijun: &Kijun) -> Option<f32> {
+        let sum = self.get_sum();
+        let mut sum_percentage = 0.0;
 
-            if let None = sum_value.get_number() { return None }
-            let num = sum_value.get_number().unwrap();
+        // 変更して追加した項目も計算に使う
+        let mut key_list: Vec<&str> = KIJUN_KEY_LIST.to_vec();
+        for key in kijun.get_overridden_key_list() {
+            if !key_list.contains(&key) {
+                key_list.push(key);
+            }
+        }
+        let mut count = 0;
+
+        for key in key_list {
+            let kijun_data = match kijun.get(key) {
+                Some(kijun_data) => kijun_data,
+                // 除いた項目は計算しない
+                None if kijun.get_override(key).is_some() => continue,
+                None => return None
+            };
+            let nutrient = Nutrient::from_name(key)?;
+            let num = sum.get_nutrient(nutrient).get_number()?;
+            // 単位が合わない場合は比較できない
+            let num = nutrient.get_unit().convert(*num, Kijun::get_unit(key)?)?;
 
-            let mut percentage = kijun_data.get_percentage(*num);
+            let mut percentage = kijun_data.get_percentage(num);
 
             percentage = match kijun_data {
                 // 範囲、以下が100%を超えた場合はマイナスにする
@@ -491,20 +872,53 @@ impl FoodTable {
             };
 
             sum_percentage += percentage;
+            count += 1;
         }
 
-        let percentage = sum_percentage / KIJUN_KEY_LIST.len() as f32;
+        if count == 0 {
+            return None
+        }
+        let percentage = sum_percentage / count as f32;
         Some(percentage)
     }
 }
 
 
+// エネルギー産生栄養素バランスの1項目
+pub struct PfcBalance {
+    pub name: &'static str,
+    pub gram: f32,
+    // 合計のエネルギーに対する割合（%）
+    pub ratio: f32,
+    pub target: Option<KijunValue>,
+}
+
+impl PfcBalance {
+    // 目標量と比べた結果
+    pub fn judge(&self) -> &'static str {
+        match self.target {
+            Some(KijunValue::Range((min, _))) if self.ratio < min => "不足",
+            Some(KijunValue::Range((_, max))) if max < self.ratio => "過剰",
+            Some(KijunValue::Less(max)) if max < self.ratio => "過剰",
+            Some(_) => "適正",
+            None => "-"
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::food::Food;
     use crate::FoodTable;
-    use crate::FoodData;
-    use crate::kijun::{Kijun, Gender, PAL};
+    use crate::food::food_data::{FoodData, TraceMode};
+    use crate::food::nutrient::Nutrient;
+    use crate::kijun::{Kijun, KijunValue, Gender, PAL};
+    use crate::kijun::KEY_LIST as KIJUN_KEY_LIST;
+    use super::{get_column_label, get_column_unit};
+    use crate::output::RowKind;
+    use std::rc::Rc;
+    use crate::query::Query;
+    use super::Computed;
 
 
     #[test]
@@ -537,6 +951,10 @@ mod test {
         }
         let food = food_table.get("01001").unwrap();
         assert_eq!(food.get("食品群").unwrap(), &FoodData::String("01".to_string()));
+        // 列の順番はNUTRIENT_LISTから決める
+        assert_eq!(food.get("エネルギー").unwrap(), &FoodData::Number(358.0));
+        assert_eq!(food.get("重量変化率").unwrap(), &FoodData::String("-".to_string()));
+        assert_eq!(food.get("備考").unwrap(), &FoodData::String("".to_string()));
     }
 
     #[test]
@@ -564,6 +982,109 @@ mod test {
         assert!(keys.contains(&&"18022".to_string()));
     }
 
+    #[test]
+    fn test_food_table_fuzzy_search() {
+        let food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
+        let result = food_table.fuzzy_search(&["ﾒﾝﾁｶﾂ"], true);
+        let keys: Vec<_> = result.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["18016", "18022"]);
+        let result = food_table.fuzzy_search(&["オートミール", "アマランサス"], false);
+        let keys: Vec<_> = result.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["01001", "01004"]);
+    }
+
+    #[test]
+    fn test_food_table_filter() {
+        let food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
+        let query = Query::parse("食品群=1 AND たんぱく質>10 AND 脂質<5").unwrap();
+        let result = food_table.filter(&query);
+        let keys: Vec<_> = result.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["01002", "01005"]);
+
+        let query = Query::parse("食品群=18").unwrap();
+        let result = food_table.fuzzy_search(&["メンチカツ"], true).filter(&query);
+        assert_eq!(result.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_food_table_density() {
+        let mut food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
+        food_table.sort_descending_order("たんぱく質 per 100kcal");
+        let food = &food_table.food_list[0].1;
+        assert_eq!(food.get("食品番号"), Some(&FoodData::String("18016".to_string())));
+
+        let computed = Computed::from_name("たんぱく質 per 100kcal");
+        let text = food_table.get_data_text(food, "たんぱく質 per 100kcal", computed.as_ref());
+        assert_eq!(text, "5.05");
+        // 摂取基準がないと計算できない
+        let density = Computed::from_name("NRF9.2");
+        assert_eq!(food_table.get_data_text(food, "NRF9.2", density.as_ref()), "-");
+
+        food_table.set_kijun(Rc::new(Kijun::new(20, 50.0, 160.0, Gender::Male, PAL::Low, 1)));
+        let food_table = food_table.get_list(&["01001"]);
+        let food = &food_table.food_list[0].1;
+        assert_ne!(food_table.get_data_text(food, "NRF9.2", density.as_ref()), "-");
+    }
+
+    #[test]
+    fn test_food_table_computed_column() {
+        let food_table = FoodTable::from_json("./test/test_foods.json").unwrap()
+            .get_list(&["01001", "01002"]);
+        let name = "P比=たんぱく質*4/エネルギー*100";
+        let computed = Computed::from_name(name);
+        let food = food_table.get("01001").unwrap();
+        assert_eq!(food_table.get_data_text(food, name, computed.as_ref()), "14.2");
+        assert_eq!(get_column_label(name), "P比");
+
+        // 合計の行は各行の値を足すのではなく合計から計算する
+        let sum = food_table.get_sum();
+        assert_eq!(food_table.get_data_text(&sum, name, computed.as_ref()), "13.2");
+
+        // 項目の名前は計算式として扱わない
+        assert!(Computed::from_name("α-トコフェロール").is_none());
+        assert!(Computed::from_name("Na/K").is_some());
+        assert!(Computed::from_name("ないもの").is_none());
+    }
+
+    #[test]
+    fn test_food_table_get_pfc_balance() {
+        let food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
+        let food_table = food_table.get_list(&["01001"]);
+        let balance_list = food_table.get_pfc_balance(30).unwrap();
+        let names: Vec<_> = balance_list.iter().map(|b| b.name).collect();
+        assert_eq!(names, vec!["たんぱく質", "脂質", "飽和脂肪酸", "炭水化物"]);
+
+        // 01001 エネルギー358kcal たんぱく質12.7g
+        let protein = &balance_list[0];
+        assert!((protein.ratio - 12.7 * 4.0 / 358.0 * 100.0).abs() < 0.001);
+        assert_eq!(protein.judge(), "適正");
+        // 炭水化物 64.9g は72.5%
+        assert_eq!(balance_list[3].judge(), "過剰");
+
+        assert!(FoodTable::new().get_pfc_balance(30).is_none());
+    }
+
+    #[test]
+    fn test_food_table_index() {
+        let food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
+        let food = food_table.get_by_index_number("2").unwrap();
+        assert_eq!(food.get("食品番号"), Some(&FoodData::String("01002".to_string())));
+        assert!(food_table.get_by_index_number("2.0").is_some());
+        assert!(food_table.get("99999").is_none());
+
+        // get_listの結果は元の食品を共有し、指定した順番に並ぶ
+        let list = food_table.get_list(&["01002", "01001"]);
+        assert!(Rc::ptr_eq(list.get_rc("01001").unwrap(), food_table.get_rc("01001").unwrap()));
+        let keys: Vec<_> = list.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["01002", "01001"]);
+
+        // 並び替えた後も引ける
+        let mut food_table = food_table;
+        food_table.sort_descending_order("たんぱく質");
+        let food = food_table.get("01001").unwrap();
+        assert_eq!(food.get("食品番号"), Some(&FoodData::String("01001".to_string())));
+    }
+
     #[test]
     fn test_food_table_get_sum() {
         let food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
@@ -572,6 +1093,35 @@ mod test {
         assert_eq!(sum.get("たんぱく質").unwrap().to_string(), "42.7");
         let sum = food_table.get_sum();
         assert_eq!(sum.get("食品名").unwrap().to_string(), "-");
+
+        // 01001と01003のアミノ酸組成によるたんぱく質は推定値
+        assert_eq!(sum.get("アミノ酸組成によるたんぱく質").unwrap().to_string(), "(39)");
+        // 01003の利用可能炭水化物は"-"なので下限値になる
+        assert!(sum.get("利用可能炭水化物（単糖当量）").unwrap().to_string().starts_with("≥"));
+    }
+
+    #[test]
+    fn test_food_table_trace_mode() {
+        // 01001のレチノール活性当量はTr、01002は(0)
+        let mut food_table = FoodTable::from_json("./test/test_foods.json").unwrap()
+            .get_list(&["01001", "01002"]);
+        let food = food_table.get("01001").unwrap();
+        assert_eq!(food.get("レチノール活性当量"), Some(&FoodData::Trace(0.5)));
+
+        let sum = food_table.get_sum();
+        assert_eq!(sum.get("レチノール活性当量"), Some(&FoodData::EstimatedNumber(0.0)));
+
+        food_table.set_trace_mode(TraceMode::Threshold);
+        let sum = food_table.get_sum();
+        assert_eq!(sum.get("レチノール活性当量"), Some(&FoodData::EstimatedNumber(0.5)));
+
+        // 取り出したFoodTableにも設定が引き継がれる
+        let sum = food_table.get_list(&["01001"]).get_sum();
+        assert_eq!(sum.get("レチノール活性当量"), Some(&FoodData::Number(0.5)));
+
+        food_table.set_trace_mode(TraceMode::Half);
+        food_table.sort_descending_order("レチノール活性当量");
+        assert_eq!(food_table.iter().next().unwrap().0, "01001");
     }
 
     #[test]
@@ -610,8 +1160,8 @@ mod test {
         let class_list = food_table.split_by_class();
         assert!(class_list.contains_key("test1"));
         assert!(class_list.contains_key("test2"));
-        assert_eq!(class_list.get("test1").unwrap().food_list, vec![("2".to_string(), f2), ("3".to_string(), f3)]);
-        assert_eq!(class_list.get("test2").unwrap().food_list, vec![("4".to_string(), f4), ("5".to_string(), f5)]);
+        assert_eq!(class_list.get("test1").unwrap().food_list, vec![("2".to_string(), Rc::new(f2)), ("3".to_string(), Rc::new(f3))]);
+        assert_eq!(class_list.get("test2").unwrap().food_list, vec![("4".to_string(), Rc::new(f4)), ("5".to_string(), Rc::new(f5))]);
     }
 
     #[test]
@@ -685,6 +1235,89 @@ mod test {
         assert_eq!(iter.next().unwrap().1.get("食品番号").unwrap(), &FoodData::String("01001".to_string()));
     }
     
+    #[test]
+    fn test_food_table_kijun_unit() {
+        // 摂取基準を比較する項目の単位が一致している
+        for key in KIJUN_KEY_LIST.iter() {
+            let nutrient = Nutrient::from_name(key).unwrap();
+            assert_eq!(Some(nutrient.get_unit()), Kijun::get_unit(key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_food_table_get_table_header() {
+        assert_eq!(get_column_label("ビタミンB12"), "ビタミンB12");
+        assert_eq!(get_column_unit("ビタミンB12"), "µg");
+        assert_eq!(get_column_unit("ENERC"), "kJ");
+        assert_eq!(get_column_unit("食品名"), "");
+    }
+
+    #[test]
+    fn test_food_table_get_report() {
+        let food_table = FoodTable::from_json("./test/test_foods.json").unwrap()
+            .get_list(&["01001", "01002"]);
+        let kijun = Kijun::new(20, 50.0, 160.0, Gender::Male, PAL::Low, 1);
+        let name_list = ["食品名", "たんぱく質"];
+        let report = food_table.get_report_with_sum_and_kijun(&name_list, &kijun);
+
+        let table = report.get_table("foods").unwrap();
+        assert_eq!(table.column_list, vec!["食品名", "たんぱく質"]);
+        assert_eq!(table.unit_list, vec!["", "g"]);
+        let kind_list: Vec<_> = table.row_list.iter().map(|r| r.kind).collect();
+        assert_eq!(&kind_list[..4], &[RowKind::Food, RowKind::Food, RowKind::Sum, RowKind::Kijun]);
+        assert_eq!(kind_list[4], RowKind::Percentage);
+        assert_eq!(table.row_list[2].cell_list[0].text, "合計");
+        assert!(report.get_table("pfc_balance").is_some());
+        assert!(report.get_table("kijun_overrides").is_none());
+
+        // 変更した摂取基準には*を付ける
+        let mut kijun = kijun;
+        crate::diet_profile::DietProfile::Ckd.apply(&mut kijun);
+        let name_list = ["食品名", "たんぱく質", "食塩相当量", "脂質"];
+        let report = food_table.get_report_with_sum_and_kijun(&name_list, &kijun);
+        let kijun_row = &report.get_table("foods").unwrap().row_list[3];
+        assert_eq!(kijun_row.cell_list[1].text, "*30 ~ 40");
+        assert_eq!(kijun_row.cell_list[2].text, "*3 ~ 6");
+        assert!(!kijun_row.cell_list[3].text.starts_with('*'));
+        let override_table = report.get_table("kijun_overrides").unwrap();
+        assert_eq!(override_table.row_list[0].cell_list[0].text, "たんぱく質");
+        assert_eq!(override_table.row_list[0].cell_list[4].text, "ckd");
+
+        // JSONの型は行ではなく列の項目で決まる
+        let name_list = ["食品番号", "食品群", "食品名", "たんぱく質", "食塩相当量"];
+        let json = food_table.get_report_with_sum_and_kijun(&name_list, &kijun).to_json();
+        let row_list = json["foods"]["rows"].as_array().unwrap();
+        assert_eq!(row_list[0]["values"]["食品番号"], "01001");
+        assert_eq!(row_list[0]["values"]["食品群"], "01");
+        assert_eq!(row_list[0]["values"]["たんぱく質"]["kind"], "number");
+        assert_eq!(row_list[2]["values"]["たんぱく質"]["kind"], "number");
+        assert_eq!(row_list[3]["values"]["たんぱく質"]["kind"], "range");
+        assert_eq!(row_list[3]["values"]["たんぱく質"]["min"].as_f64(), Some(30.0));
+        assert_eq!(row_list[3]["values"]["たんぱく質"]["overridden"], true);
+        assert_eq!(row_list[4]["values"]["たんぱく質"]["kind"], "percentage");
+        assert!(row_list[4]["values"]["たんぱく質"]["value"].is_number());
+    }
+
+    #[test]
+    fn test_food_table_set_price_from_json() {
+        // 配布している成分表と価格のデータで価格あたりの栄養密度を求められる
+        let mut food_table = FoodTable::from_json("./data/foods.json").unwrap();
+        food_table.set_price_from_json("./data/price/prices.json").unwrap();
+        assert_eq!(food_table.get("01083").unwrap().get("価格").unwrap().to_string(), "36.43");
+        assert_eq!(food_table.get("01001").unwrap().get("価格").unwrap().to_string(), "-");
+
+        let mut result = food_table.fuzzy_search(&["キャベツ"], false);
+        result.sort_descending_order("たんぱく質 per 100円");
+        let (_, food) = result.iter().next().unwrap();
+        assert!(result.get_number_or_text(food, "たんぱく質 per 100円").unwrap() > 0.0);
+
+        // 重量を変えると価格も変わる
+        food_table.set_weight(200.0);
+        let price = *food_table.get("01083").unwrap().get_nutrient(Nutrient::Price).get_number().unwrap();
+        assert!((price - 72.85375).abs() < 0.001);
+        assert!(food_table.set_price_from_json("./data/none.json").is_err());
+    }
+
     #[test]
     fn test_food_table_percentage_of_kijun() {
         let food_table = FoodTable::new();
@@ -742,5 +1375,18 @@ mod test {
         food_table.add(food.clone());
         // ナトリウムが過剰で他の栄養が完全な場合、割合が100以下になるはず
         assert!(food_table.percentage_of_kijun(&kijun).unwrap() < 100.0);
+
+        // 除いた項目は達成率に含めない
+        let mut kijun = Kijun::new(20, 50.0, 160.0, Gender::Male, PAL::Low, 1);
+        kijun.remove_override("ナトリウム", "targets");
+        assert_eq!(food_table.percentage_of_kijun(&kijun), Some(100.0));
+
+        // 追加した項目は達成率に含める
+        food.set("食塩相当量", FoodData::Number(3.0));
+        let mut food_table = FoodTable::new();
+        food_table.add(food.clone());
+        kijun.set_override("食塩相当量", KijunValue::More(6.0), "targets");
+        let percentage = food_table.percentage_of_kijun(&kijun).unwrap();
+        assert!((percentage - (100.0 * 32.0 + 50.0) / 33.0).abs() < 0.001);
     }
 }
@@ -1,12 +1,19 @@
 use std::collections::HashMap;
 
+use serde_json::{Map, Value};
+
+use crate::food::nutrient::Nutrient;
+use crate::output::{number_to_json, OutputCell, OutputTable, RowKind};
+use crate::unit::Unit;
+
 
 macro_rules! pal_match {
     ($pal:ident, $low:expr, $moderate:expr, $high:expr) => {
         match $pal {
             PAL::Low => $low,
             PAL::Moderate => $moderate,
-            PAL::High => $high
+            PAL::High => $high,
+            PAL::Value(value) => value
         }
     };
 }
@@ -30,6 +37,31 @@ pub const KEY_LIST: [&str;33] = [
     "鉄", "亜鉛", "銅", "マンガン", "ヨウ素", "セレン", "クロム", "モリブデン"
 ];
 
+// Kijun::newで求める項目。ビタミンB6はたんぱく質から求める
+const CALC_KEY_LIST: [&str; 35] = [
+    "エネルギー", "たんぱく質", "脂質", "飽和脂肪酸", "n-6系脂肪酸", "n-3系脂肪酸",
+    "多価不飽和脂肪酸", "炭水化物", "食物繊維総量", "レチノール活性当量",
+    "ビタミンD", "α-トコフェロール", "ビタミンK", "ビタミンB1", "ビタミンB2",
+    "ナイアシン", "ビタミンB6", "ビタミンB12", "葉酸", "パントテン酸", "ビオチン",
+    "ビタミンC", "ナトリウム", "カリウム", "カルシウム", "マグネシウム", "リン",
+    "鉄", "亜鉛", "銅", "マンガン", "ヨウ素", "セレン", "クロム", "モリブデン"
+];
+
+// エネルギーから求める項目
+const ENERGY_KEY_LIST: [&str; 7] = [
+    "エネルギー", "脂質", "飽和脂肪酸", "炭水化物", "ビタミンB1", "ビタミンB2", "ナイアシン"
+];
+
+// 同じ値を入れる別名の項目
+const ALIAS_LIST: [(&str, &str); 3] = [
+    ("食物繊維総量", "食物繊維"),
+    ("レチノール活性当量", "ビタミンA"),
+    ("α-トコフェロール", "ビタミンE"),
+];
+
+// エネルギー産生栄養素バランスの項目
+pub const PFC_KEY_LIST: [&str; 4] = ["たんぱく質", "脂質", "飽和脂肪酸", "炭水化物"];
+
 #[derive(Copy, Clone, Debug)]
 pub enum KijunValue {
     Suisyo(f32), // 値になるべく近い方が望ましい
@@ -114,6 +146,62 @@ impl KijunValue {
         }
     }
 
+    // JSONの値。kindはtargetsで指定するときと同じ名前にする
+    pub fn to_json(self) -> Value {
+        let (kind, value_list) = match self {
+            KijunValue::Suisyo(value) => ("suisyo", vec![("value", value)]),
+            KijunValue::Measu(value) => ("measu", vec![("value", value)]),
+            KijunValue::Less(max) => ("less", vec![("max", max)]),
+            KijunValue::More(min) => ("more", vec![("min", min)]),
+            KijunValue::Range((min, max)) => ("range", vec![("min", min), ("max", max)]),
+        };
+
+        let mut obj = Map::new();
+        obj.insert("kind".to_string(), Value::String(kind.to_string()));
+        // 単位の変換で出る端数を、表示と同じ小数点以下2桁で丸める
+        for (key, value) in value_list {
+            obj.insert(key.to_string(), number_to_json((value * 100.0).round() / 100.0));
+        }
+        Value::Object(obj)
+    }
+
+    // keyの項目の値の種類。エネルギーは目安量ではなく推定エネルギー必要量にする
+    pub fn get_kind_name(&self, key: &str) -> &'static str {
+        match self {
+            KijunValue::Suisyo(_) => "推奨量",
+            KijunValue::Measu(_) if key == "エネルギー" => "推定エネルギー必要量",
+            KijunValue::Measu(_) => "目安量",
+            KijunValue::Less(_) => "以下",
+            KijunValue::More(_) => "以上",
+            KijunValue::Range(_) => "範囲",
+        }
+    }
+
+    // 比較の基準にする値。範囲の場合は中央の値
+    pub fn get_reference(&self) -> f32 {
+        match self {
+            KijunValue::Suisyo(kijun_value) => *kijun_value,
+            KijunValue::Measu(kijun_value) => *kijun_value,
+            KijunValue::Less(kijun_value) => *kijun_value,
+            KijunValue::More(kijun_value) => *kijun_value,
+            KijunValue::Range((min, max)) => (min + max) / 2.0,
+        }
+    }
+
+    // fromの単位の値をtoの単位に変換する。変換できない単位の場合はNone
+    pub fn convert(&self, from: Unit, to: Unit) -> Option<KijunValue> {
+        let rate = from.get_rate(to)?;
+        let kijun_value = match self {
+            KijunValue::Suisyo(kijun_value) => KijunValue::Suisyo(*kijun_value * rate),
+            KijunValue::Measu(kijun_value) => KijunValue::Measu(*kijun_value * rate),
+            KijunValue::Less(kijun_value) => KijunValue::Less(*kijun_value * rate),
+            KijunValue::More(kijun_value) => KijunValue::More(*kijun_value * rate),
+            KijunValue::Range((min, max)) => KijunValue::Range((*min * rate, *max * rate)),
+        };
+
+        Some(kijun_value)
+    }
+
     pub fn change_days(&mut self, days: usize) {
         match self {
             KijunValue::Suisyo(kijun_value) => *kijun_value = *kijun_value * days as f32,
@@ -135,6 +223,13 @@ fn test_kijun_value_to_string() {
     assert_eq!(&KijunValue::Range((20.5, 30.5)).to_string(), "20.5 ~ 31");
 }
 
+#[test]
+fn test_kijun_value_to_json() {
+    assert_eq!(KijunValue::Measu(2004.5).to_json().to_string(), r#"{"kind":"measu","value":2004.5}"#);
+    assert_eq!(KijunValue::Less(7.0).to_json().to_string(), r#"{"kind":"less","max":7.0}"#);
+    assert_eq!(KijunValue::Range((45.0, 67.5)).to_json().to_string(), r#"{"kind":"range","max":67.5,"min":45.0}"#);
+}
+
 #[test]
 fn test_kijun_value_get_percentage() {
     assert_eq!(KijunValue::Suisyo(20.0).get_percentage(20.0), 100.0);
@@ -145,6 +240,19 @@ fn test_kijun_value_get_percentage() {
     assert_eq!(KijunValue::Range((20.0, 40.0)).get_percentage(30.0), 100.0);
 }
 
+#[test]
+fn test_kijun_value_get_reference() {
+    assert_eq!(KijunValue::Less(20.0).get_reference(), 20.0);
+    assert_eq!(KijunValue::Range((20.0, 40.0)).get_reference(), 30.0);
+}
+
+#[test]
+fn test_kijun_value_convert() {
+    let kijun_value = KijunValue::Range((2.0, 3.0)).convert(Unit::Milligram, Unit::Microgram);
+    assert_eq!(&kijun_value.unwrap().to_string(), "2000 ~ 3000");
+    assert!(KijunValue::Less(2.0).convert(Unit::Milligram, Unit::Kcal).is_none());
+}
+
 #[test]
 fn test_kijun_value_change_days() {
     let mut kijun_value = KijunValue::Suisyo(20.0);
@@ -159,7 +267,40 @@ fn test_kijun_value_change_days() {
 pub enum PAL {
     Low,
     Moderate,
-    High
+    High,
+    // 数値で指定した身体活動レベル
+    Value(f32)
+}
+
+// METsは座位安静時代謝量の倍数で、座位安静時代謝量は基礎代謝量のおよそ1.1倍
+const RESTING_METABOLISM_RATIO: f32 = 1.1;
+// 活動を記録していない時間は座位安静にする
+const REST_METS: f32 = 1.0;
+const MINUTES_PER_DAY: f32 = 24.0 * 60.0;
+
+// 1日の活動
+#[derive(Clone, Debug, PartialEq)]
+pub struct Activity {
+    pub name: String,
+    pub mets: f32,
+    pub minutes: f32
+}
+
+impl PAL {
+    // 1日の活動のMETsの時間加重平均から身体活動レベルを求める
+    pub fn from_activity_list(activity_list: &[Activity]) -> Result<PAL, String> {
+        let minutes: f32 = activity_list.iter().map(|activity| activity.minutes).sum();
+        if MINUTES_PER_DAY < minutes {
+            return Err(format!("活動の時間の合計が1日({}分)を超えています: {}分", MINUTES_PER_DAY, minutes))
+        }
+
+        let mets_minutes: f32 = activity_list.iter()
+            .map(|activity| activity.mets * activity.minutes)
+            .sum::<f32>() + REST_METS * (MINUTES_PER_DAY - minutes);
+        let pal = mets_minutes / MINUTES_PER_DAY * RESTING_METABOLISM_RATIO;
+
+        Ok(PAL::Value((pal * 100.0).round() / 100.0))
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -168,6 +309,56 @@ pub enum Gender {
     Male
 }
 
+// エネルギー必要量の求め方
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EnergyMethod {
+    // 国立健康・栄養研究所の式(Ganpuleの式)
+    Ganpule,
+    HarrisBenedict,
+    MifflinStJeor,
+    // 基礎代謝基準値 × 参照体重
+    Reference,
+    // 測定した基礎代謝量(kcal/日)
+    MeasuredBmr(f32),
+    // わかっている総エネルギー消費量(kcal/日)。身体活動レベルは使わない
+    MeasuredTee(f32),
+}
+
+impl EnergyMethod {
+    pub fn from_str(text: &str) -> Result<EnergyMethod, String> {
+        match text {
+            "ganpule" => Ok(EnergyMethod::Ganpule),
+            "harris-benedict" => Ok(EnergyMethod::HarrisBenedict),
+            "mifflin-st-jeor" => Ok(EnergyMethod::MifflinStJeor),
+            "reference" => Ok(EnergyMethod::Reference),
+            _ => Err(format!("{} という基礎代謝量の式はありません。ganpule, harris-benedict, \
+                              mifflin-st-jeor, reference のいずれかにしてください", text))
+        }
+    }
+
+    pub fn get_name(self) -> String {
+        match self {
+            EnergyMethod::Ganpule => "国立健康・栄養研究所の式".to_string(),
+            EnergyMethod::HarrisBenedict => "Harris-Benedictの式".to_string(),
+            EnergyMethod::MifflinStJeor => "Mifflin-St Jeorの式".to_string(),
+            EnergyMethod::Reference => "基礎代謝基準値 × 参照体重".to_string(),
+            EnergyMethod::MeasuredBmr(bmr) => format!("測定した基礎代謝量 {}kcal", bmr),
+            EnergyMethod::MeasuredTee(tee) => format!("総エネルギー消費量 {}kcal", tee),
+        }
+    }
+}
+
+// 摂取基準の値をどのように求めたか
+pub struct KijunExplanation {
+    pub key: String,
+    // 日数を反映した値。求められなかった項目と除いた項目はNone
+    pub value: Option<KijunValue>,
+    // 計算に使った値
+    pub input: String,
+    // 変更した理由か、求められなかった理由
+    pub note: String,
+}
+
 pub struct Kijun {
     pub age: usize,
     pub weight: f32,
@@ -175,7 +366,11 @@ pub struct Kijun {
     pub gender: Gender,
     pub pal: PAL,
     pub days: usize,
-    data_list: HashMap<String, KijunValue>
+    data_list: HashMap<String, KijunValue>,
+    // 食事療法などで変更した項目。(変更した理由, 変更する前の値)
+    override_list: HashMap<String, (String, Option<KijunValue>)>,
+    // エネルギーを求め直したときに計算に使った値。Noneなら体重と身長、身体活動レベルから求めた
+    energy_input: Option<String>,
 }
 
 impl Kijun {
@@ -185,177 +380,326 @@ impl Kijun {
            gender: Gender,
            pal: PAL,
            days: usize) -> Kijun {
-        let mut data_list: HashMap<String, KijunValue> = HashMap::new();
-
-        if let Ok(energy_val) = Kijun::get_energy(weight, height, age, gender, pal) {
-            data_list.insert("エネルギー".to_string(), energy_val);
-
-            let energy_val = match energy_val {
-                KijunValue::Measu(energy_val) => energy_val,
-                _ => panic!("内部的なエラーです。\
-                                 energyのKijunValueの値が間違っています")
-            };
-
-            if let Ok(lipid) = Kijun::get_lipid(age, energy_val) {
-                data_list.insert("脂質".to_string(), lipid);
-            }
-
-            if let Ok(sfa) = Kijun::get_saturated_fatty_acid(age, energy_val) {
-                data_list.insert("飽和脂肪酸".to_string(), sfa);
-            }
-
-            if let Ok(ca) = Kijun::get_carbohydrate(age, energy_val) {
-                data_list.insert("炭水化物".to_string(), ca);
-            }
+        let mut kijun = Kijun {
+            age,
+            weight,
+            height,
+            gender,
+            pal,
+            days,
+            data_list: HashMap::new(),
+            override_list: HashMap::new(),
+            energy_input: None,
+        };
 
-            if let Ok(vitamin_b1) = Kijun::get_vitamin_b1(age, energy_val) {
-                data_list.insert("ビタミンB1".to_string(), vitamin_b1);
-            }
-   
-            if let Ok(vitamin_b2) = Kijun::get_vitamin_b2(age, energy_val) {
-                data_list.insert("ビタミンB2".to_string(), vitamin_b2);
-            }
-            
-            if let Ok(niacin) = Kijun::get_niacin(age, energy_val) {
-                data_list.insert("ナイアシン".to_string(), niacin);
+        let energy = Kijun::get_energy(weight, height, age, gender, pal)
+            .map(|energy| energy.get_reference());
+        let protein = Kijun::get_protein(age, weight);
+        for key in CALC_KEY_LIST.iter() {
+            if let Ok(mut kijun_value) = kijun.calc_value(key, &energy, &protein) {
+                // 日数を反映
+                kijun_value.change_days(days);
+                kijun.insert(key, kijun_value);
             }
         }
 
-        if let Ok(protein) = Kijun::get_protein(age, weight) {
-            data_list.insert("たんぱく質".to_string(), protein);
+        kijun
+    }
 
-            if let KijunValue::More(protein_val) = protein {
-                if let Ok(vitamin_b6) = Kijun::get_vitamin_b6(age, protein_val) {
-                    data_list.insert("ビタミンB6".to_string(), vitamin_b6);
-                }
-            }
-        }
-        
-        if let Ok(n6_fatty_acid) = Kijun::get_n6_fatty_acid(age, gender) {
-            data_list.insert("n-6系脂肪酸".to_string(), n6_fatty_acid);
-        }
-        
-        if let Ok(n3_fatty_acid) = Kijun::get_n3_fatty_acid(age, gender) {
-            data_list.insert("n-3系脂肪酸".to_string(), n3_fatty_acid);
+    // 別名の項目にも同じ値を入れる
+    fn insert(&mut self, key: &str, kijun_value: KijunValue) {
+        self.data_list.insert(key.to_string(), kijun_value);
+        if let Some((_, alias)) = ALIAS_LIST.iter().find(|(k, _)| *k == key) {
+            self.data_list.insert(alias.to_string(), kijun_value);
         }
+    }
 
-        if let Ok(pufa) = Kijun::get_pufa(age, gender) {
-            data_list.insert("多価不飽和脂肪酸".to_string(), pufa);
-        }
-        
-        if let Ok(fiber) = Kijun::get_fiber(age, weight) {
-            data_list.insert("食物繊維".to_string(), fiber);
-            data_list.insert("食物繊維総量".to_string(), fiber);
+    // 1日分の値を求める。energy(kcal)とproteinは先に求めた値
+    fn calc_value(&self, key: &str,
+                  energy: &Result<f32, String>,
+                  protein: &Result<KijunValue, String>) -> Result<KijunValue, String> {
+        let (age, weight, gender) = (self.age, self.weight, self.gender);
+        match key {
+            "エネルギー" => energy.clone().map(KijunValue::Measu),
+            "脂質" => Kijun::get_lipid(age, energy.clone()?),
+            "飽和脂肪酸" => Kijun::get_saturated_fatty_acid(age, energy.clone()?),
+            "炭水化物" => Kijun::get_carbohydrate(age, energy.clone()?),
+            "ビタミンB1" => Kijun::get_vitamin_b1(age, energy.clone()?),
+            "ビタミンB2" => Kijun::get_vitamin_b2(age, energy.clone()?),
+            "ナイアシン" => Kijun::get_niacin(age, energy.clone()?),
+            "たんぱく質" => protein.clone(),
+            "ビタミンB6" => match protein.clone()? {
+                KijunValue::More(protein) => Kijun::get_vitamin_b6(age, protein),
+                _ => Err("たんぱく質が推奨量ではないのでビタミンB6を求めることができません".to_string())
+            },
+            "n-6系脂肪酸" => Kijun::get_n6_fatty_acid(age, gender),
+            "n-3系脂肪酸" => Kijun::get_n3_fatty_acid(age, gender),
+            "多価不飽和脂肪酸" => Kijun::get_pufa(age, gender),
+            "食物繊維総量" => Kijun::get_fiber(age, weight),
+            "レチノール活性当量" => Kijun::get_vitamin_a(age, weight),
+            "ビタミンD" => Kijun::get_vitamin_d(age, gender),
+            "α-トコフェロール" => Kijun::get_vitamin_e(age, gender),
+            "ビタミンK" => Kijun::get_vitamin_k(age, gender),
+            "ビタミンB12" => Kijun::get_vitamin_b12(age, gender),
+            "葉酸" => Kijun::get_folic_acid(age, gender),
+            "パントテン酸" => Kijun::get_pantothenic_acid(age, gender),
+            "ビオチン" => Kijun::get_biotin(age, gender),
+            "ビタミンC" => Kijun::get_vitamin_c(age, gender),
+            "ナトリウム" => Kijun::get_sodium(age, gender),
+            "カリウム" => Kijun::get_potassium(age, gender),
+            "カルシウム" => Kijun::get_calcium(age, gender),
+            "マグネシウム" => Kijun::get_magnesium(age, weight),
+            "リン" => Kijun::get_phosphorus(age, gender),
+            "鉄" => Kijun::get_iron(age, gender),
+            "亜鉛" => Kijun::get_zinc(age, gender),
+            "銅" => Kijun::get_copper(age, gender),
+            "マンガン" => Kijun::get_manganese(age, gender),
+            "ヨウ素" => Kijun::get_iodine(age, gender),
+            "セレン" => Kijun::get_selenium(age, gender),
+            "クロム" => Kijun::get_chromium(age, gender),
+            "モリブデン" => Kijun::get_molybdenum(age, gender),
+            _ => Err(format!("{}の摂取基準はありません", key))
         }
+    }
 
-        if let Ok(vitamin_a) = Kijun::get_vitamin_a(age, weight) {
-            data_list.insert("ビタミンA".to_string(), vitamin_a);
-            data_list.insert("レチノール活性当量".to_string(), vitamin_a);
-        }
+    pub fn get(&self, key: &str) -> Option<&KijunValue> {
+        self.data_list.get(key)
+    }
 
-        if let Ok(vitamin_d) = Kijun::get_vitamin_d(age, gender) {
-            data_list.insert("ビタミンD".to_string(), vitamin_d);
+    // エネルギーの計算に使った値の説明
+    fn get_energy_input(&self) -> String {
+        if let Some(energy_input) = &self.energy_input {
+            return energy_input.to_string()
         }
 
-        if let Ok(vitamin_e) = Kijun::get_vitamin_e(age, gender) {
-            data_list.insert("ビタミンE".to_string(), vitamin_e);
-            data_list.insert("α-トコフェロール".to_string(), vitamin_e);
+        match Kijun::get_pal(self.age, self.pal) {
+            Ok(pal) => format!("体重 {}kg, 身長 {}cm, 身体活動レベル {}", self.weight, self.height, pal),
+            Err(_) => format!("体重 {}kg, 身長 {}cm", self.weight, self.height)
         }
+    }
 
-        if let Ok(vitamin_k) = Kijun::get_vitamin_k(age, gender) {
-            data_list.insert("ビタミンK".to_string(), vitamin_k);
+    // 計算に使った値の説明
+    fn get_input_text(&self, key: &str, energy: &Result<f32, String>,
+                      protein: &Result<KijunValue, String>) -> String {
+        match key {
+            "エネルギー" => self.get_energy_input(),
+            "脂質" | "飽和脂肪酸" | "炭水化物" | "ビタミンB1" | "ビタミンB2" | "ナイアシン" => match energy {
+                Ok(energy) => format!("エネルギー {:.0}kcal", energy),
+                Err(_) => "エネルギー".to_string()
+            },
+            "たんぱく質" | "食物繊維総量" | "レチノール活性当量" | "マグネシウム" => {
+                format!("体重 {}kg", self.weight)
+            },
+            "ビタミンB6" => match protein {
+                Ok(protein) => format!("たんぱく質 {:.1}g", protein.get_reference()),
+                Err(_) => "たんぱく質".to_string()
+            },
+            _ => match self.gender {
+                Gender::Female => "性別 女性".to_string(),
+                Gender::Male => "性別 男性".to_string()
+            }
         }
+    }
 
-        if let Ok(vitamin_b12) = Kijun::get_vitamin_b12(age, gender) {
-            data_list.insert("ビタミンB12".to_string(), vitamin_b12);
-        }
+    // 項目ごとの値と求め方。求められなかった項目はその理由を付ける
+    pub fn explain(&self) -> Vec<KijunExplanation> {
+        // エネルギーは求め方や増減で変わるので、変更する前の値から1日分を求める
+        let energy = match self.get_override("エネルギー") {
+            Some((_, original)) => original.copied(),
+            None => self.get("エネルギー").copied()
+        };
+        let energy = match energy {
+            Some(energy) => Ok(energy.get_reference() / self.days as f32),
+            None => Kijun::get_energy(self.weight, self.height, self.age, self.gender, self.pal)
+                .map(|energy| energy.get_reference())
+        };
+        let protein = Kijun::get_protein(self.age, self.weight);
 
-        if let Ok(folic_acid) = Kijun::get_folic_acid(age, gender) {
-            data_list.insert("葉酸".to_string(), folic_acid);
+        let mut key_list: Vec<&str> = CALC_KEY_LIST.to_vec();
+        for key in self.get_overridden_key_list() {
+            if !key_list.contains(&key) {
+                key_list.push(key);
+            }
         }
 
-        if let Ok(pantothenic_acid) = Kijun::get_pantothenic_acid(age, gender) {
-            data_list.insert("パントテン酸".to_string(), pantothenic_acid);
-        }
+        key_list.into_iter().map(|key| {
+            let value = self.get(key).copied();
+            let note = match (self.get_override(key), &value) {
+                (Some((source, _)), Some(_)) => format!("{}で変更", source),
+                (Some((source, _)), None) => format!("{}で除いた", source),
+                (None, Some(_)) => String::new(),
+                (None, None) => match self.calc_value(key, &energy, &protein) {
+                    Err(e) => e,
+                    Ok(_) => String::new()
+                }
+            };
+            let input = if CALC_KEY_LIST.contains(&key) {
+                self.get_input_text(key, &energy, &protein)
+            } else {
+                String::new()
+            };
 
-        if let Ok(biotin) = Kijun::get_biotin(age, gender) {
-            data_list.insert("ビオチン".to_string(), biotin);
-        }
+            KijunExplanation { key: key.to_string(), value, input, note }
+        }).collect()
+    }
 
-        if let Ok(vitamin_c) = Kijun::get_vitamin_c(age, gender) {
-            data_list.insert("ビタミンC".to_string(), vitamin_c);
+    // 摂取基準の表。is_explainedなら求め方と求められなかった項目も表示する
+    pub fn get_table(&self, is_explained: bool) -> OutputTable {
+        let mut column_list = vec!["項目", "種類", "値", "単位"];
+        if is_explained {
+            column_list.append(&mut vec!["年齢区分", "計算に使った値", "日数", "備考"]);
         }
+        let mut table = OutputTable::new(
+            column_list.iter().map(|c| c.to_string()).collect(),
+            vec![String::new(); column_list.len()],
+            false);
+
+        let age_band = Kijun::get_age_band(self.age);
+        for explanation in self.explain() {
+            if !is_explained && explanation.value.is_none() {
+                continue
+            }
 
-        if let Ok(sodium) = Kijun::get_sodium(age, gender) {
-            data_list.insert("ナトリウム".to_string(), sodium);
-        }
+            let style = match (&explanation.value, self.get_override(&explanation.key)) {
+                (_, Some(_)) => "m+",
+                (None, None) => "r",
+                _ => ""
+            };
+            let unit = Kijun::get_unit(&explanation.key).map(|unit| unit.to_str().to_string()).unwrap_or_default();
+            let (kind, value) = match &explanation.value {
+                Some(value) => (value.get_kind_name(&explanation.key),
+                                OutputCell::with_value(&value.to_string(), style, value.to_json())),
+                None => ("-", OutputCell::with_value("-", style, Value::Null))
+            };
 
-        if let Ok(potassium) = Kijun::get_potassium(age, gender) {
-            data_list.insert("カリウム".to_string(), potassium);
+            let mut row = vec![
+                OutputCell::new(&explanation.key, ""),
+                OutputCell::new(kind, ""),
+                value,
+                OutputCell::new(&unit, ""),
+            ];
+            if is_explained {
+                row.push(OutputCell::new(age_band, ""));
+                row.push(OutputCell::new(&explanation.input, ""));
+                row.push(OutputCell::new(&format!("×{}", self.days), ""));
+                row.push(OutputCell::new(&explanation.note, style));
+            }
+            table.add_row(RowKind::Kijun, row);
         }
 
-        if let Ok(calcium) = Kijun::get_calcium(age, gender) {
-            data_list.insert("カルシウム".to_string(), calcium);
-        }
+        table
+    }
 
-        if let Ok(magnesium) = Kijun::get_magnesium(age, weight) {
-            data_list.insert("マグネシウム".to_string(), magnesium);
+    // 摂取基準の表で使われる年齢の区分
+    pub fn get_age_band(age: usize) -> &'static str {
+        match age {
+            0 => "0歳",
+            1 ..= 2 => "1~2歳",
+            3 ..= 5 => "3~5歳",
+            6 ..= 7 => "6~7歳",
+            8 ..= 9 => "8~9歳",
+            10 ..= 11 => "10~11歳",
+            12 ..= 14 => "12~14歳",
+            15 ..= 17 => "15~17歳",
+            18 ..= 29 => "18~29歳",
+            30 ..= 49 => "30~49歳",
+            50 ..= 69 => "50~69歳",
+            _ => "70歳以上",
         }
+    }
 
-        if let Ok(phosphorus) = Kijun::get_phosphorus(age, gender) {
-            data_list.insert("リン".to_string(), phosphorus);
-        }
+    // 減量や増量のために1日あたりのエネルギーをenergy_change(kcal)だけ増減する。
+    // エネルギーから求める項目も増減したエネルギーで求め直す
+    pub fn change_energy(&mut self, energy_change: f32) {
+        let energy_val = match self.data_list.get("エネルギー") {
+            Some(KijunValue::Measu(energy_val)) => (energy_val / self.days as f32 + energy_change).max(0.0),
+            _ => return
+        };
 
-        if let Ok(iron) = Kijun::get_iron(age, gender) {
-            data_list.insert("鉄".to_string(), iron);
-        }
+        let energy_input = format!("{}, 増減 {:+.0}kcal/日", self.get_energy_input(), energy_change);
+        self.set_energy(energy_val, energy_input);
+    }
 
-        if let Ok(zinc) = Kijun::get_zinc(age, gender) {
-            data_list.insert("亜鉛".to_string(), zinc);
-        }
+    // エネルギー必要量を別の求め方で求め直す
+    pub fn change_energy_method(&mut self, method: EnergyMethod) -> Result<(), String> {
+        let (energy_val, energy_input) = match method {
+            EnergyMethod::MeasuredTee(tee) => (tee, method.get_name()),
+            _ => {
+                let bm = Kijun::get_base_metabolism_by(method, self.weight, self.height, self.age, self.gender)?;
+                let pal = Kijun::get_pal(self.age, self.pal)?;
+                let energy_input = match method {
+                    EnergyMethod::MeasuredBmr(_) | EnergyMethod::Reference => {
+                        format!("{}, 身体活動レベル {}", method.get_name(), pal)
+                    },
+                    _ => format!("{}, 体重 {}kg, 身長 {}cm, 身体活動レベル {}",
+                                 method.get_name(), self.weight, self.height, pal)
+                };
+                (bm * pal, energy_input)
+            }
+        };
 
-        if let Ok(copper) = Kijun::get_copper(age, gender) {
-            data_list.insert("銅".to_string(), copper);
-        }
+        self.set_energy(energy_val, energy_input);
+        Ok(())
+    }
 
-        if let Ok(manganese) = Kijun::get_manganese(age, gender) {
-            data_list.insert("マンガン".to_string(), manganese);
+    // 1日分のエネルギーと、エネルギーから求める項目を変更する。energy_inputは計算に使った値の説明
+    fn set_energy(&mut self, energy_val: f32, energy_input: String) {
+        self.energy_input = Some(energy_input);
+        let protein = Kijun::get_protein(self.age, self.weight);
+        for key in ENERGY_KEY_LIST.iter() {
+            if let Ok(mut kijun_value) = self.calc_value(key, &Ok(energy_val), &protein) {
+                kijun_value.change_days(self.days);
+                self.insert(key, kijun_value);
+            }
         }
+    }
 
-        if let Ok(iodine) = Kijun::get_iodine(age, gender) {
-            data_list.insert("ヨウ素".to_string(), iodine);
-        }
+    // 1日分の値で摂取基準を変更する。sourceは変更した理由
+    pub fn set_override(&mut self, key: &str, mut kijun_value: KijunValue, source: &str) {
+        kijun_value.change_days(self.days);
+        self.record_override(key, source);
+        self.data_list.insert(key.to_string(), kijun_value);
+    }
 
-        if let Ok(selenium) = Kijun::get_selenium(age, gender) {
-            data_list.insert("セレン".to_string(), selenium);
-        }
-        
-        if let Ok(chromium) = Kijun::get_chromium(age, gender) {
-            data_list.insert("クロム".to_string(), chromium);
-        }
+    // 摂取基準から項目を除く。除いた項目は達成率の計算に使わない
+    pub fn remove_override(&mut self, key: &str, source: &str) {
+        self.record_override(key, source);
+        self.data_list.remove(key);
+    }
 
-        if let Ok(molybdenum) = Kijun::get_molybdenum(age, gender) {
-            data_list.insert("モリブデン".to_string(), molybdenum);
-        }
+    // 変更した理由を記録する。変更する前の値は最初の値を残す
+    fn record_override(&mut self, key: &str, source: &str) {
+        let original = match self.override_list.remove(key) {
+            Some((_, original)) => original,
+            None => self.data_list.get(key).copied()
+        };
+        self.override_list.insert(key.to_string(), (source.to_string(), original));
+    }
 
-        // 日数を反映
-        for (_, kijun_value) in &mut data_list {
-            kijun_value.change_days(days);
-        }
+    // 変更した理由と、変更する前の値
+    pub fn get_override(&self, key: &str) -> Option<(&str, Option<&KijunValue>)> {
+        self.override_list.get(key)
+            .map(|(source, original)| (source.as_str(), original.as_ref()))
+    }
 
-        Kijun {
-            age,
-            weight,
-            height,
-            gender,
-            pal,
-            days,
-            data_list
-        }
+    // 変更した項目。KEY_LISTの順番で、それ以外の項目は最後にする
+    pub fn get_overridden_key_list(&self) -> Vec<&str> {
+        let mut key_list: Vec<&str> = self.override_list.keys().map(|key| key.as_str()).collect();
+        key_list.sort_by_key(|key| (KEY_LIST.iter().position(|k| k == key).unwrap_or(KEY_LIST.len()), *key));
+        key_list
     }
 
-    pub fn get(&self, key: &str) -> Option<&KijunValue> {
-        self.data_list.get(key)
+    // 摂取基準の値の単位
+    pub fn get_unit(key: &str) -> Option<Unit> {
+        match key {
+            // 成分表にない項目
+            "n-6系脂肪酸" | "n-3系脂肪酸" => Some(Unit::Gram),
+            // 食品の項目と同じ単位にする
+            _ => match Nutrient::from_name(key)?.get_unit() {
+                Unit::None => None,
+                unit => Some(unit)
+            }
+        }
     }
 
     pub fn get_list(&self, keys: &[&str]) -> Vec<Option<&KijunValue>> {
@@ -371,6 +715,9 @@ impl Kijun {
     pub fn get_pal(age: usize, pal: PAL) -> Result<f32, String> {
         let result = if age < 1 {
             return Err("年齢が１より小さい場合はPALの値を求めることはできません".to_string())
+        } else if let PAL::Value(pal) = pal {
+            // 数値で指定した場合は表を使わない
+            pal
         } else if 1 <= age && age <= 2 {
             match pal {
                 PAL::Moderate => 1.35,
@@ -406,6 +753,31 @@ impl Kijun {
         return Ok(result)
     }
 
+    // BMI。身長はcm
+    pub fn get_bmi(weight: f32, height: f32) -> f32 {
+        weight / (height / 100.0).powi(2)
+    }
+
+    // 目標とするBMIの範囲。年齢の区分はget_age_bandと同じにする
+    pub fn get_target_bmi(age: usize) -> Result<KijunValue, String> {
+        let result = if age < 18 {
+            return Err("17歳以下は目標とするBMIを求めることができません".to_string())
+        } else if age <= 49 {
+            KijunValue::Range((18.5, 24.9))
+        } else if age <= 69 {
+            KijunValue::Range((20.0, 24.9))
+        } else {
+            KijunValue::Range((21.5, 24.9))
+        };
+
+        Ok(result)
+    }
+
+    // BMIが22になる標準体重
+    pub fn get_standard_weight(height: f32) -> f32 {
+        22.0 * (height / 100.0).powi(2)
+    }
+
     // 基礎代謝量
     pub fn get_base_metabolism(weight: f32,
                            height: f32,
@@ -418,6 +790,54 @@ impl Kijun {
                 * 1000.0 / 4.186)
     }
 
+    // 求め方を選んだ基礎代謝量
+    pub fn get_base_metabolism_by(method: EnergyMethod,
+                                  weight: f32,
+                                  height: f32,
+                                  age: usize,
+                                  gender: Gender) -> Result<f32, String> {
+        // 成人のための式なので、17歳以下には使わない
+        let is_adult_formula = matches!(method, EnergyMethod::HarrisBenedict | EnergyMethod::MifflinStJeor);
+        if is_adult_formula && age <= 17 {
+            return Err(format!("17歳以下は{}で基礎代謝量を求めることができません", method.get_name()))
+        }
+
+        let result = match method {
+            EnergyMethod::Ganpule => Kijun::get_base_metabolism(weight, height, age, gender),
+            EnergyMethod::HarrisBenedict => gender_match!(gender,
+                66.47 + 13.75 * weight + 5.003 * height - 6.755 * age as f32,
+                655.1 + 9.563 * weight + 1.850 * height - 4.676 * age as f32),
+            EnergyMethod::MifflinStJeor => gender_match!(gender,
+                10.0 * weight + 6.25 * height - 5.0 * age as f32 + 5.0,
+                10.0 * weight + 6.25 * height - 5.0 * age as f32 - 161.0),
+            EnergyMethod::Reference => {
+                let (standard, reference_weight) = Kijun::get_base_metabolism_reference(age, gender)?;
+                standard * reference_weight
+            },
+            EnergyMethod::MeasuredBmr(bmr) => bmr,
+            EnergyMethod::MeasuredTee(_) => return Err("総エネルギー消費量から基礎代謝量は求められません".to_string())
+        };
+
+        Ok(result)
+    }
+
+    // 基礎代謝基準値（kcal/kg/日）と参照体重（kg）。年齢の区分はget_age_bandと同じにする
+    pub fn get_base_metabolism_reference(age: usize, gender: Gender) -> Result<(f32, f32), String> {
+        let result = if age <= 17 {
+            return Err("17歳以下は基礎代謝基準値と参照体重を求めることができません".to_string())
+        } else if age <= 29 {
+            gender_match!(gender, (24.0, 63.2), (22.1, 50.0))
+        } else if age <= 49 {
+            gender_match!(gender, (22.3, 68.5), (21.7, 53.1))
+        } else if age <= 69 {
+            gender_match!(gender, (21.5, 65.3), (20.7, 53.0))
+        } else {
+            gender_match!(gender, (21.5, 60.0), (20.7, 49.5))
+        };
+
+        Ok(result)
+    }
+
     // エネルギー必要量（kcal）
     pub fn get_energy(weight: f32, 
                   height: f32, 
@@ -556,6 +976,34 @@ impl Kijun {
         Ok(KijunValue::Measu(n6_fatty_acid + n3_fatty_acid))
     }
 
+    // エネルギー産生栄養素バランスの目標量（%エネルギー）
+    // 他の項目と同じ2015年版の値にする。飽和脂肪酸はget_saturated_fatty_acidと同じく18歳以上だけ
+    pub fn get_energy_ratio(age: usize, key: &str) -> Result<KijunValue, String> {
+        if age == 0 {
+            return Err("0歳はエネルギー産生栄養素バランスを求めることができません".to_string())
+        }
+
+        match key {
+            "たんぱく質" => Ok(KijunValue::Range((13.0, 20.0))),
+            "脂質" => Ok(KijunValue::Range((20.0, 30.0))),
+            "飽和脂肪酸" => match age {
+                0..=17 => Err("17歳以下は飽和脂肪酸の目標量を求めることができません".to_string()),
+                _ => Ok(KijunValue::Less(7.0)),
+            },
+            "炭水化物" => Ok(KijunValue::Range((50.0, 65.0))),
+            _ => Err(format!("{}はエネルギー産生栄養素ではありません", key))
+        }
+    }
+
+    // エネルギー産生栄養素の1gあたりのエネルギー（kcal）
+    pub fn get_energy_per_gram(key: &str) -> Option<f32> {
+        match key {
+            "たんぱく質" | "炭水化物" => Some(4.0),
+            "脂質" | "飽和脂肪酸" => Some(9.0),
+            _ => None
+        }
+    }
+
     // 炭水化物
     pub fn get_carbohydrate(age: usize, energy: f32) -> Result<KijunValue, String> {
         if age <= 0 {
@@ -1185,3 +1633,167 @@ impl Kijun {
         Ok(KijunValue::Suisyo(result))
     }
 }
+
+#[test]
+fn test_kijun_get_energy_ratio() {
+    assert_eq!(Kijun::get_energy_ratio(30, "たんぱく質").unwrap().to_string(), "13 ~ 20");
+    // 年齢の区分の境目でも2015年版の値にする
+    assert_eq!(Kijun::get_energy_ratio(65, "たんぱく質").unwrap().to_string(), "13 ~ 20");
+    assert_eq!(Kijun::get_energy_ratio(70, "たんぱく質").unwrap().to_string(), "13 ~ 20");
+    assert_eq!(Kijun::get_energy_ratio(18, "飽和脂肪酸").unwrap().to_string(), "- 7");
+    // 飽和脂肪酸の摂取基準と同じく17歳以下は求めない
+    assert_eq!(Kijun::get_energy_ratio(16, "飽和脂肪酸").unwrap_err(),
+               Kijun::get_saturated_fatty_acid(16, 2000.0).unwrap_err());
+    assert!(Kijun::get_energy_ratio(2, "飽和脂肪酸").is_err());
+    assert!(Kijun::get_energy_ratio(30, "ビタミンC").is_err());
+}
+
+#[test]
+fn test_kijun_bmi() {
+    assert!((Kijun::get_bmi(64.0, 160.0) - 25.0).abs() < 0.001);
+    assert_eq!(Kijun::get_target_bmi(30).unwrap().to_string(), "18.5 ~ 24.9");
+    assert_eq!(Kijun::get_target_bmi(69).unwrap().to_string(), "20 ~ 24.9");
+    assert_eq!(Kijun::get_target_bmi(70).unwrap().to_string(), "21.5 ~ 24.9");
+    assert!(Kijun::get_target_bmi(17).is_err());
+    assert!((Kijun::get_standard_weight(160.0) - 56.32).abs() < 0.001);
+
+    // エネルギーを減らすと、エネルギーから求める項目も減る
+    let kijun = Kijun::new(30, 60.0, 170.0, Gender::Male, PAL::Moderate, 2);
+    let mut changed = Kijun::new(30, 60.0, 170.0, Gender::Male, PAL::Moderate, 2);
+    changed.change_energy(-500.0);
+    let energy = kijun.get("エネルギー").unwrap().get_reference();
+    assert!((changed.get("エネルギー").unwrap().get_reference() - (energy - 1000.0)).abs() < 0.01);
+    assert!(changed.get("炭水化物").unwrap().get_reference() < kijun.get("炭水化物").unwrap().get_reference());
+    assert_eq!(changed.get("たんぱく質").unwrap().to_string(), kijun.get("たんぱく質").unwrap().to_string());
+}
+
+#[test]
+fn test_kijun_change_energy_method() {
+    let bm = Kijun::get_base_metabolism_by(EnergyMethod::MifflinStJeor, 60.0, 170.0, 30, Gender::Male).unwrap();
+    assert_eq!(bm, 600.0 + 1062.5 - 150.0 + 5.0);
+    let bm = Kijun::get_base_metabolism_by(EnergyMethod::Reference, 60.0, 170.0, 30, Gender::Female).unwrap();
+    assert!((bm - 21.7 * 53.1).abs() < 0.01);
+    assert!(Kijun::get_base_metabolism_by(EnergyMethod::Reference, 60.0, 170.0, 17, Gender::Male).is_err());
+    // 成人の式は17歳以下に使えない
+    assert!(Kijun::get_base_metabolism_by(EnergyMethod::HarrisBenedict, 50.0, 160.0, 15, Gender::Male).is_err());
+    assert!(Kijun::get_base_metabolism_by(EnergyMethod::MifflinStJeor, 50.0, 160.0, 17, Gender::Female).is_err());
+    assert_eq!(Kijun::get_base_metabolism_by(EnergyMethod::Ganpule, 60.0, 170.0, 30, Gender::Male).unwrap(),
+               Kijun::get_base_metabolism(60.0, 170.0, 30, Gender::Male));
+
+    // 測定した基礎代謝量に身体活動レベルを掛ける
+    let mut kijun = Kijun::new(30, 60.0, 170.0, Gender::Male, PAL::Moderate, 2);
+    kijun.change_energy_method(EnergyMethod::MeasuredBmr(1500.0)).unwrap();
+    assert_eq!(kijun.get("エネルギー").unwrap().get_reference(), 1500.0 * 1.75 * 2.0);
+
+    // 総エネルギー消費量はそのまま使う
+    kijun.change_energy_method(EnergyMethod::MeasuredTee(2000.0)).unwrap();
+    assert_eq!(kijun.get("エネルギー").unwrap().get_reference(), 4000.0);
+    let other = Kijun::new(30, 60.0, 170.0, Gender::Male, PAL::Moderate, 2);
+    assert!(kijun.get("炭水化物").unwrap().get_reference() < other.get("炭水化物").unwrap().get_reference());
+}
+
+#[test]
+fn test_kijun_pal_value() {
+    assert_eq!(Kijun::get_pal(30, PAL::Value(2.2)).unwrap(), 2.2);
+    assert_eq!(Kijun::get_pal(3, PAL::Value(1.6)).unwrap(), 1.6);
+    let kijun = Kijun::new(30, 60.0, 170.0, Gender::Male, PAL::Value(2.2), 1);
+    let bm = Kijun::get_base_metabolism(60.0, 170.0, 30, Gender::Male);
+    assert_eq!(kijun.get("エネルギー").unwrap().get_reference(), bm * 2.2);
+
+    // 記録していない時間は1METs
+    let activity_list = vec![
+        Activity { name: "ランニング".to_string(), mets: 10.0, minutes: 60.0 },
+        Activity { name: "睡眠".to_string(), mets: 0.9, minutes: 480.0 },
+    ];
+    match PAL::from_activity_list(&activity_list).unwrap() {
+        PAL::Value(pal) => assert_eq!(pal, ((600.0 + 432.0 + 900.0) / 1440.0 * 1.1 * 100.0_f32).round() / 100.0),
+        _ => panic!("数値の身体活動レベルにする")
+    }
+    let activity_list = vec![Activity { name: "".to_string(), mets: 1.0, minutes: 1500.0 }];
+    assert!(PAL::from_activity_list(&activity_list).is_err());
+}
+
+#[test]
+fn test_kijun_explain() {
+    let mut kijun = Kijun::new(16, 50.0, 165.0, Gender::Female, PAL::Low, 2);
+    kijun.set_override("カリウム", KijunValue::Less(2000.0), "ckd");
+    let explanation_list = kijun.explain();
+    let get = |key: &str| explanation_list.iter().find(|e| e.key == key).unwrap();
+
+    // 求められなかった項目は理由を付ける
+    assert!(get("たんぱく質").value.is_none());
+    assert_eq!(get("たんぱく質").note, "17歳以下はたんぱく質の推奨量を求めることができません");
+    assert_eq!(get("ビタミンB6").note, "17歳以下はたんぱく質の推奨量を求めることができません");
+    assert_eq!(get("ビタミンB6").input, "たんぱく質");
+    assert_eq!(get("ビタミンC").input, "性別 女性");
+    assert_eq!(get("カリウム").note, "ckdで変更");
+    assert_eq!(get("カリウム").value.unwrap().to_string(), "- 4000");
+
+    let kijun = Kijun::new(30, 60.0, 170.0, Gender::Male, PAL::Low, 1);
+    let energy = kijun.get("エネルギー").unwrap().get_reference();
+    let explanation_list = kijun.explain();
+    let get = |key: &str| explanation_list.iter().find(|e| e.key == key).unwrap();
+    assert_eq!(get("脂質").input, format!("エネルギー {:.0}kcal", energy));
+    assert_eq!(get("たんぱく質").input, "体重 60kg");
+    assert_eq!(get("たんぱく質").value.unwrap().get_kind_name("たんぱく質"), "以上");
+    assert_eq!(get("エネルギー").value.unwrap().get_kind_name("エネルギー"), "推定エネルギー必要量");
+    assert_eq!(get("ビタミンD").value.unwrap().get_kind_name("ビタミンD"), "目安量");
+    assert!(explanation_list.iter().all(|e| e.note.is_empty()));
+
+    let table = kijun.get_table(true);
+    assert_eq!(table.column_list.len(), 8);
+    assert_eq!(table.row_list[0].cell_list[4].text, "30~49歳");
+    assert_eq!(kijun.get_table(false).column_list.len(), 4);
+    assert_eq!(Kijun::get_age_band(70), "70歳以上");
+
+    // エネルギーを求め直した場合はその求め方を表示する
+    let get_energy_input = |kijun: &Kijun| {
+        kijun.explain().into_iter().find(|e| e.key == "エネルギー").unwrap().input
+    };
+    let mut kijun = Kijun::new(30, 60.0, 170.0, Gender::Male, PAL::Low, 1);
+    assert_eq!(get_energy_input(&kijun), "体重 60kg, 身長 170cm, 身体活動レベル 1.5");
+    kijun.change_energy_method(EnergyMethod::MeasuredTee(2000.0)).unwrap();
+    assert_eq!(get_energy_input(&kijun), "総エネルギー消費量 2000kcal");
+    kijun.change_energy(-300.0);
+    assert_eq!(get_energy_input(&kijun), "総エネルギー消費量 2000kcal, 増減 -300kcal/日");
+    kijun.change_energy_method(EnergyMethod::MeasuredBmr(1400.0)).unwrap();
+    assert_eq!(get_energy_input(&kijun), "測定した基礎代謝量 1400kcal, 身体活動レベル 1.5");
+}
+
+#[test]
+fn test_kijun_get_unit() {
+    for key in CALC_KEY_LIST.iter() {
+        assert!(Kijun::get_unit(key).is_some(), "{}", key);
+    }
+    assert_eq!(Kijun::get_unit("ビタミンA"), Some(Unit::Microgram));
+    assert_eq!(Kijun::get_unit("食塩相当量"), Some(Unit::Gram));
+    assert_eq!(Kijun::get_unit("n-3系脂肪酸"), Some(Unit::Gram));
+    assert_eq!(Kijun::get_unit("食品名"), None);
+    assert_eq!(Kijun::get_unit("ないもの"), None);
+}
+
+#[test]
+fn test_kijun_set_override() {
+    let mut kijun = Kijun::new(30, 60.0, 170.0, Gender::Male, PAL::Moderate, 2);
+    let original = *kijun.get("カリウム").unwrap();
+    kijun.set_override("カリウム", KijunValue::Less(2000.0), "ckd");
+    // 日数を反映する
+    assert_eq!(kijun.get("カリウム").unwrap().to_string(), "- 4000");
+    let (source, before) = kijun.get_override("カリウム").unwrap();
+    assert_eq!(source, "ckd");
+    assert_eq!(before.unwrap().to_string(), original.to_string());
+
+    // 2回変更しても元の値は最初の値
+    kijun.set_override("カリウム", KijunValue::Less(1500.0), "other");
+    assert_eq!(kijun.get_override("カリウム").unwrap().1.unwrap().to_string(), original.to_string());
+
+    kijun.set_override("食塩相当量", KijunValue::Less(6.0), "hypertension");
+    assert!(kijun.get_override("食塩相当量").unwrap().1.is_none());
+    assert_eq!(kijun.get_overridden_key_list(), vec!["カリウム", "食塩相当量"]);
+    assert!(kijun.get_override("ナトリウム").is_none());
+
+    let original = *kijun.get("ヨウ素").unwrap();
+    kijun.remove_override("ヨウ素", "targets");
+    assert!(kijun.get("ヨウ素").is_none());
+    assert_eq!(kijun.get_override("ヨウ素").unwrap().1.unwrap().to_string(), original.to_string());
+}
@@ -1,7 +1,10 @@
 use std::str::FromStr;
 use std::fs::File;
 use std::io::BufReader;
+use std::rc::Rc;
+use std::path::PathBuf;
 
+use chrono::{Duration, Local, NaiveDate};
 use clap::{Arg, App, SubCommand, ArgMatches, AppSettings};
 
 mod food;
@@ -10,10 +13,29 @@ mod kijun;
 mod parse_json;
 mod automatic_selection;
 mod combination;
+mod unit;
+mod search;
+mod query;
+mod density;
+mod expression;
+mod output;
+mod spreadsheet;
+mod preset;
+mod tui;
+mod diary;
+mod shopping;
+mod pantry;
+mod restriction;
+mod diet_profile;
 use food_table::FoodTable;
-use food::food_data::FoodData;
-use parse_json::parse_json;
+use food::food_data::TraceMode;
+use parse_json::{parse_json, parse_body_json, parse_purchase_units, ParsedData};
 use crate::kijun::Kijun;
+use crate::query::Query;
+use crate::output::{Format, Layout, OutputTable, Report, print_report_list};
+use crate::preset::PresetList;
+use crate::restriction::{Restriction, TagTable};
+use crate::diet_profile::DietProfile;
 
 enum SearchType {
     Or,
@@ -37,52 +59,45 @@ fn search(matches: &ArgMatches) -> Result<(), String> {
         values.into_iter().for_each(|v| name_list.push(v.to_string()));
     }
 
-    let text_list: Vec<_> = matches.values_of("text").unwrap().collect();
-    match matches.value_of("column-type") {
-        None => (),
-        Some(t) => match t {
-            "0" => [
-                "廃棄率", "エネルギー", "エネルギー（kJ)", "水分", "たんぱく質",
-                "アミノ酸組成によるたんぱく質", "脂質",
-                "トリアシルグリセロール当量", "飽和脂肪酸", "一価不飽和脂肪酸",
-                "多価不飽和脂肪酸", "コレステロール", "炭水化物",
-                "利用可能炭水化物（単糖当量）", "水溶性食物繊維", "不溶性食物繊維",
-                "食物繊維総量", "灰   分", "ナトリウム", "カリウム", "カルシウム",
-                "マグネシウム", "リン", "鉄", "亜鉛", "銅", "マンガン", "ヨウ素",
-                "セレン", "クロム", "モリブデン", "レチノール", "α-カロテン",
-                "β-カロテン", "β-クリプトキサンチン", "β-カロテン当量",
-                "レチノール活性当量", "ビタミンD", "α-トコフェロール",
-                "β-トコフェロール", "γ-トコフェロール", "δ-トコフェロール",
-                "ビタミンK", "ビタミンB1", "ビタミンB2", "ナイアシン",
-                "ビタミンB6", "ビタミンB12", "葉酸", "パントテン酸", "ビオチン",
-                "ビタミンC", "食塩相当量", "アルコール", "硝酸イオン",
-                "テオブロミン", "カフェイン", "タンニン", "ポリフェノール", "酢酸",
-                "調理油", "有機酸", "重量変化率"
-            ].iter().for_each(|v| name_list.push(v.to_string())),
-            "1" => [
-                "重量", "エネルギー", "たんぱく質", "脂質", "炭水化物",
-                "食物繊維総量", "ナトリウム", "カルシウム", "鉄",
-                "レチノール活性当量", "ビタミンB1", "ビタミンB2", "ビタミンC"
-            ].iter().for_each(|v| name_list.push(v.to_string())),
-            "2" => [
-                "重量", "エネルギー", "たんぱく質", "脂質", "多価不飽和脂肪酸",
-                "炭水化物", "食物繊維総量", "レチノール活性当量", "ビタミンD",
-                "α-トコフェロール", "ビタミンK", "ビタミンB1", "ビタミンB2",
-                "ナイアシン", "ビタミンB6", "ビタミンB12", "葉酸", "パントテン酸",
-                "ビオチン", "ビタミンC", "ナトリウム", "カリウム", "カルシウム",
-                "マグネシウム", "リン", "鉄", "亜鉛", "銅", "マンガン", "ヨウ素",
-                "セレン", "クロム", "モリブデン"
-            ].iter().for_each(|v| name_list.push(v.to_string())),
-            _ => ["食品番号", "食品名"].iter().for_each(|v| name_list.push(v.to_string())),
-        }
+    let text_list: Vec<_> = match matches.values_of("text") {
+        Some(values) => values.collect(),
+        None => Vec::new()
     };
+    let preset_list = PresetList::load()?;
+    // ないプリセットはname_listの"preset:名前"と同じくエラーにする
+    if let Some(name) = matches.value_of("column-type") {
+        let preset = preset_list.get(name).ok_or_else(|| format!("{} というプリセットはありません", name))?;
+        for column in &preset.column_list {
+            if !name_list.contains(column) {
+                name_list.push(column.to_string());
+            }
+        }
+    }
+    let name_list = preset_list.expand(&name_list)?;
 
-    let path = "/home/jibuntu/programming_language/rust/project/meal/data/foods.json";
-    let mut foods = match FoodTable::from_json(path) {
-        Ok(foods) => foods,
-        Err(e) => return Err(e.to_string())
+    let mut foods = read_food_table()?;
+
+    if let Some(trace) = matches.value_of("trace") {
+        foods.set_trace_mode(TraceMode::from_str(trace)?);
+    }
+
+    let mut restriction_list = match matches.values_of("restriction") {
+        Some(values) => restriction::parse_restriction_list(&values.collect::<Vec<_>>())?,
+        None => Vec::new()
     };
 
+    // 摂取基準に対する栄養密度を計算するために使う
+    if let Some(path) = matches.value_of("body") {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => return Err(e.to_string())
+        };
+        let body = parse_body_json(BufReader::new(file))?;
+        let kijun = body.get_kijun(1)?;
+        foods.set_kijun(Rc::new(kijun));
+        restriction_list.extend(body.restrictions);
+    }
+
     if let Some(weight) = matches.value_of("weight") {
         match f32::from_str(weight) {
             Ok(num) => foods.set_weight(num),
@@ -90,27 +105,69 @@ fn search(matches: &ArgMatches) -> Result<(), String> {
         }
     }
 
+    let is_exact = matches.is_present("exact");
+    let mut result = match search_type {
+        // textがなければ条件式だけで絞り込む
+        _ if text_list.is_empty() => foods,
+        SearchType::Or if is_exact => foods.search_or(&text_list),
+        SearchType::And if is_exact => foods.search_and(&text_list),
+        SearchType::Or => foods.fuzzy_search(&text_list, false),
+        SearchType::And => foods.fuzzy_search(&text_list, true),
+        SearchType::Number => foods.get_list(&text_list)
+    };
+
+    if let Some(text) = matches.value_of("query") {
+        result = result.filter(&Query::parse(text)?);
+    }
+
+    if !restriction_list.is_empty() {
+        result = TagTable::load()?.filter(&result, &restriction_list);
+    }
+
+    // ソートが指定されたときは関連度の順番より優先する
     if let Some(name) = matches.value_of("ascending-order") {
-        foods.sort_ascending_order(name);
+        result.sort_ascending_order(name);
     }
 
     if let Some(name) = matches.value_of("descending-order") {
-        foods.sort_descending_order(name);
+        result.sort_descending_order(name);
     }
 
-    let result = match search_type {
-        SearchType::Or => foods.search_or(&text_list),
-        SearchType::And => foods.search_and(&text_list),
-        SearchType::Number => foods.get_list(&text_list)
-    };
-
+    let format = Format::from_str(matches.value_of("format").unwrap_or("text"))?;
     let list: Vec<_> = name_list.iter().map(|name| name.as_str()).collect();
-    result.print(&list);
+    let mut report = result.get_report(&list);
+    report.set_layout(Layout::from_terminal(matches.is_present("transpose")));
+    report.print(format);
 
     Ok(())
 }
 
-fn print_table(path: &str, foods: &FoodTable) -> Result<(), String> {
+// 食品成分表と価格のデータを読み込む
+pub fn read_food_table() -> Result<FoodTable, String> {
+    let path = "/home/jibuntu/programming_language/rust/project/meal/data/foods.json";
+    let mut foods = match FoodTable::from_json(path) {
+        Ok(foods) => foods,
+        Err(e) => return Err(e.to_string())
+    };
+    foods.set_price_from_json("/home/jibuntu/programming_language/rust/project/meal/data/price/prices.json")?;
+
+    Ok(foods)
+}
+
+// calcのJSONから読み込んだもの
+struct CalcInput {
+    food_table: FoodTable,
+    // 表示する列
+    name_list: Vec<String>,
+    kijun: Rc<Kijun>,
+    restriction_list: Vec<Restriction>,
+    profile_list: Vec<DietProfile>,
+    // 体重、BMI、目標体重
+    body_table: OutputTable,
+}
+
+// calcのJSONを読み込む
+fn read_calc_json(path: &str, foods: &FoodTable) -> Result<CalcInput, String> {
     let file = match File::open(path) {
         Ok(file) => file,
         Err(e) => return Err(e.to_string())
@@ -121,84 +178,353 @@ fn print_table(path: &str, foods: &FoodTable) -> Result<(), String> {
         Err(e) => return Err(e)
     };
 
-    let mut udf_table = FoodTable::new();
-    if let Some(udfs) = parsed_data.user_definition_foods {
-        for udf in udfs {
-            let mut food = food::Food::new();
-            // 食品番号の先頭にuをつける
-            // すでにある食品番号と被らないように
-            food.set("食品番号", FoodData::String("u".to_string() + &udf.number));
-            food.set("重量", FoodData::Number(udf.weight));
-            for (name, food_data) in udf.data {
-                food.set(&name, FoodData::from_str(&food_data))
-            }
-            udf_table.add(food)
+    get_calc_input(parsed_data, foods)
+}
+
+// 読み込んだcalcのJSONから。tuiで編集した献立にも使う
+fn get_calc_input(parsed_data: ParsedData, foods: &FoodTable) -> Result<CalcInput, String> {
+    let mut food_table = parsed_data.get_food_table(foods)?;
+
+    let kijun = parsed_data.body.get_kijun(parsed_data.body.days.unwrap_or(1))?;
+    let kijun = Rc::new(kijun);
+    food_table.set_kijun(Rc::clone(&kijun));
+
+//    if parsed_data.options.show_status {
+//        println!("{}", food_table.get_status());
+//    }
+
+    let name_list = PresetList::load()?.expand(&parsed_data.name_list)?;
+
+    Ok(CalcInput {
+        food_table,
+        name_list,
+        kijun,
+        body_table: parsed_data.body.get_table(),
+        restriction_list: parsed_data.body.restrictions,
+        profile_list: parsed_data.body.profiles,
+    })
+}
+
+fn get_report(path: &str, foods: &FoodTable) -> Result<Report, String> {
+    let mut report = get_calc_report(read_calc_json(path, foods)?)?;
+    report.set_title(path);
+
+    Ok(report)
+}
+
+// calcで表示する結果
+fn get_calc_report(input: CalcInput) -> Result<Report, String> {
+    let list: Vec<_> = input.name_list.iter().map(|name| name.as_str()).collect();
+    let mut report = input.food_table.get_report_with_sum_and_kijun(&list, &input.kijun);
+    report.add_table("body", Some("体格"), input.body_table);
+
+    // body.restrictionsに当てはまる食品があれば警告する
+    if let Some(table) = TagTable::load()?.get_warning_table(&input.food_table, &input.restriction_list) {
+        report.add_table("restriction_warnings", Some("制限に当てはまる食品"), table);
+    }
+
+    // 食事療法で摂取基準の値とは別に確かめる項目
+    for profile in &input.profile_list {
+        if let Some(table) = profile.get_check_table(&input.food_table) {
+            report.add_table(&format!("{}_check", profile.to_str()), Some(profile.get_description()), table);
         }
     }
 
-    let mut food_table = FoodTable::new();
-    for parsed_food in parsed_data.foods {
-        let mut food = match foods.get(&parsed_food.number) {
-            Some(food) => food.change_weight(parsed_food.weight.unwrap_or(100.0)).unwrap(),
-            None => match udf_table.get(&parsed_food.number) {
-                Some(food) => food.change_weight(parsed_food.weight.unwrap_or(100.0)).unwrap(),
-                None => return Err(format!("{}番の食材はありません。JSONの値が間違っています", &parsed_food.number))
+    Ok(report)
+}
+
+// 全体とクラスごとのシートにして書き出す
+fn export(export_path: &str, file_list: &[&str], foods: &FoodTable) -> Result<(), String> {
+    let mut sheet_list = Vec::new();
+    for file_name in file_list {
+        let input = read_calc_json(file_name, foods)?;
+        // ファイルが1つの場合はシート名にファイル名を付けない
+        let name = if file_list.len() == 1 {
+            "全体".to_string()
+        } else {
+            match std::path::Path::new(file_name).file_stem() {
+                Some(stem) => stem.to_string_lossy().to_string(),
+                None => file_name.to_string()
             }
         };
+        sheet_list.append(&mut spreadsheet::get_sheet_list(&name, input.food_table, &input.name_list, input.kijun));
+    }
 
-        if let Some(price) = parsed_food.price {
-            food.set("価格", FoodData::Number(price));
-        }
+    spreadsheet::write_xlsx(export_path, &sheet_list)?;
+    println!("{}に書き出しました", export_path);
 
-        if let Some(class) = parsed_food.class {
-            food.set("クラス", FoodData::String(class));
-        }
+    Ok(())
+}
+
+fn calc(matches: &ArgMatches) -> Result<(), String>{
+    let foods = read_food_table()?;
 
-        if parsed_food.include_refuse == true {
-            food = match food.include_refuse() {
+    if let Some(export_path) = matches.value_of("export") {
+        let file_list: Vec<_> = matches.values_of("file").unwrap().collect();
+        return export(export_path, &file_list, &foods)
+    }
+
+    let format = Format::from_str(matches.value_of("format").unwrap_or("text"))?;
+    let mut report_list = Vec::new();
+    for file_name in matches.values_of("file").unwrap() {
+        let mut report = get_report(file_name, &foods)?;
+        report.set_layout(Layout::from_terminal(matches.is_present("transpose")));
+        report_list.push(report);
+    }
+
+    print_report_list(&report_list, format);
+
+    Ok(())
+}
+
+// 複数の日の献立から買い物リストを作る
+fn shopping_list(matches: &ArgMatches) -> Result<(), String> {
+    let foods = read_food_table()?;
+
+    // 購入単位は設定ファイルのpurchase_unitsに、献立のJSONのpurchase_unitsを上書きする
+    let mut purchase_units = match preset::read_config()?.get("purchase_units") {
+        Some(value) => parse_purchase_units(value)?,
+        None => std::collections::HashMap::new()
+    };
+
+    let mut shopping_list = shopping::ShoppingList::new();
+    for file_name in matches.values_of("file").unwrap() {
+        let file = match File::open(file_name) {
+            Ok(file) => file,
+            Err(e) => return Err(format!("{}: {}", file_name, e))
+        };
+        let parsed_data = parse_json(BufReader::new(file))?;
+        let udf_table = parsed_data.get_user_definition_table();
+
+        for parsed_food in &parsed_data.foods {
+            let food = match foods.get(&parsed_food.number) {
                 Some(food) => food,
-                None => return Err(format!("{}番の食材は廃棄部分を含めることはできません", &parsed_food.number))
+                None => match udf_table.get(&parsed_food.number) {
+                    Some(food) => food,
+                    None => return Err(format!("{}番の食材はありません。JSONの値が間違っています", &parsed_food.number))
+                }
             };
+            shopping_list.add(&parsed_food.number, food, parsed_food.weight.unwrap_or(100.0),
+                              parsed_food.include_refuse, parsed_food.price)?;
         }
 
-        food_table.add(food);
+        purchase_units.extend(parsed_data.purchase_units);
     }
 
-    let list: Vec<_> = parsed_data.name_list.iter().map(|name| name.as_str()).collect();
+    let format = Format::from_str(matches.value_of("format").unwrap_or("text"))?;
+    let mut report = Report::new(Some("買い物リスト"));
+    report.add_table("items", None, shopping_list.get_table(&purchase_units, matches.is_present("round")));
+    report.set_layout(Layout::from_terminal(matches.is_present("transpose")));
+    report.print(format);
 
-    let kijun = Kijun::new(parsed_data.body.age,
-                           parsed_data.body.weight,
-                           parsed_data.body.height,
-                           parsed_data.body.gender,
-                           parsed_data.body.pal,
-                           parsed_data.body.days.unwrap_or(1));
-    food_table.print_with_sum_and_kijun(&list, &kijun);
+    Ok(())
+}
 
-//    if parsed_data.options.show_status {
-//        println!("{}", food_table.get_status());
-//    }
-    
-    println!();
+fn get_diary_path(matches: &ArgMatches) -> Result<PathBuf, String> {
+    match matches.value_of("diary") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => diary::get_diary_path().ok_or_else(|| "記録するファイルの場所がわかりません。--diaryで指定してください".to_string())
+    }
+}
+
+// --fromと--toの期間。指定がなければ今日までの7日間
+fn get_period(matches: &ArgMatches) -> Result<(NaiveDate, NaiveDate), String> {
+    let to = match matches.value_of("to") {
+        Some(to) => diary::parse_date(to)?,
+        None => Local::now().date_naive()
+    };
+    let from = match matches.value_of("from") {
+        Some(from) => diary::parse_date(from)?,
+        None => to - Duration::days(6)
+    };
+    if to < from {
+        return Err("--fromは--toより前の日付にしてください".to_string())
+    }
+
+    Ok((from, to))
+}
+
+fn log(matches: &ArgMatches) -> Result<(), String> {
+    let foods = read_food_table()?;
+
+    if let Some(matches) = matches.subcommand_matches("add") {
+        let diary_path = get_diary_path(matches)?;
+        let number = diary::find_food(&foods, matches.value_of("food").unwrap())?;
+        let weight = match f32::from_str(matches.value_of("grams").unwrap()) {
+            Ok(weight) if weight >= 0.0 => weight,
+            _ => return Err("重量は0以上の数値にしてください".to_string())
+        };
+        let date = match matches.value_of("date") {
+            Some(date) => diary::parse_date(date)?,
+            None => Local::now().date_naive()
+        };
+        let entry = diary::DiaryEntry {
+            date,
+            meal: matches.value_of("meal").map(|meal| meal.to_string()),
+            number,
+            weight,
+        };
+        // 先に日記に記録してからパントリーを減らす。パントリーだけ減って記録がない状態にしない
+        diary::append(&diary_path, &entry)?;
+        println!("{} {}（{}） {}gを記録しました",
+                 entry.date, diary::get_food_name(&foods, &entry.number), entry.number, entry.weight);
+
+        // パントリーにある食材なら食べた分を減らす
+        if !matches.is_present("no-pantry") {
+            let pantry_error = |e: String| format!("食事は記録しましたが、パントリーを更新できませんでした: {}", e);
+            let pantry_path = get_pantry_path(matches).map_err(pantry_error)?;
+            let mut pantry = pantry::Pantry::read(&pantry_path).map_err(pantry_error)?;
+            if pantry.contains(&entry.number) {
+                let used = pantry.consume(&entry.number, entry.weight);
+                pantry.write(&pantry_path).map_err(pantry_error)?;
+                // 残りは期限が切れたものも含める
+                println!("パントリーから{}gを減らしました（残り{}g）",
+                         used, pantry.get_total_weight(&entry.number));
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("list") {
+        let (from, to) = get_period(matches)?;
+        let entry_list: Vec<_> = diary::read(&get_diary_path(matches)?)?.into_iter()
+            .filter(|entry| from <= entry.date && entry.date <= to)
+            .collect();
+        let mut report = Report::new(None);
+        report.add_table("entries", None, diary::get_entry_table(&entry_list, &foods));
+        report.set_layout(Layout::from_terminal(matches.is_present("transpose")));
+        report.print(Format::from_str(matches.value_of("format").unwrap_or("text"))?);
+    }
 
     Ok(())
 }
 
-fn calc(matches: &ArgMatches) -> Result<(), String>{
-    let path = "/home/jibuntu/programming_language/rust/project/meal/data/foods.json";
-    let foods = match FoodTable::from_json(path) {
-        Ok(foods) => foods,
+fn get_pantry_path(matches: &ArgMatches) -> Result<PathBuf, String> {
+    match matches.value_of("pantry-file") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => pantry::get_pantry_path().ok_or_else(|| "パントリーのファイルの場所がわかりません。--pantry-fileで指定してください".to_string())
+    }
+}
+
+fn pantry(matches: &ArgMatches) -> Result<(), String> {
+    let foods = read_food_table()?;
+
+    if let Some(matches) = matches.subcommand_matches("add") {
+        let pantry_path = get_pantry_path(matches)?;
+        let mut pantry = pantry::Pantry::read(&pantry_path)?;
+        let number = diary::find_food(&foods, matches.value_of("food").unwrap())?;
+        let weight = match f32::from_str(matches.value_of("grams").unwrap()) {
+            Ok(weight) if weight > 0.0 => weight,
+            _ => return Err("重量は0より大きい数値にしてください".to_string())
+        };
+        let expiry = match matches.value_of("expiry") {
+            Some(expiry) => Some(diary::parse_date(expiry)?),
+            None => None
+        };
+        pantry.add(&number, weight, expiry);
+        pantry.write(&pantry_path)?;
+        println!("{}（{}） {}gをパントリーに追加しました", diary::get_food_name(&foods, &number), number, weight);
+    } else if let Some(matches) = matches.subcommand_matches("list") {
+        let pantry = pantry::Pantry::read(&get_pantry_path(matches)?)?;
+        let mut report = Report::new(None);
+        report.add_table("pantry", None, pantry.get_table(&foods, Local::now().date_naive()));
+        report.set_layout(Layout::from_terminal(matches.is_present("transpose")));
+        report.print(Format::from_str(matches.value_of("format").unwrap_or("text"))?);
+    }
+
+    Ok(())
+}
+
+fn report(matches: &ArgMatches) -> Result<(), String> {
+    let foods = read_food_table()?;
+
+    let file = match File::open(matches.value_of("body").unwrap()) {
+        Ok(file) => file,
         Err(e) => return Err(e.to_string())
     };
+    let body = parse_body_json(BufReader::new(file))?;
+    // 日ごとに比べるので1日分にする
+    let kijun = body.get_kijun(1)?;
 
-    for file_name in matches.values_of("file").unwrap() {
-        if let Err(e) = print_table(file_name, &foods) {
-            return Err(e);
-        }
-    }
+    let (from, to) = get_period(matches)?;
+    let entry_list = diary::read(&get_diary_path(matches)?)?;
+    let mut report = diary::get_report(&entry_list, &foods, &kijun, from, to);
+    report.set_layout(Layout::from_terminal(matches.is_present("transpose")));
+    report.print(Format::from_str(matches.value_of("format").unwrap_or("text"))?);
+
+    Ok(())
+}
+
+fn kijun(matches: &ArgMatches) -> Result<(), String> {
+    let file = match File::open(matches.value_of("body").unwrap()) {
+        Ok(file) => file,
+        Err(e) => return Err(e.to_string())
+    };
+    let body = parse_body_json(BufReader::new(file))?;
+    let days = match matches.value_of("days") {
+        Some(days) => days.parse::<usize>().map_err(|_| "daysの値は整数にしてください".to_string())?,
+        None => body.days.unwrap_or(1)
+    };
+    let kijun = body.get_kijun(days)?;
+
+    let mut report = Report::new(Some(&format!("摂取基準（{}日分）", days)));
+    report.add_table("body", Some("体格"), body.get_table());
+    report.add_table("kijun", None, kijun.get_table(matches.is_present("explain")));
+    report.set_layout(Layout::from_terminal(matches.is_present("transpose")));
+    report.print(Format::from_str(matches.value_of("format").unwrap_or("text"))?);
+
+    Ok(())
+}
+
+fn diary_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("diary")
+        .long("diary")
+        .takes_value(true)
+        .help("記録するファイルを指定します。デフォルトはMEAL_DIARYか~/.local/share/meal/diary.jsonlです")
+}
+
+fn pantry_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("pantry-file")
+        .long("pantry-file")
+        .takes_value(true)
+        .help("パントリーのファイルを指定します。デフォルトはMEAL_PANTRYか~/.local/share/meal/pantry.jsonです")
+}
+
+fn period_arg_list<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("from")
+            .long("from")
+            .takes_value(true)
+            .help("期間の最初の日を指定します。デフォルトは--toの6日前です"),
+        Arg::with_name("to")
+            .long("to")
+            .takes_value(true)
+            .help("期間の最後の日を指定します。デフォルトは今日です"),
+    ]
+}
+
+// profilesやpresetsの一覧を表示する
+fn print_list(matches: &ArgMatches, key: &str, table: OutputTable) -> Result<(), String> {
+    let format = Format::from_str(matches.value_of("format").unwrap_or("text"))?;
+    let mut report = Report::new(None);
+    report.add_table(key, None, table);
+    report.print(format);
 
     Ok(())
 }
 
+fn format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("format")
+        .long("format")
+        .short("f")
+        .takes_value(true)
+        .possible_values(&["text", "json", "csv", "tsv", "markdown", "html"])
+        .help("出力の形式を指定します。デフォルトはtextです")
+}
+
+fn transpose_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("transpose")
+        .long("transpose")
+        .help("栄養素を行に、食品を列にして表示します")
+}
+
 fn main() {
     let matches = App::new("meal")
         .version("0.0")
@@ -212,7 +538,7 @@ fn main() {
             .about("食品を検索します")
             .arg(Arg::with_name("text")
                 .min_values(1)
-                .required(true))
+                .required_unless("query"))
             .arg(Arg::with_name("search-or")
                 .long("or")
                 .short("o")
@@ -225,6 +551,17 @@ fn main() {
                 .long("number")
                 .short("n")
                 .help("食品番号がtextのものを取得します"))
+            .arg(Arg::with_name("query")
+                .long("query")
+                .short("q")
+                .takes_value(true)
+                .help("条件式で絞り込みます\n\
+                      例: \"食品群=12 AND たんぱく質>20 AND 脂質<5\"\n\
+                      例: \"ビタミンC per 100kcal > 50\""))
+            .arg(Arg::with_name("exact")
+                .long("exact")
+                .short("e")
+                .help("表記ゆれや誤字を許さず、食品名にtextを含むものだけを取得します"))
             .arg(Arg::with_name("weight")
                 .long("weight")
                 .short("w")
@@ -235,24 +572,52 @@ fn main() {
                 .short("c")
                 .takes_value(true)
                 .min_values(1)
-                .help(r#"表示する列を指定します。食品番号と食品名はデフォルトです。"#))
+                .help("表示する列を指定します。食品番号と食品名はデフォルトです。\n\
+                      栄養密度も指定できます\n\
+                      例: \"たんぱく質 per 100kcal\", \"ビタミンC per 100円\"\n\
+                      例: \"ビタミンC% per 100kcal\"（摂取基準に対する割合、--bodyが必要）\n\
+                      例: \"NRF9.2\"（Nutrient Rich Food Index、--bodyが必要）\n\
+                      計算式も指定できます\n\
+                      例: \"P比=たんぱく質*4/エネルギー*100\", \"Na/K\", \"食塩相当量/1000kcal\""))
+            .arg(Arg::with_name("body")
+                .long("body")
+                .short("b")
+                .takes_value(true)
+                .help("摂取基準の計算に使うbodyのJSONファイルを指定します。\n\
+                      bodyにrestrictionsがあれば当てはまる食品を除きます"))
+            .arg(Arg::with_name("restriction")
+                .long("restriction")
+                .takes_value(true)
+                .multiple(true)
+                .help("当てはまる食品を除きます。\n\
+                      えび、かに、小麦、そば、卵、乳、落花生、肉、魚介、豚、アルコール、はちみつ、高FODMAPのタグか、\n\
+                      vegetarian, vegan, halal, low-fodmapを指定します"))
+            .arg(Arg::with_name("trace")
+                .long("trace")
+                .takes_value(true)
+                .possible_values(&["zero", "half", "threshold"])
+                .help("ソートでTrをどのように扱うかを指定します"))
             .arg(Arg::with_name("ascending-order")
                 .long("12")
                 .takes_value(true)
                 .max_values(1)
-                .help("結果を昇順ソートします。値は列名です。"))
+                .help("結果を昇順ソートします。値は列名か栄養密度です。"))
             .arg(Arg::with_name("descending-order")
                 .long("21")
                 .takes_value(true)
                 .max_values(1)
-                .help("結果を降順ソートします。値は列名です。"))
+                .help("結果を降順ソートします。値は列名か栄養密度です。"))
             .arg(Arg::with_name("column-type")
                 .long("type")
                 .short("t")
                 .min_values(1)
-                .help("columnのデフォルトの種類を設定します\n\
-                      0: 「調理・献立作成の基礎」と同じ設定にします\n\
-                      1: 摂取基準と同じ設定にします")))
+                .help("columnのプリセットを名前か番号で指定します\n\
+                      0, kiso: 「調理・献立作成の基礎」と同じ設定にします\n\
+                      1, main: 主な栄養素にします\n\
+                      2, nutrients: 摂取基準がある栄養素にします\n\
+                      プリセットの一覧はpresetsサブコマンドで表示します"))
+            .arg(format_arg())
+            .arg(transpose_arg()))
         .subcommand(SubCommand::with_name("calc")
             .visible_alias("c")
             .setting(AppSettings::DeriveDisplayOrder)
@@ -260,7 +625,15 @@ fn main() {
             .arg(Arg::with_name("file")
                 .min_values(1)
                 .required(true)
-                .help("ファイルを指定します")))
+                .help("ファイルを指定します"))
+            .arg(format_arg())
+            .arg(transpose_arg())
+            .arg(Arg::with_name("export")
+                .long("export")
+                .short("x")
+                .takes_value(true)
+                .help("xlsx形式のファイルに書き出します。\n\
+                      全体とクラスごとのシートに分け、合計と摂取基準に対する割合は式にします")))
         .subcommand(SubCommand::with_name("automatic-selection")
             .visible_alias("a")
             .setting(AppSettings::DeriveDisplayOrder)
@@ -268,7 +641,142 @@ fn main() {
             .arg(Arg::with_name("file")
                 .takes_value(true)
                 .required(true)
-                .help("ファイルを指定します")))
+                .help("ファイルを指定します"))
+            .arg(Arg::with_name("pantry")
+                .long("pantry")
+                .short("p")
+                .help("パントリーにある食材だけから選びます。\n\
+                      重量はパントリーにある重量までにし、期限が近い食材を優先します。\n\
+                      JSONに食材がなければパントリーの食材をすべて候補にします"))
+            .arg(pantry_file_arg())
+            .arg(format_arg())
+            .arg(transpose_arg()))
+        .subcommand(SubCommand::with_name("shopping-list")
+            .setting(AppSettings::DeriveDisplayOrder)
+            .about("献立のJSONから廃棄部分を含めた購入量と価格の買い物リストを作ります")
+            .arg(Arg::with_name("file")
+                .min_values(1)
+                .required(true)
+                .help("ファイルを指定します。複数のファイルを指定すると合計します"))
+            .arg(Arg::with_name("round")
+                .long("round")
+                .short("r")
+                .help("purchase_unitsで購入単位を指定した食品の購入量を単位に切り上げます"))
+            .arg(format_arg())
+            .arg(transpose_arg()))
+        .subcommand(SubCommand::with_name("tui")
+            .setting(AppSettings::DeriveDisplayOrder)
+            .about("食品を検索しながら献立を作ります")
+            .arg(Arg::with_name("file")
+                .required(true)
+                .help("保存するJSONファイルを指定します。ファイルがあれば読み込んで続きから編集します"))
+            .arg(Arg::with_name("body")
+                .long("body")
+                .short("b")
+                .takes_value(true)
+                .help("摂取基準の計算に使うbodyのJSONファイルを指定します"))
+            .arg(format_arg()
+                .help("終了したときに、献立の結果をcalcと同じように指定した形式で表示します")))
+        .subcommand(SubCommand::with_name("log")
+            .setting(AppSettings::DeriveDisplayOrder)
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .about("食べたものを記録します")
+            .subcommand(SubCommand::with_name("add")
+                .setting(AppSettings::DeriveDisplayOrder)
+                .about("食べたものを記録します")
+                .arg(Arg::with_name("food")
+                    .required(true)
+                    .help("食品番号か食品名を指定します。食品名の場合は最も一致する食品にします"))
+                .arg(Arg::with_name("grams")
+                    .required(true)
+                    .help("重量(g)を指定します"))
+                .arg(Arg::with_name("date")
+                    .long("date")
+                    .short("d")
+                    .takes_value(true)
+                    .help("日付を2020-01-31のように指定します。デフォルトは今日です"))
+                .arg(Arg::with_name("meal")
+                    .long("meal")
+                    .short("m")
+                    .takes_value(true)
+                    .help("朝食、昼食などの食事の名前を指定します"))
+                .arg(Arg::with_name("no-pantry")
+                    .long("no-pantry")
+                    .help("パントリーから食べた分を減らしません"))
+                .arg(pantry_file_arg())
+                .arg(diary_arg()))
+            .subcommand(SubCommand::with_name("list")
+                .setting(AppSettings::DeriveDisplayOrder)
+                .about("記録を表示します")
+                .args(&period_arg_list())
+                .arg(diary_arg())
+                .arg(format_arg())
+                .arg(transpose_arg())))
+        .subcommand(SubCommand::with_name("pantry")
+            .setting(AppSettings::DeriveDisplayOrder)
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .about("手元にある食材を管理します")
+            .subcommand(SubCommand::with_name("add")
+                .setting(AppSettings::DeriveDisplayOrder)
+                .about("食材を追加します")
+                .arg(Arg::with_name("food")
+                    .required(true)
+                    .help("食品番号か食品名を指定します。食品名の場合は最も一致する食品にします"))
+                .arg(Arg::with_name("grams")
+                    .required(true)
+                    .help("重量(g)を指定します"))
+                .arg(Arg::with_name("expiry")
+                    .long("expiry")
+                    .short("e")
+                    .takes_value(true)
+                    .help("期限を2020-01-31のように指定します"))
+                .arg(pantry_file_arg()))
+            .subcommand(SubCommand::with_name("list")
+                .setting(AppSettings::DeriveDisplayOrder)
+                .about("食材を期限が近い順に表示します")
+                .arg(pantry_file_arg())
+                .arg(format_arg())
+                .arg(transpose_arg())))
+        .subcommand(SubCommand::with_name("report")
+            .setting(AppSettings::DeriveDisplayOrder)
+            .about("記録を摂取基準と比べて、日ごと、週ごとの割合と不足している栄養素を表示します")
+            .arg(Arg::with_name("body")
+                .long("body")
+                .short("b")
+                .takes_value(true)
+                .required(true)
+                .help("摂取基準の計算に使うbodyのJSONファイルを指定します"))
+            .args(&period_arg_list())
+            .arg(diary_arg())
+            .arg(format_arg())
+            .arg(transpose_arg()))
+        .subcommand(SubCommand::with_name("kijun")
+            .setting(AppSettings::DeriveDisplayOrder)
+            .about("bodyの摂取基準を表示します")
+            .arg(Arg::with_name("body")
+                .takes_value(true)
+                .required(true)
+                .help("bodyを含むJSONファイルを指定します"))
+            .arg(Arg::with_name("explain")
+                .long("explain")
+                .short("e")
+                .help("値の種類、年齢区分、計算に使った値、日数と、\n\
+                      求められなかった項目とその理由も表示します"))
+            .arg(Arg::with_name("days")
+                .long("days")
+                .short("d")
+                .takes_value(true)
+                .help("日数を指定します。デフォルトはbodyのdaysか1です"))
+            .arg(format_arg())
+            .arg(transpose_arg()))
+        .subcommand(SubCommand::with_name("profiles")
+            .about("食事療法のプロファイルの一覧を表示します\n\
+                    bodyのprofilesに指定すると摂取基準の一部の項目を変更します")
+            .arg(format_arg()))
+        .subcommand(SubCommand::with_name("presets")
+            .about("列のプリセットの一覧を表示します\n\
+                    プリセットは設定ファイル（MEAL_CONFIGか~/.config/meal/config.json）に追加できます")
+            .arg(format_arg()))
         .get_matches();
 
     let result = if let Some(matches) = matches.subcommand_matches("search") {
@@ -277,12 +785,30 @@ fn main() {
         calc(matches)
     } else if let Some(matches) = matches.subcommand_matches("automatic-selection") {
         automatic_selection::automatic_selection(matches)
+    } else if let Some(matches) = matches.subcommand_matches("tui") {
+        read_food_table().and_then(|foods| tui::tui(matches, &foods))
+    } else if let Some(matches) = matches.subcommand_matches("shopping-list") {
+        shopping_list(matches)
+    } else if let Some(matches) = matches.subcommand_matches("log") {
+        log(matches)
+    } else if let Some(matches) = matches.subcommand_matches("pantry") {
+        pantry(matches)
+    } else if let Some(matches) = matches.subcommand_matches("report") {
+        report(matches)
+    } else if let Some(matches) = matches.subcommand_matches("kijun") {
+        kijun(matches)
+    } else if let Some(matches) = matches.subcommand_matches("profiles") {
+        print_list(matches, "profiles", DietProfile::get_list_table())
+    } else if let Some(matches) = matches.subcommand_matches("presets") {
+        PresetList::load().and_then(|preset_list| print_list(matches, "presets", preset_list.get_table()))
     } else {
         Err("サブコマンドが間違っています".to_string())
     };
 
+    // エラーは標準エラー出力に書いて、JSONなどの出力と混ざらないようにする
     if let Err(e) = result {
-        println!("error: {}", e);
+        eprintln!("error: {}", e);
+        std::process::exit(1);
     }
 
 
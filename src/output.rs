@@ -0,0 +1,826 @@
+use std::io::IsTerminal;
+
+use prettytable::{Table, Row, Cell};
+use serde_json::{Value, Map};
+use unicode_width::UnicodeWidthStr;
+
+// 出力の形式
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+    Tsv,
+    Markdown,
+    Html,
+}
+
+impl Format {
+    pub fn from_str(format: &str) -> Result<Format, String> {
+        match format {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "markdown" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            _ => Err(format!("{} という出力形式はありません", format))
+        }
+    }
+}
+
+// 標準出力が端末でないときは色を付けない
+pub fn color(text: &str, style: &str) -> String {
+    if !std::io::stdout().is_terminal() {
+        return text.to_string()
+    }
+
+    let mut colored_text = String::new();
+    let unset_style = "\x1b[0m";
+
+    for c in style.chars() {
+        let color_style = match c {
+            'd' => "\x1b[30m",
+            'r' => "\x1b[31m",
+            'g' => "\x1b[32m",
+            'y' => "\x1b[33m",
+            'b' => "\x1b[34m",
+            'm' => "\x1b[35m",
+            'c' => "\x1b[36m",
+            'w' => "\x1b[37m",
+            'D' => "\x1b[90m",
+            'R' => "\x1b[91m",
+            'G' => "\x1b[92m",
+            'Y' => "\x1b[93m",
+            'B' => "\x1b[94m",
+            'M' => "\x1b[95m",
+            'C' => "\x1b[96m",
+            'W' => "\x1b[97m",
+            '+' => "\x1b[1m",
+            _ => continue
+        };
+
+        colored_text += color_style;
+    }
+
+    colored_text + text + unset_style
+}
+
+// 横書きの見出しを折り返す幅
+const HEADER_WRAP_WIDTH: usize = 20;
+// 表の左端の罫線の幅
+const TABLE_BORDER_WIDTH: usize = 1;
+// 列ごとの左右の余白と罫線の幅
+const COLUMN_PADDING_WIDTH: usize = 3;
+
+// 端末に表示するときの幅。全角文字は2にする
+pub fn display_width(text: &str) -> usize {
+    text.lines().map(|line| line.width()).max().unwrap_or(0)
+}
+
+// 表示幅がwidthを超えないように改行を入れる
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut line_list = vec![String::new()];
+
+    for c in text.chars() {
+        let line = line_list.last_mut().unwrap();
+        if !line.is_empty() && display_width(&format!("{}{}", line, c)) > width {
+            line_list.push(c.to_string());
+        } else {
+            line.push(c);
+        }
+    }
+
+    line_list.join("\n")
+}
+
+// 端末に表示するときの表の配置
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Layout {
+    // 端末の幅。Noneなら幅に合わせて分割しない
+    pub width: Option<usize>,
+    // 栄養素を行に、食品を列にする
+    pub is_transposed: bool,
+}
+
+impl Layout {
+    // 環境変数COLUMNSか端末の幅を使う。標準出力が端末でない場合は幅を制限しない
+    pub fn from_terminal(is_transposed: bool) -> Layout {
+        let width = match std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()) {
+            Some(width) => Some(width),
+            None if std::io::stdout().is_terminal() => {
+                terminal_size::terminal_size().map(|(width, _)| width.0 as usize)
+            },
+            None => None
+        };
+
+        Layout { width, is_transposed }
+    }
+}
+
+// 表の見出しの向きと、ページごとに表示する列の位置
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page {
+    pub is_vertical_header: bool,
+    pub index_list: Vec<usize>,
+}
+
+// 行の種類
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RowKind {
+    Food,
+    Sum,
+    Kijun,
+    Percentage,
+    ClassShare,
+    Pfc,
+    Day,
+    Week,
+    Nutrient,
+}
+
+impl RowKind {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            RowKind::Food => "food",
+            RowKind::Sum => "sum",
+            RowKind::Kijun => "kijun",
+            RowKind::Percentage => "percentage",
+            RowKind::ClassShare => "class_share",
+            RowKind::Pfc => "pfc",
+            RowKind::Day => "day",
+            RowKind::Week => "week",
+            RowKind::Nutrient => "nutrient",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputCell {
+    pub text: String,
+    // 端末に表示するときの色。colorのstyleと同じ
+    pub style: &'static str,
+    // JSONにするときの値。Noneならtextを文字列にする
+    pub value: Option<Value>,
+}
+
+impl OutputCell {
+    pub fn new(text: &str, style: &'static str) -> OutputCell {
+        OutputCell { text: text.to_string(), style, value: None }
+    }
+
+    // JSONでは数値やオブジェクトにするセル
+    pub fn with_value(text: &str, style: &'static str, value: Value) -> OutputCell {
+        OutputCell { text: text.to_string(), style, value: Some(value) }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputRow {
+    pub kind: RowKind,
+    pub cell_list: Vec<OutputCell>,
+}
+
+// 出力する表。形式に依存しない
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputTable {
+    pub column_list: Vec<String>,
+    pub unit_list: Vec<String>,
+    // 幅が足りないときに見出しを縦書きにしてよいか
+    pub is_vertical_header: bool,
+    pub row_list: Vec<OutputRow>,
+}
+
+// f32をJSONの数値にする。f32をそのままf64にすると12.3が12.300000190734863のようになるので、
+// 表示する値を読み直す
+pub fn number_to_json(num: f32) -> Value {
+    let num = num.to_string().parse().unwrap_or(num as f64);
+    serde_json::Number::from_f64(num).map(Value::Number).unwrap_or(Value::Null)
+}
+
+// 割合のセルのJSON。percentageは%の値
+pub fn percentage_to_json(percentage: f32, is_estimated: bool) -> Value {
+    let mut obj = Map::new();
+    obj.insert("kind".to_string(), Value::String("percentage".to_string()));
+    obj.insert("value".to_string(), number_to_json(percentage));
+    obj.insert("estimated".to_string(), Value::Bool(is_estimated));
+    Value::Object(obj)
+}
+
+fn escape_csv(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn escape_tsv(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+impl OutputTable {
+    pub fn new(column_list: Vec<String>, unit_list: Vec<String>, is_vertical_header: bool) -> OutputTable {
+        OutputTable {
+            column_list,
+            unit_list,
+            is_vertical_header,
+            row_list: Vec::new(),
+        }
+    }
+
+    pub fn add_row(&mut self, kind: RowKind, cell_list: Vec<OutputCell>) {
+        self.row_list.push(OutputRow { kind, cell_list });
+    }
+
+    // 食品名と、見出しを縦書きにしない表の最初の列は左寄せにする
+    fn is_left_aligned(&self, index: usize) -> bool {
+        self.column_list[index] == "食品名" || (!self.is_vertical_header && index == 0)
+    }
+
+    // 単位を付けた列の名前
+    fn get_label(&self, index: usize) -> String {
+        let unit = &self.unit_list[index];
+        if unit.is_empty() {
+            self.column_list[index].to_string()
+        } else {
+            format!("{}({})", self.column_list[index], unit)
+        }
+    }
+
+    fn get_header(&self, index: usize, is_vertical: bool) -> String {
+        let unit = &self.unit_list[index];
+        if is_vertical {
+            let mut header = self.column_list[index].chars()
+                .map(|c| c.to_string() + "\n").collect::<String>();
+            if !unit.is_empty() {
+                header += &format!("({})", unit);
+            }
+            header
+        } else {
+            wrap(&self.get_label(index), HEADER_WRAP_WIDTH)
+        }
+    }
+
+    // 列の表示幅。罫線と余白を含む
+    fn get_column_width(&self, index: usize, is_vertical: bool) -> usize {
+        let cell_width = self.row_list.iter()
+            .filter_map(|row| row.cell_list.get(index))
+            .map(|cell| display_width(&cell.text))
+            .max()
+            .unwrap_or(0);
+
+        cell_width.max(display_width(&self.get_header(index, is_vertical))) + COLUMN_PADDING_WIDTH
+    }
+
+    // ページを分けたときに、すべてのページに表示する列
+    fn get_key_index_list(&self) -> Vec<usize> {
+        match self.column_list.iter().position(|column| column == "食品名") {
+            Some(index) => vec![index],
+            None => vec![0]
+        }
+    }
+
+    // 幅に収まるなら横書き、収まらなければ縦書きにして、それでも収まらなければ列を分ける
+    pub fn get_page_list(&self, width: Option<usize>) -> Vec<Page> {
+        let all_index_list: Vec<_> = (0..self.column_list.len()).collect();
+        let width = match width {
+            Some(width) => width,
+            None => return vec![Page { is_vertical_header: false, index_list: all_index_list }]
+        };
+
+        let get_total_width = |index_list: &[usize], is_vertical| -> usize {
+            TABLE_BORDER_WIDTH + index_list.iter()
+                .map(|index| self.get_column_width(*index, is_vertical))
+                .sum::<usize>()
+        };
+
+        if get_total_width(&all_index_list, false) <= width {
+            return vec![Page { is_vertical_header: false, index_list: all_index_list }]
+        }
+        let is_vertical = self.is_vertical_header;
+        if is_vertical && get_total_width(&all_index_list, true) <= width {
+            return vec![Page { is_vertical_header: true, index_list: all_index_list }]
+        }
+
+        let key_index_list = self.get_key_index_list();
+        let mut page_list = Vec::new();
+        let mut index_list = key_index_list.clone();
+        for index in all_index_list {
+            if key_index_list.contains(&index) {
+                continue
+            }
+
+            // 1ページに少なくとも1列は表示する
+            let mut next_index_list = index_list.clone();
+            next_index_list.push(index);
+            if index_list.len() > key_index_list.len() && get_total_width(&next_index_list, is_vertical) > width {
+                page_list.push(Page { is_vertical_header: is_vertical, index_list });
+                index_list = key_index_list.clone();
+                index_list.push(index);
+            } else {
+                index_list = next_index_list;
+            }
+        }
+        if index_list.len() > key_index_list.len() || page_list.is_empty() {
+            page_list.push(Page { is_vertical_header: is_vertical, index_list });
+        }
+
+        // 元の列の順番で表示する
+        for page in &mut page_list {
+            page.index_list.sort_unstable();
+        }
+
+        page_list
+    }
+
+    // 列と行を入れ替える。最初の列は項目名にして、各行の食品名を見出しにする
+    pub fn transpose(&self) -> OutputTable {
+        let key_index = self.get_key_index_list()[0];
+        let mut column_list = vec!["項目".to_string()];
+        for row in &self.row_list {
+            let name = row.cell_list.get(key_index).map(|cell| cell.text.as_str()).unwrap_or("");
+            column_list.push(name.to_string());
+        }
+
+        let mut table = OutputTable::new(column_list.clone(), vec![String::new(); column_list.len()], false);
+        for index in 0..self.column_list.len() {
+            if index == key_index {
+                continue
+            }
+
+            let mut cell_list = vec![OutputCell::new(&self.get_label(index), "")];
+            for row in &self.row_list {
+                match row.cell_list.get(index) {
+                    Some(cell) => cell_list.push(cell.clone()),
+                    None => cell_list.push(OutputCell::new("", ""))
+                }
+            }
+            table.add_row(RowKind::Food, cell_list);
+        }
+
+        table
+    }
+
+    pub fn to_prettytable(&self, page: &Page) -> Table {
+        let mut table = Table::new();
+        table.set_format(*prettytable::format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+
+        let header: Vec<_> = page.index_list.iter().map(|index| {
+            let mut cell = Cell::new(&self.get_header(*index, page.is_vertical_header));
+            cell.align(prettytable::format::Alignment::CENTER);
+            cell
+        }).collect();
+        table.set_titles(Row::new(header));
+
+        for row in &self.row_list {
+            let cell_list = page.index_list.iter().filter_map(|index| {
+                let output_cell = row.cell_list.get(*index)?;
+                let text = if output_cell.style.is_empty() {
+                    output_cell.text.to_string()
+                } else {
+                    color(&output_cell.text, output_cell.style)
+                };
+                let mut cell = Cell::new(&text);
+                if self.is_left_aligned(*index) {
+                    cell.align(prettytable::format::Alignment::LEFT);
+                } else {
+                    cell.align(prettytable::format::Alignment::RIGHT);
+                }
+                Some(cell)
+            }).collect();
+            table.add_row(Row::new(cell_list));
+        }
+
+        table
+    }
+
+    // 端末の幅に合わせて表示する
+    pub fn print(&self, layout: Layout) {
+        let transposed;
+        let table = if layout.is_transposed {
+            transposed = self.transpose();
+            &transposed
+        } else {
+            self
+        };
+
+        let page_list = table.get_page_list(layout.width);
+        let page_count = page_list.len();
+        for (index, page) in page_list.iter().enumerate() {
+            if page_count > 1 {
+                println!("{}", color(&format!("[{}/{}]", index + 1, page_count), "D"));
+            }
+            table.to_prettytable(page).printstd();
+        }
+    }
+
+    // printと同じ表の文字列。色は付けない
+    pub fn to_text(&self, layout: Layout) -> String {
+        let transposed;
+        let table = if layout.is_transposed {
+            transposed = self.transpose();
+            &transposed
+        } else {
+            self
+        };
+
+        let mut text = String::new();
+        let page_list = table.get_page_list(layout.width);
+        let page_count = page_list.len();
+        for (index, page) in page_list.iter().enumerate() {
+            if page_count > 1 {
+                text += &format!("[{}/{}]\n", index + 1, page_count);
+            }
+            text += &table.to_prettytable(page).to_string();
+        }
+
+        text
+    }
+
+    pub fn to_json(&self) -> Value {
+        let row_list = self.row_list.iter().map(|row| {
+            let mut values = Map::new();
+            for (column, cell) in self.column_list.iter().zip(row.cell_list.iter()) {
+                let value = cell.value.clone().unwrap_or_else(|| Value::String(cell.text.to_string()));
+                values.insert(column.to_string(), value);
+            }
+
+            let mut obj = Map::new();
+            obj.insert("kind".to_string(), Value::String(row.kind.to_str().to_string()));
+            obj.insert("values".to_string(), Value::Object(values));
+            Value::Object(obj)
+        }).collect();
+
+        let mut obj = Map::new();
+        obj.insert("columns".to_string(), Value::Array(
+            self.column_list.iter().map(|c| Value::String(c.to_string())).collect()));
+        obj.insert("units".to_string(), Value::Array(
+            self.unit_list.iter().map(|u| Value::String(u.to_string())).collect()));
+        obj.insert("rows".to_string(), Value::Array(row_list));
+        Value::Object(obj)
+    }
+
+    // CSVとTSV。最初の列は行の種類にする
+    pub fn to_delimited(&self, format: Format) -> String {
+        let (separator, escape): (&str, fn(&str) -> String) = match format {
+            Format::Tsv => ("\t", escape_tsv),
+            _ => (",", escape_csv),
+        };
+
+        let mut text = std::iter::once("kind".to_string())
+            .chain(self.column_list.iter().map(|c| escape(c)))
+            .collect::<Vec<_>>().join(separator) + "\n";
+        for row in &self.row_list {
+            text += &std::iter::once(row.kind.to_str().to_string())
+                .chain(row.cell_list.iter().map(|cell| escape(&cell.text)))
+                .collect::<Vec<_>>().join(separator);
+            text += "\n";
+        }
+
+        text
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let header: Vec<_> = (0..self.column_list.len()).map(|index| {
+            let unit = &self.unit_list[index];
+            let column = escape_markdown(&self.column_list[index]);
+            if unit.is_empty() { column } else { format!("{}({})", column, unit) }
+        }).collect();
+        let align: Vec<_> = (0..self.column_list.len()).map(|index| {
+            if self.is_left_aligned(index) { ":---" } else { "---:" }
+        }).collect();
+
+        let mut text = format!("| {} |\n| {} |\n", header.join(" | "), align.join(" | "));
+        for row in &self.row_list {
+            let cell_list: Vec<_> = row.cell_list.iter().map(|cell| escape_markdown(&cell.text)).collect();
+            text += &format!("| {} |\n", cell_list.join(" | "));
+        }
+
+        text
+    }
+
+    // 行の種類はclass属性にする
+    pub fn to_html(&self, caption: Option<&str>) -> String {
+        let mut text = "<table>\n".to_string();
+        if let Some(caption) = caption {
+            text += &format!("<caption>{}</caption>\n", escape_html(caption));
+        }
+
+        text += "<thead><tr>";
+        for index in 0..self.column_list.len() {
+            let unit = &self.unit_list[index];
+            let column = escape_html(&self.column_list[index]);
+            if unit.is_empty() {
+                text += &format!("<th>{}</th>", column);
+            } else {
+                text += &format!("<th>{}({})</th>", column, escape_html(unit));
+            }
+        }
+        text += "</tr></thead>\n<tbody>\n";
+
+        for row in &self.row_list {
+            text += &format!("<tr class=\"{}\">", row.kind.to_str());
+            for cell in &row.cell_list {
+                text += &format!("<td>{}</td>", escape_html(&cell.text));
+            }
+            text += "</tr>\n";
+        }
+        text += "</tbody>\n</table>\n";
+
+        text
+    }
+}
+
+// 1回の出力の内容。検索結果やcalcの1ファイル分
+pub struct Report {
+    title: Option<String>,
+    // (キー, 端末に表示する文字列, 値)
+    value_list: Vec<(String, String, f32)>,
+    // (キー, 見出し, 表)
+    table_list: Vec<(String, Option<String>, OutputTable)>,
+    layout: Layout,
+}
+
+impl Report {
+    pub fn new(title: Option<&str>) -> Report {
+        Report {
+            title: title.map(|title| title.to_string()),
+            value_list: Vec::new(),
+            table_list: Vec::new(),
+            layout: Layout::from_terminal(false),
+        }
+    }
+
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = Some(title.to_string());
+    }
+
+    pub fn add_value(&mut self, key: &str, text: &str, value: f32) {
+        self.value_list.push((key.to_string(), text.to_string(), value));
+    }
+
+    pub fn add_table(&mut self, key: &str, caption: Option<&str>, table: OutputTable) {
+        self.table_list.push((key.to_string(), caption.map(|c| c.to_string()), table));
+    }
+
+    #[cfg(test)]
+    pub fn get_table(&self, key: &str) -> Option<&OutputTable> {
+        self.table_list.iter().find(|(k, _, _)| k == key).map(|(_, _, table)| table)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut obj = Map::new();
+        if let Some(title) = &self.title {
+            obj.insert("title".to_string(), Value::String(title.to_string()));
+        }
+        for (key, _, value) in &self.value_list {
+            obj.insert(key.to_string(), number_to_json(*value));
+        }
+        for (key, _, table) in &self.table_list {
+            obj.insert(key.to_string(), table.to_json());
+        }
+
+        Value::Object(obj)
+    }
+
+    // 出力する形式の文字列
+    pub fn render(&self, format: Format) -> String {
+        let mut text = String::new();
+
+        match format {
+            Format::Markdown => {
+                if let Some(title) = &self.title {
+                    text += &format!("## {}\n\n", escape_markdown(title));
+                }
+                for (_, value_text, _) in &self.value_list {
+                    text += &format!("{}\n\n", escape_markdown(value_text));
+                }
+                for (_, caption, table) in &self.table_list {
+                    if let Some(caption) = caption {
+                        text += &format!("### {}\n\n", escape_markdown(caption));
+                    }
+                    text += &table.to_markdown();
+                    text += "\n";
+                }
+            },
+            Format::Html => {
+                if let Some(title) = &self.title {
+                    text += &format!("<h2>{}</h2>\n", escape_html(title));
+                }
+                for (key, value_text, _) in &self.value_list {
+                    text += &format!("<p class=\"{}\">{}</p>\n", key, escape_html(value_text));
+                }
+                for (_, caption, table) in &self.table_list {
+                    text += &table.to_html(caption.as_deref());
+                }
+            },
+            Format::Csv | Format::Tsv => {
+                let separator = if format == Format::Tsv { "\t" } else { "," };
+                for (key, _, value) in &self.value_list {
+                    text += &format!("{}{}{}\n", key, separator, value);
+                }
+                if !self.value_list.is_empty() {
+                    text += "\n";
+                }
+                let table_text: Vec<_> = self.table_list.iter()
+                    .map(|(_, _, table)| table.to_delimited(format)).collect();
+                text += &table_text.join("\n");
+            },
+            // 色を付けずに、端末に表示する場合と同じ表にする
+            Format::Text => {
+                if let Some(title) = &self.title {
+                    text += &format!("{}\n", title);
+                }
+                for (_, value_text, _) in &self.value_list {
+                    text += &format!("{}\n", value_text);
+                }
+                for (_, caption, table) in &self.table_list {
+                    if let Some(caption) = caption {
+                        text += &format!("{}\n", caption);
+                    }
+                    text += &table.to_text(self.layout);
+                }
+            },
+            Format::Json => {
+                text += &serde_json::to_string_pretty(&self.to_json()).unwrap();
+                text += "\n";
+            }
+        }
+
+        text
+    }
+
+    pub fn print(&self, format: Format) {
+        match format {
+            Format::Text => {
+                if let Some(title) = &self.title {
+                    println!("{}", color(title, "g+"));
+                }
+                for (_, value_text, _) in &self.value_list {
+                    println!("{}", color(value_text, "g+"));
+                }
+                for (_, caption, table) in &self.table_list {
+                    if let Some(caption) = caption {
+                        println!("{}", color(caption, "c+"));
+                    }
+                    table.print(self.layout);
+                }
+            },
+            // JSONはprint_report_listと同じく配列にする
+            Format::Json => print_report_list(std::slice::from_ref(self), format),
+            _ => print!("{}", self.render(format))
+        }
+    }
+}
+
+// 複数の出力をまとめて表示する。JSONの場合は配列にする
+// どのサブコマンドでも、JSONはレポートが1つでも配列にして同じ形にする
+pub fn print_report_list(report_list: &[Report], format: Format) {
+    match format {
+        Format::Json => {
+            let list = Value::Array(report_list.iter().map(|report| report.to_json()).collect());
+            println!("{}", serde_json::to_string_pretty(&list).unwrap());
+        },
+        Format::Text => {
+            for report in report_list {
+                report.print(format);
+                println!();
+            }
+        },
+        _ => {
+            let text_list: Vec<_> = report_list.iter().map(|report| report.render(format)).collect();
+            print!("{}", text_list.join("\n"));
+        }
+    }
+}
+
+
+#[cfg(test)]
+fn get_test_table() -> OutputTable {
+    let mut table = OutputTable::new(
+        vec!["食品名".to_string(), "たんぱく質".to_string()],
+        vec!["".to_string(), "g".to_string()],
+        true);
+    table.add_row(RowKind::Food, vec![
+        OutputCell::new("卵, 生", ""), OutputCell::with_value("12.2", "", number_to_json(12.2))]);
+    table.add_row(RowKind::Percentage, vec![
+        OutputCell::new("割合", "g+"), OutputCell::with_value("20%", "g+", percentage_to_json(20.0, false))]);
+    table
+}
+
+#[test]
+fn test_output_format_from_str() {
+    assert_eq!(Format::from_str("json"), Ok(Format::Json));
+    assert_eq!(Format::from_str("markdown"), Ok(Format::Markdown));
+    assert!(Format::from_str("xml").is_err());
+}
+
+#[test]
+fn test_output_table_to_json() {
+    let json = get_test_table().to_json();
+    assert_eq!(json["columns"][1], "たんぱく質");
+    assert_eq!(json["units"][1], "g");
+    assert_eq!(json["rows"][0]["kind"], "food");
+    assert_eq!(json["rows"][0]["values"]["食品名"], "卵, 生");
+    assert_eq!(json["rows"][0]["values"]["たんぱく質"].to_string(), "12.2");
+    assert_eq!(json["rows"][1]["values"]["たんぱく質"]["kind"], "percentage");
+    assert_eq!(json["rows"][1]["values"]["たんぱく質"]["value"].as_f64(), Some(20.0));
+
+    // 値がないセルは数値のように見えても文字列にする
+    let mut table = OutputTable::new(vec!["食品番号".to_string()], vec![String::new()], false);
+    table.add_row(RowKind::Food, vec![OutputCell::new("01001", "")]);
+    assert_eq!(table.to_json()["rows"][0]["values"]["食品番号"], "01001");
+}
+
+#[test]
+fn test_output_table_to_delimited() {
+    let table = get_test_table();
+    assert_eq!(table.to_delimited(Format::Csv),
+               "kind,食品名,たんぱく質\nfood,\"卵, 生\",12.2\npercentage,割合,20%\n");
+    assert_eq!(table.to_delimited(Format::Tsv),
+               "kind\t食品名\tたんぱく質\nfood\t卵, 生\t12.2\npercentage\t割合\t20%\n");
+}
+
+#[test]
+fn test_output_table_to_markdown_and_html() {
+    let table = get_test_table();
+    assert_eq!(table.to_markdown(),
+               "| 食品名 | たんぱく質(g) |\n| :--- | ---: |\n| 卵, 生 | 12.2 |\n| 割合 | 20% |\n");
+    let html = table.to_html(Some("<結果>"));
+    assert!(html.contains("<caption>&lt;結果&gt;</caption>"));
+    assert!(html.contains("<tr class=\"percentage\"><td>割合</td><td>20%</td></tr>"));
+}
+
+#[test]
+fn test_report_to_json() {
+    let mut report = Report::new(Some("test.json"));
+    report.add_value("achievement", "達成率: 50.00%", 50.0);
+    report.add_table("foods", None, get_test_table());
+    let json = report.to_json();
+    assert_eq!(json["title"], "test.json");
+    assert_eq!(json["achievement"].as_f64(), Some(50.0));
+    assert_eq!(json["foods"]["rows"][1]["kind"], "percentage");
+    assert!(report.render(Format::Csv).starts_with("achievement,50\n\nkind,"));
+    assert!(report.render(Format::Json).starts_with("{"));
+
+    // テキストはJSONにしない
+    let mut report = Report::new(Some("test.json"));
+    report.add_value("achievement", "達成率: 50.00%", 50.0);
+    assert_eq!(report.render(Format::Text), "test.json\n達成率: 50.00%\n");
+}
+
+#[test]
+fn test_output_display_width() {
+    assert_eq!(display_width("abc"), 3);
+    assert_eq!(display_width("たんぱく質"), 10);
+    assert_eq!(display_width("ﾊﾝ"), 2);
+    assert_eq!(display_width("あ\nabc"), 3);
+    assert_eq!(wrap("レチノール活性当量(µg)", 10), "レチノール\n活性当量(µ\ng)");
+}
+
+#[test]
+fn test_output_table_get_page_list() {
+    let column_list = ["食品名", "A", "B", "C"].iter().map(|c| c.to_string()).collect();
+    let unit_list = ["", "g", "g", "g"].iter().map(|u| u.to_string()).collect();
+    let mut table = OutputTable::new(column_list, unit_list, true);
+    table.add_row(RowKind::Food, ["卵", "100.0", "100.0", "100.0"].iter()
+        .map(|text| OutputCell::new(text, "")).collect());
+
+    let page = |is_vertical_header, index_list: &[usize]| Page {
+        is_vertical_header, index_list: index_list.to_vec()
+    };
+    assert_eq!(table.get_page_list(None), vec![page(false, &[0, 1, 2, 3])]);
+    assert_eq!(table.get_page_list(Some(40)), vec![page(false, &[0, 1, 2, 3])]);
+    assert_eq!(table.get_page_list(Some(32)), vec![page(true, &[0, 1, 2, 3])]);
+    // 食品名はすべてのページに表示する
+    assert_eq!(table.get_page_list(Some(22)), vec![page(true, &[0, 1, 2]), page(true, &[0, 3])]);
+    assert_eq!(table.get_page_list(Some(5)),
+               vec![page(true, &[0, 1]), page(true, &[0, 2]), page(true, &[0, 3])]);
+}
+
+#[test]
+fn test_output_table_transpose() {
+    let table = get_test_table().transpose();
+    assert_eq!(table.column_list, vec!["項目", "卵, 生", "割合"]);
+    assert_eq!(table.row_list.len(), 1);
+    let text_list: Vec<_> = table.row_list[0].cell_list.iter().map(|cell| cell.text.as_str()).collect();
+    assert_eq!(text_list, vec!["たんぱく質(g)", "12.2", "20%"]);
+    assert_eq!(table.row_list[0].cell_list[2].style, "g+");
+}
@@ -0,0 +1,298 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde_json::{json, Map, Value};
+
+use crate::diary::{get_food_name, parse_date, DATE_FORMAT};
+use crate::food_table::FoodTable;
+use crate::output::{number_to_json, OutputCell, OutputTable, RowKind};
+
+// 期限までの日数がこれより少ない食材を優先する
+const EXPIRY_DAYS: i64 = 7;
+
+// 手元にある食材
+#[derive(Debug, PartialEq)]
+pub struct PantryItem {
+    pub number: String,
+    pub weight: f32,
+    pub expiry: Option<NaiveDate>,
+}
+
+impl PantryItem {
+    fn to_json(&self) -> Value {
+        let mut obj = Map::new();
+        obj.insert("number".to_string(), Value::String(self.number.to_string()));
+        obj.insert("weight".to_string(), number_to_json(self.weight));
+        if let Some(expiry) = self.expiry {
+            obj.insert("expiry".to_string(), Value::String(expiry.format(DATE_FORMAT).to_string()));
+        }
+
+        Value::Object(obj)
+    }
+
+    fn from_json(value: &Value) -> Result<PantryItem, String> {
+        let number = match value.get("number") {
+            Some(Value::String(number)) => number.to_string(),
+            _ => return Err("numberがありません".to_string())
+        };
+        let weight = match value.get("weight").and_then(|w| w.as_f64()) {
+            Some(weight) if weight >= 0.0 => weight as f32,
+            _ => return Err(format!("{}のweightは0以上の数値にしてください", number))
+        };
+        let expiry = match value.get("expiry") {
+            Some(Value::String(expiry)) => Some(parse_date(expiry)?),
+            Some(Value::Null) | None => None,
+            Some(_) => return Err(format!("{}のexpiryは文字列にしてください", number))
+        };
+
+        Ok(PantryItem { number, weight, expiry })
+    }
+
+    fn is_expired(&self, today: NaiveDate) -> bool {
+        matches!(self.expiry, Some(expiry) if expiry < today)
+    }
+}
+
+// パントリーのファイルの場所。MEAL_PANTRYがあればそれを使い、なければ~/.local/share/meal/pantry.json
+pub fn get_pantry_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("MEAL_PANTRY") {
+        return Some(PathBuf::from(path))
+    }
+
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".local").join("share").join("meal").join("pantry.json"))
+}
+
+pub struct Pantry {
+    item_list: Vec<PantryItem>,
+}
+
+impl Pantry {
+    pub fn new() -> Pantry {
+        Pantry { item_list: Vec::new() }
+    }
+
+    // {"items": [{"number": "01001", "weight": 300, "expiry": "2020-01-31"}, ...]}
+    pub fn from_json(value: &Value) -> Result<Pantry, String> {
+        let item_list = match value.get("items") {
+            Some(Value::Array(items)) => items.iter()
+                .map(PantryItem::from_json)
+                .collect::<Result<Vec<_>, String>>()?,
+            _ => return Err("itemsの値は配列にしてください".to_string())
+        };
+
+        Ok(Pantry { item_list })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "items": self.item_list.iter().map(|item| item.to_json()).collect::<Vec<_>>() })
+    }
+
+    // ファイルがなければ空にする
+    pub fn read(path: &Path) -> Result<Pantry, String> {
+        if !path.exists() {
+            return Ok(Pantry::new())
+        }
+
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => return Err(format!("{}: {}", path.display(), e))
+        };
+        let value: Value = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Pantry::from_json(&value).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+        }
+
+        let text = serde_json::to_string_pretty(&self.to_json()).map_err(|e| e.to_string())?;
+        std::fs::write(path, text + "\n").map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // 同じ食品で期限も同じなら重量を足す
+    pub fn add(&mut self, number: &str, weight: f32, expiry: Option<NaiveDate>) {
+        match self.item_list.iter_mut().find(|item| item.number == number && item.expiry == expiry) {
+            Some(item) => item.weight += weight,
+            None => self.item_list.push(PantryItem { number: number.to_string(), weight, expiry })
+        }
+    }
+
+    pub fn contains(&self, number: &str) -> bool {
+        self.item_list.iter().any(|item| item.number == number)
+    }
+
+    // 期限が切れたものも含めた食材の重量
+    pub fn get_total_weight(&self, number: &str) -> f32 {
+        self.item_list.iter()
+            .filter(|item| item.number == number)
+            .map(|item| item.weight)
+            .sum()
+    }
+
+    // 期限が切れていない食材の重量
+    pub fn get_weight(&self, number: &str, today: NaiveDate) -> f32 {
+        self.item_list.iter()
+            .filter(|item| item.number == number && !item.is_expired(today))
+            .map(|item| item.weight)
+            .sum()
+    }
+
+    // 期限が切れていない食材の食品番号。同じ食品は1つにする
+    pub fn get_number_list(&self, today: NaiveDate) -> Vec<String> {
+        let mut number_list: Vec<String> = Vec::new();
+        for item in &self.item_list {
+            if item.weight > 0.0 && !item.is_expired(today) && !number_list.contains(&item.number) {
+                number_list.push(item.number.to_string());
+            }
+        }
+        number_list
+    }
+
+    // 期限が近いほど大きくなる0から1の値。期限が切れたものと期限がないものは0
+    pub fn get_expiry_score(&self, number: &str, today: NaiveDate) -> f32 {
+        self.item_list.iter()
+            .filter(|item| item.number == number && item.weight > 0.0)
+            .filter_map(|item| item.expiry)
+            .map(|expiry| (expiry - today).num_days())
+            .filter(|days| (0..EXPIRY_DAYS).contains(days))
+            .map(|days| (EXPIRY_DAYS - days) as f32 / EXPIRY_DAYS as f32)
+            .fold(0.0, f32::max)
+    }
+
+    // 期限が近いものから減らす。使い切った食材は消す。減らした重量を返す
+    pub fn consume(&mut self, number: &str, weight: f32) -> f32 {
+        let mut index_list: Vec<usize> = (0..self.item_list.len())
+            .filter(|index| self.item_list[*index].number == number)
+            .collect();
+        // 期限がないものは最後にする
+        index_list.sort_by_key(|index| {
+            let expiry = self.item_list[*index].expiry;
+            (expiry.is_none(), expiry)
+        });
+
+        let mut rest = weight;
+        for index in index_list {
+            let item = &mut self.item_list[index];
+            let used = rest.min(item.weight);
+            item.weight -= used;
+            rest -= used;
+            if rest <= 0.0 {
+                break
+            }
+        }
+        self.item_list.retain(|item| item.weight > 0.0);
+
+        weight - rest
+    }
+
+    pub fn get_table(&self, foods: &FoodTable, today: NaiveDate) -> OutputTable {
+        let mut table = OutputTable::new(
+            ["食品番号", "食品名", "重量", "期限", "残り日数"].iter().map(|c| c.to_string()).collect(),
+            ["", "", "g", "", "日"].iter().map(|u| u.to_string()).collect(),
+            false);
+
+        let mut item_list: Vec<_> = self.item_list.iter().collect();
+        item_list.sort_by_key(|item| (item.expiry.is_none(), item.expiry));
+        for item in item_list {
+            let (expiry, days, style) = match item.expiry {
+                Some(expiry) => {
+                    let days = (expiry - today).num_days();
+                    let style = if days < 0 {
+                        "r"
+                    } else if days < EXPIRY_DAYS {
+                        "y"
+                    } else {
+                        ""
+                    };
+                    let days = OutputCell::with_value(&days.to_string(), style, Value::from(days));
+                    (expiry.format(DATE_FORMAT).to_string(), days, style)
+                },
+                None => ("-".to_string(), OutputCell::with_value("-", "", Value::Null), "")
+            };
+
+            table.add_row(RowKind::Food, vec![
+                OutputCell::new(&item.number, ""),
+                OutputCell::new(&get_food_name(foods, &item.number), ""),
+                OutputCell::with_value(&item.weight.to_string(), "", number_to_json(item.weight)),
+                OutputCell::new(&expiry, style),
+                days,
+            ]);
+        }
+
+        table
+    }
+}
+
+
+#[cfg(test)]
+fn get_test_pantry() -> Pantry {
+    Pantry::from_json(&json!({"items": [
+        {"number": "01001", "weight": 100, "expiry": "2020-01-10"},
+        {"number": "01001", "weight": 50, "expiry": "2020-01-03"},
+        {"number": "01002", "weight": 200},
+        {"number": "01003", "weight": 30, "expiry": "2019-12-31"}
+    ]})).unwrap()
+}
+
+#[test]
+fn test_pantry_from_json() {
+    let pantry = get_test_pantry();
+    assert_eq!(pantry.item_list.len(), 4);
+    assert_eq!(Pantry::from_json(&pantry.to_json()).unwrap().item_list, pantry.item_list);
+    assert!(Pantry::from_json(&json!({"items": [{"number": "01001"}]})).is_err());
+    assert!(Pantry::from_json(&json!({"items": [{"number": "01001", "weight": 1, "expiry": "1/1"}]})).is_err());
+
+    let mut pantry = Pantry::from_json(&json!({"items": []})).unwrap();
+    pantry.add("01001", 12.3, None);
+    assert_eq!(pantry.to_json()["items"][0]["weight"].to_string(), "12.3");
+}
+
+#[test]
+fn test_pantry_get_weight_and_expiry_score() {
+    let pantry = get_test_pantry();
+    let today = parse_date("2020-01-01").unwrap();
+    assert_eq!(pantry.get_weight("01001", today), 150.0);
+    // 期限が切れたものは使えない
+    assert_eq!(pantry.get_weight("01003", today), 0.0);
+    assert_eq!(pantry.get_total_weight("01003"), 30.0);
+    assert_eq!(pantry.get_number_list(today), vec!["01001", "01002"]);
+
+    // 2日後の期限が最も近い
+    assert_eq!(pantry.get_expiry_score("01001", today), 5.0 / 7.0);
+    assert_eq!(pantry.get_expiry_score("01002", today), 0.0);
+    assert_eq!(pantry.get_expiry_score("01003", today), 0.0);
+}
+
+#[test]
+fn test_pantry_consume() {
+    let mut pantry = get_test_pantry();
+    // 期限が近い50gを使い切ってから、残りを減らす
+    assert_eq!(pantry.consume("01001", 80.0), 80.0);
+    assert_eq!(pantry.item_list.iter().filter(|item| item.number == "01001").count(), 1);
+    assert_eq!(pantry.get_weight("01001", parse_date("2020-01-01").unwrap()), 70.0);
+
+    // ない分は減らせない
+    assert_eq!(pantry.consume("01002", 300.0), 200.0);
+    assert!(!pantry.contains("01002"));
+    assert_eq!(pantry.consume("99999", 10.0), 0.0);
+
+    pantry.add("01001", 30.0, parse_date("2020-01-10").ok());
+    assert_eq!(pantry.item_list.len(), 2);
+}
+
+#[test]
+fn test_pantry_write_and_read() {
+    let path = std::env::temp_dir().join("meal_test_pantry.json");
+    let pantry = get_test_pantry();
+    pantry.write(&path).unwrap();
+    assert_eq!(Pantry::read(&path).unwrap().item_list, pantry.item_list);
+    std::fs::remove_file(&path).unwrap();
+    assert!(Pantry::read(&path).unwrap().item_list.is_empty());
+}
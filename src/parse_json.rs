@@ -3,7 +3,14 @@ use std::collections::HashMap;
 
 use serde_json::Value;
 
-use crate::kijun::{Gender, PAL};
+use crate::kijun::{Activity, EnergyMethod, Gender, Kijun, KijunValue, PAL};
+use crate::diet_profile::DietProfile;
+use crate::food::Food;
+use crate::food::food_data::{FoodData, TraceMode};
+use crate::food_table::FoodTable;
+use crate::food::nutrient::Nutrient;
+use crate::output::{number_to_json, OutputCell, OutputTable, RowKind};
+use crate::restriction::Restriction;
 
 macro_rules! value_or_error {
     ($option:expr, $error:expr) => {
@@ -20,7 +27,67 @@ pub struct ParsedData {
     pub body: Body,
     pub comb: Option<Vec<usize>>,
     pub user_definition_foods: Option<Vec<UserDefinitionFood>>,
-    pub options: Options
+    pub options: Options,
+    pub purchase_units: HashMap<String, PurchaseUnit>
+}
+
+impl ParsedData {
+    // user_definition_foodsの食品の表
+    pub fn get_user_definition_table(&self) -> FoodTable {
+        let mut udf_table = FoodTable::new();
+        if let Some(udfs) = &self.user_definition_foods {
+            for udf in udfs {
+                let mut food = Food::new();
+                // 食品番号の先頭にuをつける
+                // すでにある食品番号と被らないように
+                food.set("食品番号", FoodData::String("u".to_string() + &udf.number));
+                food.set("重量", FoodData::Number(udf.weight));
+                for (name, food_data) in &udf.data {
+                    food.set_str(name, food_data)
+                }
+                udf_table.add(food)
+            }
+        }
+
+        udf_table
+    }
+
+    // foodsの食品の表。価格、クラス、廃棄部分とoptionsのtraceを反映する
+    // calcとtuiで同じ献立になるように、JSONの献立はこれで読み込む
+    pub fn get_food_table(&self, foods: &FoodTable) -> Result<FoodTable, String> {
+        let udf_table = self.get_user_definition_table();
+
+        let mut food_table = FoodTable::new();
+        food_table.set_trace_mode(self.options.trace);
+        for parsed_food in &self.foods {
+            let mut food = match foods.get(&parsed_food.number) {
+                Some(food) => food.change_weight(parsed_food.weight.unwrap_or(100.0)).unwrap(),
+                None => match udf_table.get(&parsed_food.number) {
+                    Some(food) => food.change_weight(parsed_food.weight.unwrap_or(100.0)).unwrap(),
+                    None => return Err(format!("{}番の食材はありません。JSONの値が間違っています", &parsed_food.number))
+                }
+            };
+
+            if let Some(price) = parsed_food.price {
+                food.set("価格", FoodData::Number(price));
+            }
+
+            if let Some(class) = &parsed_food.class {
+                food.set("クラス", FoodData::String(class.to_string()));
+            }
+
+            if parsed_food.include_refuse {
+                food = match food.include_refuse() {
+                    Some(food) => food,
+                    None => return Err(format!("{}番の食材は廃棄部分を含めることはできません", &parsed_food.number))
+                };
+            }
+
+            food_table.add(food);
+        }
+
+        Ok(food_table)
+    }
 }
 
 pub struct ParsedFood {
@@ -37,23 +104,130 @@ pub struct UserDefinitionFood {
     pub data: HashMap<String, String>
 }
 
+// 買うときの単位。納豆1パック45gなど
+#[derive(Debug, PartialEq)]
+pub struct PurchaseUnit {
+    pub name: String,
+    pub weight: f32
+}
+
 pub struct Body {
     pub age: usize,
     pub weight: f32,
     pub height: f32,
     pub gender: Gender,
     pub pal: PAL,
-    pub days: Option<usize>
+    pub days: Option<usize>,
+    // アレルゲンや食事のタイプの制限
+    pub restrictions: Vec<Restriction>,
+    // 摂取基準を変更する食事療法のプロファイル。後のプロファイルを優先する
+    pub profiles: Vec<DietProfile>,
+    // 項目ごとに変更する1日分の摂取基準。Noneは項目を除く。プロファイルより優先する
+    pub targets: Vec<(String, Option<KijunValue>)>,
+    // 摂取基準の計算に使う目標体重
+    pub goal_weight: Option<f32>,
+    // 減量や増量のための1日あたりのエネルギーの増減(kcal)
+    pub energy_change: f32,
+    // エネルギー必要量の求め方
+    pub energy_method: EnergyMethod
+}
+
+impl Body {
+    // プロファイルを反映した摂取基準
+    // 指定したエネルギー必要量の求め方で求められない場合はエラー
+    pub fn get_kijun(&self, days: usize) -> Result<Kijun, String> {
+        let mut kijun = Kijun::new(self.age, self.get_kijun_weight(), self.height, self.gender, self.pal, days);
+        if self.energy_method != EnergyMethod::Ganpule {
+            if let Err(e) = kijun.change_energy_method(self.energy_method) {
+                return Err(format!("{}でエネルギー必要量を求めることができません: {}",
+                                   self.energy_method.get_name(), e))
+            }
+        }
+        if self.energy_change != 0.0 {
+            kijun.change_energy(self.energy_change);
+        }
+        for profile in &self.profiles {
+            profile.apply(&mut kijun);
+        }
+        for (key, target) in &self.targets {
+            match target {
+                Some(kijun_value) => kijun.set_override(key, *kijun_value, "targets"),
+                None => kijun.remove_override(key, "targets")
+            }
+        }
+        Ok(kijun)
+    }
+
+    // 目標体重があれば目標体重で摂取基準を求める
+    pub fn get_kijun_weight(&self) -> f32 {
+        self.goal_weight.unwrap_or(self.weight)
+    }
+
+    // 体重、BMI、目標体重、エネルギーの増減
+    pub fn get_table(&self) -> OutputTable {
+        let mut table = OutputTable::new(
+            ["項目", "値", "単位"].iter().map(|c| c.to_string()).collect(),
+            vec![String::new(); 3],
+            false);
+
+        let bmi = Kijun::get_bmi(self.weight, self.height);
+        // 目標とするBMIの範囲外なら色を付ける
+        let (target_bmi, style) = match Kijun::get_target_bmi(self.age) {
+            Ok(target @ KijunValue::Range((min, max))) => {
+                let style = if bmi < min || max < bmi { "r" } else { "" };
+                (OutputCell::with_value(&format!("{} ~ {}", min, max), "", target.to_json()), style)
+            },
+            _ => (OutputCell::with_value("-", "", Value::Null), "")
+        };
+        // 数値の値はJSONでも数値にする
+        let number = |text: String, num: f32, style| OutputCell::with_value(&text, style, number_to_json(num));
+        let standard_weight = Kijun::get_standard_weight(self.height);
+
+        let mut row_list = vec![
+            ("体重", number(format!("{}", self.weight), self.weight, ""), "kg"),
+            ("BMI", number(format!("{:.1}", bmi), bmi, style), ""),
+            ("目標とするBMI", target_bmi, ""),
+            ("標準体重(BMI 22)", number(format!("{:.1}", standard_weight), standard_weight, ""), "kg"),
+        ];
+        if let Some(goal_weight) = self.goal_weight {
+            let goal_bmi = Kijun::get_bmi(goal_weight, self.height);
+            row_list.push(("目標体重", number(format!("{:.1}", goal_weight), goal_weight, "m+"), "kg"));
+            row_list.push(("目標体重のBMI", number(format!("{:.1}", goal_bmi), goal_bmi, ""), ""));
+        }
+        let energy_method = match self.energy_method {
+            EnergyMethod::MeasuredTee(_) => self.energy_method.get_name(),
+            _ => match Kijun::get_pal(self.age, self.pal) {
+                Ok(pal) => format!("{} × 身体活動レベル {}", self.energy_method.get_name(), pal),
+                Err(_) => self.energy_method.get_name()
+            }
+        };
+        row_list.push(("エネルギーの求め方", OutputCell::new(&energy_method, ""), ""));
+        if self.energy_change != 0.0 {
+            row_list.push(("エネルギーの増減", number(format!("{:+.0}", self.energy_change), self.energy_change, "m+"), "kcal/日"));
+        }
+
+        for (name, value, unit) in row_list {
+            table.add_row(RowKind::Food, vec![
+                OutputCell::new(name, ""),
+                value,
+                OutputCell::new(unit, ""),
+            ]);
+        }
+
+        table
+    }
 }
 
 pub struct Options {
-    pub show_status: bool
+    pub show_status: bool,
+    pub trace: TraceMode
 }
 
 impl Options {
     fn new() -> Options {
         Options {
-            show_status: false
+            show_status: false,
+            trace: TraceMode::Zero
         }
     }
 
@@ -62,6 +236,10 @@ impl Options {
             "show_status" => {
                 self.show_status = value_or_error!(v.as_bool(), "show_statusの値はboolにしてください");
             },
+            "trace" => {
+                let trace = value_or_error!(v.as_str(), "traceの値は文字列にしてください");
+                self.trace = TraceMode::from_str(trace)?;
+            },
             _ => return Err(format!("{} というオプションはありません", k))
         }
 
@@ -145,13 +323,15 @@ pub fn parse_name_list(data: &Value) -> Result<Vec<String>, String> {
     let mut parsed_name_list = Vec::new();
     let name_list = match data {
         Value::Array(name_list) => name_list,
-        _ => return Err("name_listの値は文字列の配列にしてください".to_string())
+        // "preset:kijun"のように1つの文字列でもよい
+        Value::String(name) => return Ok(vec![name.to_string()]),
+        _ => return Err("name_listの値は文字列か文字列の配列にしてください".to_string())
     };
 
     for name in name_list {
         let parsed_name = match name {
             Value::String(parsed_name) => parsed_name.clone(),
-            _ => return Err("name_listの値は文字列の配列にしてください".to_string())
+            _ => return Err("name_listの値は文字列か文字列の配列にしてください".to_string())
         };
 
         parsed_name_list.push(parsed_name)
@@ -160,6 +340,146 @@ pub fn parse_name_list(data: &Value) -> Result<Vec<String>, String> {
     Ok(parsed_name_list)
 }
 
+// {"04046": {"name": "パック", "weight": 45}} か {"04046": 45}
+pub fn parse_purchase_units(data: &Value) -> Result<HashMap<String, PurchaseUnit>, String> {
+    let obj = match data {
+        Value::Object(obj) => obj,
+        _ => return Err("purchase_unitsの値はオブジェクトにしてください".to_string())
+    };
+
+    let mut purchase_units = HashMap::new();
+    for (number, value) in obj {
+        let (name, weight) = match value {
+            Value::Number(weight) => ("個".to_string(), weight.as_f64()),
+            Value::Object(unit) => {
+                let name = match unit.get("name") {
+                    Some(Value::String(name)) => name.to_string(),
+                    None => "個".to_string(),
+                    _ => return Err(format!("purchase_unitsの{}のnameは文字列にしてください", number))
+                };
+                (name, unit.get("weight").and_then(|w| w.as_f64()))
+            },
+            _ => return Err(format!("purchase_unitsの{}の値は数値かオブジェクトにしてください", number))
+        };
+
+        match weight {
+            Some(weight) if weight > 0.0 => {
+                purchase_units.insert(number.to_string(), PurchaseUnit { name, weight: weight as f32 });
+            },
+            _ => return Err(format!("purchase_unitsの{}のweightは0より大きい数値にしてください", number))
+        }
+    }
+
+    Ok(purchase_units)
+}
+
+// {"たんぱく質": {"kind": "more", "amount": 120}, "脂質": {"kind": "range", "amount": [40, 60]}, "ヨウ素": null}
+pub fn parse_targets(data: &Value) -> Result<Vec<(String, Option<KijunValue>)>, String> {
+    let obj = match data {
+        Value::Object(obj) => obj,
+        _ => return Err("targetsの値はオブジェクトにしてください".to_string())
+    };
+
+    let mut targets = Vec::new();
+    for (key, value) in obj {
+        // 食品の成分と比較できる項目だけにする
+        if Nutrient::from_name(key).is_none() || Kijun::get_unit(key).is_none() {
+            return Err(format!("targetsの{}は摂取基準にできない項目です", key))
+        }
+
+        let target = match value {
+            Value::Null => None,
+            Value::Object(target) => {
+                let kind = match target.get("kind") {
+                    Some(Value::String(kind)) => kind.to_lowercase(),
+                    _ => return Err(format!("targetsの{}のkindは文字列にしてください", key))
+                };
+                let amount = value_or_error!(target.get("amount"), format!("targetsの{}にamountがありません", key));
+                let number = |value: &Value| -> Result<f32, String> {
+                    match value.as_f64() {
+                        Some(num) if num >= 0.0 => Ok(num as f32),
+                        _ => Err(format!("targetsの{}のamountは0以上の数値にしてください", key))
+                    }
+                };
+
+                let kijun_value = match kind.as_str() {
+                    "suisyo" => KijunValue::Suisyo(number(amount)?),
+                    "measu" => KijunValue::Measu(number(amount)?),
+                    "less" => KijunValue::Less(number(amount)?),
+                    "more" => KijunValue::More(number(amount)?),
+                    "range" => match amount.as_array().map(|amount| amount.as_slice()) {
+                        Some([min, max]) => {
+                            let (min, max) = (number(min)?, number(max)?);
+                            if max < min {
+                                return Err(format!("targetsの{}の範囲は小さい値から順にしてください", key))
+                            }
+                            KijunValue::Range((min, max))
+                        },
+                        _ => return Err(format!("targetsの{}のamountは[最小, 最大]の配列にしてください", key))
+                    },
+                    _ => return Err(format!("targetsの{}のkindは \"suisyo\", \"measu\", \"less\", \"more\", \"range\" \
+                                             のいずれかにしてください", key))
+                };
+                Some(kijun_value)
+            },
+            _ => return Err(format!("targetsの{}の値はオブジェクトかnullにしてください", key))
+        };
+
+        targets.push((key.to_string(), target));
+    }
+
+    Ok(targets)
+}
+
+// 脂肪1kgあたりのエネルギー(kcal)
+const ENERGY_PER_KG: f32 = 7200.0;
+
+// {"kcal_per_day": -500} か {"kg_per_week": -0.5}。1日あたりのエネルギーの増減にする
+pub fn parse_weight_plan(data: &Value) -> Result<f32, String> {
+    let obj = match data {
+        Value::Object(obj) => obj,
+        _ => return Err("weight_planの値はオブジェクトにしてください".to_string())
+    };
+
+    match (obj.get("kcal_per_day"), obj.get("kg_per_week")) {
+        (Some(kcal), None) => {
+            let kcal = value_or_error!(kcal.as_f64(), "weight_planのkcal_per_dayの値は数値にしてください");
+            Ok(kcal as f32)
+        },
+        (None, Some(kg)) => {
+            let kg = value_or_error!(kg.as_f64(), "weight_planのkg_per_weekの値は数値にしてください");
+            Ok(kg as f32 * ENERGY_PER_KG / 7.0)
+        },
+        _ => Err("weight_planにはkcal_per_dayかkg_per_weekのどちらかを指定してください".to_string())
+    }
+}
+
+// [{"name": "ランニング", "mets": 8, "minutes": 30}, ...]
+pub fn parse_activities(data: &Value) -> Result<Vec<Activity>, String> {
+    let values = value_or_error!(data.as_array(), "activitiesの値はオブジェクトの配列にしてください");
+
+    let mut activity_list = Vec::new();
+    for value in values {
+        let name = match value.get("name") {
+            Some(Value::String(name)) => name.to_string(),
+            None => String::new(),
+            _ => return Err("activitiesのnameの値は文字列にしてください".to_string())
+        };
+        let mets = match value.get("mets").and_then(|mets| mets.as_f64()) {
+            Some(mets) if mets > 0.0 => mets as f32,
+            _ => return Err(format!("activitiesの{}のmetsは0より大きい数値にしてください", name))
+        };
+        let minutes = match value.get("minutes").and_then(|minutes| minutes.as_f64()) {
+            Some(minutes) if minutes >= 0.0 => minutes as f32,
+            _ => return Err(format!("activitiesの{}のminutesは0以上の数値にしてください", name))
+        };
+
+        activity_list.push(Activity { name, mets, minutes });
+    }
+
+    Ok(activity_list)
+}
+
 pub fn parse_body(data: &Value) -> Result<Body, String> {
     let obj = match data {
         Value::Object(obj) => obj,
@@ -213,19 +533,26 @@ pub fn parse_body(data: &Value) -> Result<Body, String> {
         None => return Err("bodyにgender属性がありません".to_string())
     };
 
-    let pal = match obj.get("pal") {
-        Some(value) => match value {
+    // palは文字列か数値。activitiesがあれば活動から求める
+    let pal = match (obj.get("pal"), obj.get("activities")) {
+        (Some(_), Some(_)) => return Err("palとactivitiesはどちらか一方にしてください".to_string()),
+        (Some(value), None) => match value {
             Value::String(pal) => match pal.as_str() {
                 "low" => PAL::Low,
                 "moderate" => PAL::Moderate,
                 "high" => PAL::High,
                 _ => return Err("palの値は \"low\", \"moderate\", \"high\" \
-                             のいずれかの文字列にしてください".to_string())
+                             のいずれかの文字列か数値にしてください".to_string())
+            },
+            Value::Number(num) => match num.as_f64() {
+                Some(num) if num > 0.0 => PAL::Value(num as f32),
+                _ => return Err("palの値は0より大きい数値にしてください".to_string())
             },
             _ => return Err("palの値は \"low\", \"moderate\", \"high\" \
-                             のいずれかの文字列にしてください".to_string())
+                             のいずれかの文字列か数値にしてください".to_string())
         },
-        None => return Err("bodyにpal属性がありません".to_string())
+        (None, Some(value)) => PAL::from_activity_list(&parse_activities(value)?)?,
+        (None, None) => return Err("bodyにpal属性がありません".to_string())
     };
 
     let days = match obj.get("days") {
@@ -233,6 +560,63 @@ pub fn parse_body(data: &Value) -> Result<Body, String> {
         None => None
     };
 
+    let restrictions = match obj.get("restrictions") {
+        Some(Value::Array(values)) => {
+            let mut restrictions = Vec::new();
+            for value in values {
+                let text = value_or_error!(value.as_str(), "restrictionsの値は文字列の配列にしてください");
+                restrictions.push(Restriction::from_str(text)?);
+            }
+            restrictions
+        },
+        Some(_) => return Err("restrictionsの値は文字列の配列にしてください".to_string()),
+        None => Vec::new()
+    };
+
+    // "ckd"か["ckd", "hypertension"]
+    let profiles = match obj.get("profiles") {
+        Some(value) => parse_name_list(value)
+            .map_err(|_| "profilesの値は文字列か文字列の配列にしてください".to_string())?
+            .iter()
+            .map(|name| DietProfile::from_str(name))
+            .collect::<Result<Vec<_>, String>>()?,
+        None => Vec::new()
+    };
+
+    let targets = match obj.get("targets") {
+        Some(value) => parse_targets(value)?,
+        None => Vec::new()
+    };
+
+    // 数値か、標準体重にする"standard"
+    let goal_weight = match obj.get("goal_weight") {
+        Some(Value::String(text)) if text == "standard" => Some(Kijun::get_standard_weight(height)),
+        Some(value) => match value.as_f64() {
+            Some(goal_weight) if goal_weight > 0.0 => Some(goal_weight as f32),
+            _ => return Err("goal_weightの値は0より大きい数値か \"standard\" にしてください".to_string())
+        },
+        None => None
+    };
+
+    let energy_change = match obj.get("weight_plan") {
+        Some(value) => parse_weight_plan(value)?,
+        None => 0.0
+    };
+
+    // "bmr"は式の名前か測定した基礎代謝量、"tee"は総エネルギー消費量
+    let energy_method = match (obj.get("bmr"), obj.get("tee")) {
+        (Some(_), Some(_)) => return Err("bmrとteeはどちらか一方にしてください".to_string()),
+        (Some(Value::String(formula)), None) => EnergyMethod::from_str(formula)?,
+        (Some(bmr), None) => match bmr.as_f64() {
+            Some(bmr) if bmr > 0.0 => EnergyMethod::MeasuredBmr(bmr as f32),
+            _ => return Err("bmrの値は式の名前か0より大きい数値にしてください".to_string())
+        },
+        (None, Some(tee)) => match tee.as_f64() {
+            Some(tee) if tee > 0.0 => EnergyMethod::MeasuredTee(tee as f32),
+            _ => return Err("teeの値は0より大きい数値にしてください".to_string())
+        },
+        (None, None) => EnergyMethod::Ganpule
+    };
 
     Ok(Body {
         age,
@@ -240,7 +624,13 @@ pub fn parse_body(data: &Value) -> Result<Body, String> {
         height,
         gender,
         pal,
-        days
+        days,
+        restrictions,
+        profiles,
+        targets,
+        goal_weight,
+        energy_change,
+        energy_method
     })
 }
 
@@ -304,6 +694,11 @@ pub fn parse_json<T: std::io::Read>(reader: BufReader<T>) -> Result<ParsedData,
         Err(e) => return Err(e.to_string())
     };
 
+    parse_json_value(&data)
+}
+
+// 読み込んだJSONの値から。tuiで編集中の献立に使う
+pub fn parse_json_value(data: &Value) -> Result<ParsedData, String> {
     let obj = match data {
         Value::Object(obj) => obj,
         _ => return Err("Cannot load json".to_string())
@@ -317,15 +712,16 @@ pub fn parse_json<T: std::io::Read>(reader: BufReader<T>) -> Result<ParsedData,
         }
     };
 
+    // name_listがない場合は摂取基準と比べるための列にする
     let name_list = match obj.get("name_list") {
-        None => return Err("name_list属性がありません".to_string()),
+        None => vec!["preset:kijun".to_string()],
         Some(value) => match parse_name_list(value) {
             Ok(name_list) => name_list,
             Err(e) => return Err(e)
         }
     };
 
-    let body = match obj.get("body") {
+    let mut body = match obj.get("body") {
         None => return Err("body属性がありません".to_string()),
         Some(value) => match parse_body(value) {
             Ok(body) => body,
@@ -333,6 +729,11 @@ pub fn parse_json<T: std::io::Read>(reader: BufReader<T>) -> Result<ParsedData,
         }
     };
 
+    // トップレベルのtargetsはbodyのtargetsより優先する
+    if let Some(value) = obj.get("targets") {
+        body.targets.extend(parse_targets(value)?);
+    }
+
     let comb = match obj.get("combination") {
         None => None,
         Some(value) => match parse_combination(value) {
@@ -358,16 +759,41 @@ pub fn parse_json<T: std::io::Read>(reader: BufReader<T>) -> Result<ParsedData,
     };
 
 
+    let purchase_units = match obj.get("purchase_units") {
+        None => HashMap::new(),
+        Some(value) => parse_purchase_units(value)?
+    };
+
     Ok(ParsedData {
         foods,
         name_list,
         body,
         comb,
         user_definition_foods,
-        options
+        options,
+        purchase_units
     })
 }
 
+// bodyだけを読み込む。calcのJSONでも、bodyのオブジェクトだけのJSONでもよい
+pub fn parse_body_json<T: std::io::Read>(reader: BufReader<T>) -> Result<Body, String> {
+    let data: Value = match serde_json::from_reader(reader) {
+        Ok(data) => data,
+        Err(e) => return Err(e.to_string())
+    };
+
+    match data.get("body") {
+        Some(value) => {
+            let mut body = parse_body(value)?;
+            if let Some(value) = data.get("targets") {
+                body.targets.extend(parse_targets(value)?);
+            }
+            Ok(body)
+        },
+        None => parse_body(&data)
+    }
+}
+
 #[test]
 fn test_parse_foods() {
     let test_json = r#"{
@@ -400,3 +826,201 @@ fn test_parse_foods() {
     assert_eq!(parsed_data.foods[2].weight, Some(50.0));
     assert_eq!(parsed_data.foods[2].include_refuse, true);
 }
+
+#[test]
+fn test_parse_json_default_name_list() {
+    let test_json = r#"{
+    "foods": [],
+    "body": {
+        "age": 20,
+        "weight": 50,
+        "height": 160,
+        "gender": "male",
+        "pal": "low"
+    }
+}"#;
+    let parsed_data = parse_json(BufReader::new(test_json.as_bytes())).unwrap();
+    assert_eq!(parsed_data.name_list, vec!["preset:kijun"]);
+}
+
+#[test]
+fn test_parse_options() {
+    let test_json = r#"{
+    "foods": [],
+    "name_list": [],
+    "body": {
+        "age": 20,
+        "weight": 50,
+        "height": 160,
+        "gender": "male",
+        "pal": "low"
+    },
+    "options": {
+        "trace": "half"
+    }
+}"#;
+    let reader = BufReader::new(test_json.as_bytes());
+    let parsed_data = parse_json(reader).unwrap();
+    assert_eq!(parsed_data.options.trace, TraceMode::Half);
+
+    let test_json = test_json.replace("half", "one");
+    let reader = BufReader::new(test_json.as_bytes());
+    assert!(parse_json(reader).is_err());
+}
+
+#[test]
+fn test_parse_body_json() {
+    let test_json = r#"{
+    "age": 20,
+    "weight": 50,
+    "height": 160,
+    "gender": "male",
+    "pal": "low"
+}"#;
+    let body = parse_body_json(BufReader::new(test_json.as_bytes())).unwrap();
+    assert_eq!(body.age, 20);
+    assert!(body.restrictions.is_empty());
+
+    let test_json = format!(r#"{{"foods": [], "body": {}}}"#, test_json);
+    let body = parse_body_json(BufReader::new(test_json.as_bytes())).unwrap();
+    assert_eq!(body.height, 160.0);
+
+    assert!(parse_body_json(BufReader::new("{}".as_bytes())).is_err());
+
+    let test_json = test_json.replace(r#""age": 20"#, r#""age": 20, "restrictions": ["卵", "vegan"]"#);
+    let body = parse_body_json(BufReader::new(test_json.as_bytes())).unwrap();
+    assert_eq!(body.restrictions, vec![Restriction::Allergen(crate::restriction::Tag::Egg), Restriction::Vegan]);
+
+    let test_json = test_json.replace("vegan", "none");
+    assert!(parse_body_json(BufReader::new(test_json.as_bytes())).is_err());
+}
+
+#[test]
+fn test_parse_targets() {
+    let value = serde_json::json!({
+        "たんぱく質": {"kind": "More", "amount": 120},
+        "脂質": {"kind": "range", "amount": [40, 60]},
+        "ヨウ素": null
+    });
+    let targets = parse_targets(&value).unwrap();
+    assert_eq!(targets.len(), 3);
+    let get = |key: &str| targets.iter().find(|(k, _)| k == key).unwrap().1;
+    assert_eq!(get("たんぱく質").unwrap().to_string(), "+ 120");
+    assert_eq!(get("脂質").unwrap().to_string(), "40 ~ 60");
+    assert!(get("ヨウ素").is_none());
+
+    assert!(parse_targets(&serde_json::json!({"たんぱく質": {"kind": "many", "amount": 1}})).is_err());
+    assert!(parse_targets(&serde_json::json!({"脂質": {"kind": "range", "amount": [60, 40]}})).is_err());
+    assert!(parse_targets(&serde_json::json!({"食品名": {"kind": "more", "amount": 1}})).is_err());
+
+    // プロファイルの後に反映する。トップレベルのtargetsも使える
+    let test_json = r#"{
+    "foods": [],
+    "name_list": [],
+    "body": {"age": 20, "weight": 50, "height": 160, "gender": "male", "pal": "low", "days": 2,
+             "profiles": "hypertension", "targets": {"カリウム": {"kind": "less", "amount": 3000}}},
+    "targets": {"ヨウ素": null}
+}"#;
+    let parsed_data = parse_json(BufReader::new(test_json.as_bytes())).unwrap();
+    let kijun = parsed_data.body.get_kijun(2).unwrap();
+    assert_eq!(kijun.get("カリウム").unwrap().to_string(), "- 6000");
+    assert_eq!(kijun.get_override("カリウム").unwrap().0, "targets");
+    assert!(kijun.get("ヨウ素").is_none());
+    assert_eq!(kijun.get("食塩相当量").unwrap().to_string(), "- 12");
+}
+
+#[test]
+fn test_parse_goal_weight_and_weight_plan() {
+    let body = parse_body(&serde_json::json!({
+        "age": 30, "weight": 80, "height": 170, "gender": "male", "pal": "low",
+        "goal_weight": 65, "weight_plan": {"kg_per_week": -0.5}
+    })).unwrap();
+    assert_eq!(body.get_kijun_weight(), 65.0);
+    assert!((body.energy_change - (-0.5 * 7200.0 / 7.0)).abs() < 0.001);
+
+    // 目標体重で摂取基準を求める
+    let kijun = body.get_kijun(1).unwrap();
+    assert_eq!(kijun.weight, 65.0);
+    let goal = Kijun::new(30, 65.0, 170.0, Gender::Male, PAL::Low, 1);
+    assert_eq!(kijun.get("たんぱく質").unwrap().to_string(), goal.get("たんぱく質").unwrap().to_string());
+    assert!(kijun.get("エネルギー").unwrap().get_reference() < goal.get("エネルギー").unwrap().get_reference());
+
+    let table = body.get_table();
+    assert_eq!(table.row_list[1].cell_list[1].text, "27.7");
+    assert_eq!(table.row_list[1].cell_list[1].style, "r");
+    assert_eq!(table.row_list.len(), 8);
+    assert_eq!(table.row_list[6].cell_list[1].text, "国立健康・栄養研究所の式 × 身体活動レベル 1.5");
+
+    let body = parse_body(&serde_json::json!({
+        "age": 30, "weight": 80, "height": 160, "gender": "male", "pal": "low", "goal_weight": "standard"
+    })).unwrap();
+    assert!((body.get_kijun_weight() - 56.32).abs() < 0.001);
+    assert!(parse_weight_plan(&serde_json::json!({})).is_err());
+    assert!(parse_weight_plan(&serde_json::json!({"kcal_per_day": 100, "kg_per_week": 1})).is_err());
+}
+
+#[test]
+fn test_parse_energy_method() {
+    let get_body = |value: serde_json::Value| {
+        let mut obj = serde_json::json!({"age": 30, "weight": 60, "height": 170, "gender": "male", "pal": "low"});
+        obj.as_object_mut().unwrap().extend(value.as_object().unwrap().clone());
+        parse_body(&obj)
+    };
+
+    assert_eq!(get_body(serde_json::json!({})).unwrap().energy_method, EnergyMethod::Ganpule);
+    assert_eq!(get_body(serde_json::json!({"bmr": "harris-benedict"})).unwrap().energy_method, EnergyMethod::HarrisBenedict);
+    assert_eq!(get_body(serde_json::json!({"bmr": 1400})).unwrap().energy_method, EnergyMethod::MeasuredBmr(1400.0));
+    let body = get_body(serde_json::json!({"tee": 2300})).unwrap();
+    assert_eq!(body.get_kijun(1).unwrap().get("エネルギー").unwrap().get_reference(), 2300.0);
+    assert_eq!(body.get_table().row_list[4].cell_list[1].text, "総エネルギー消費量 2300kcal");
+
+    assert!(get_body(serde_json::json!({"bmr": "none"})).is_err());
+    assert!(get_body(serde_json::json!({"bmr": 1400, "tee": 2300})).is_err());
+
+    // 求められない場合は標準の式にせずにエラーにする
+    let body = get_body(serde_json::json!({"age": 15, "bmr": "harris-benedict"})).unwrap();
+    assert!(body.get_kijun(1).is_err());
+}
+
+#[test]
+fn test_parse_pal_and_activities() {
+    let get_body = |value: serde_json::Value| {
+        let mut obj = serde_json::json!({"age": 30, "weight": 60, "height": 170, "gender": "male"});
+        obj.as_object_mut().unwrap().extend(value.as_object().unwrap().clone());
+        parse_body(&obj)
+    };
+
+    let body = get_body(serde_json::json!({"pal": 2.3})).unwrap();
+    assert_eq!(Kijun::get_pal(body.age, body.pal).unwrap(), 2.3);
+
+    let body = get_body(serde_json::json!({"activities": [
+        {"name": "練習", "mets": 8, "minutes": 120},
+        {"name": "睡眠", "mets": 0.9, "minutes": 480}
+    ]})).unwrap();
+    assert_eq!(Kijun::get_pal(body.age, body.pal).unwrap(), 1.71);
+    assert_eq!(body.get_table().row_list[4].cell_list[1].text, "国立健康・栄養研究所の式 × 身体活動レベル 1.71");
+
+    assert!(get_body(serde_json::json!({})).is_err());
+    assert!(get_body(serde_json::json!({"pal": -1})).is_err());
+    assert!(get_body(serde_json::json!({"pal": "low", "activities": []})).is_err());
+    assert!(get_body(serde_json::json!({"activities": [{"mets": 3}]})).is_err());
+}
+
+#[test]
+fn test_parse_name_list() {
+    let value = serde_json::json!(["食品名", "preset:kijun"]);
+    assert_eq!(parse_name_list(&value).unwrap(), vec!["食品名", "preset:kijun"]);
+    let value = serde_json::json!("preset:kijun");
+    assert_eq!(parse_name_list(&value).unwrap(), vec!["preset:kijun"]);
+    assert!(parse_name_list(&serde_json::json!(1)).is_err());
+}
+
+#[test]
+fn test_parse_purchase_units() {
+    let value = serde_json::json!({"04046": {"name": "パック", "weight": 45}, "12004": 50});
+    let purchase_units = parse_purchase_units(&value).unwrap();
+    assert_eq!(purchase_units["04046"], PurchaseUnit { name: "パック".to_string(), weight: 45.0 });
+    assert_eq!(purchase_units["12004"].name, "個");
+    assert!(parse_purchase_units(&serde_json::json!({"04046": 0})).is_err());
+    assert!(parse_purchase_units(&serde_json::json!([])).is_err());
+}
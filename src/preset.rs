@@ -0,0 +1,267 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::output::{wrap, OutputCell, OutputTable, RowKind};
+
+// 一覧の表で列を折り返す幅
+const COLUMN_WRAP_WIDTH: usize = 60;
+// name_listでプリセットを指定するときの接頭辞
+const PRESET_PREFIX: &str = "preset:";
+
+// 組み込みのプリセット。(名前, 番号, 説明, 列)
+const BUILTIN_PRESET_LIST: [(&str, &str, &str, &[&str]); 4] = [
+    ("kiso", "0", "「調理・献立作成の基礎」と同じ列", &[
+        "廃棄率", "エネルギー", "エネルギー（kJ)", "水分", "たんぱく質",
+        "アミノ酸組成によるたんぱく質", "脂質",
+        "トリアシルグリセロール当量", "飽和脂肪酸", "一価不飽和脂肪酸",
+        "多価不飽和脂肪酸", "コレステロール", "炭水化物",
+        "利用可能炭水化物（単糖当量）", "水溶性食物繊維", "不溶性食物繊維",
+        "食物繊維総量", "灰   分", "ナトリウム", "カリウム", "カルシウム",
+        "マグネシウム", "リン", "鉄", "亜鉛", "銅", "マンガン", "ヨウ素",
+        "セレン", "クロム", "モリブデン", "レチノール", "α-カロテン",
+        "β-カロテン", "β-クリプトキサンチン", "β-カロテン当量",
+        "レチノール活性当量", "ビタミンD", "α-トコフェロール",
+        "β-トコフェロール", "γ-トコフェロール", "δ-トコフェロール",
+        "ビタミンK", "ビタミンB1", "ビタミンB2", "ナイアシン",
+        "ビタミンB6", "ビタミンB12", "葉酸", "パントテン酸", "ビオチン",
+        "ビタミンC", "食塩相当量", "アルコール", "硝酸イオン",
+        "テオブロミン", "カフェイン", "タンニン", "ポリフェノール", "酢酸",
+        "調理油", "有機酸", "重量変化率"
+    ]),
+    ("main", "1", "主な栄養素", &[
+        "重量", "エネルギー", "たんぱく質", "脂質", "炭水化物",
+        "食物繊維総量", "ナトリウム", "カルシウム", "鉄",
+        "レチノール活性当量", "ビタミンB1", "ビタミンB2", "ビタミンC"
+    ]),
+    ("nutrients", "2", "摂取基準がある栄養素", &[
+        "重量", "エネルギー", "たんぱく質", "脂質", "多価不飽和脂肪酸",
+        "炭水化物", "食物繊維総量", "レチノール活性当量", "ビタミンD",
+        "α-トコフェロール", "ビタミンK", "ビタミンB1", "ビタミンB2",
+        "ナイアシン", "ビタミンB6", "ビタミンB12", "葉酸", "パントテン酸",
+        "ビオチン", "ビタミンC", "ナトリウム", "カリウム", "カルシウム",
+        "マグネシウム", "リン", "鉄", "亜鉛", "銅", "マンガン", "ヨウ素",
+        "セレン", "クロム", "モリブデン"
+    ]),
+    ("kijun", "", "calcで摂取基準と比べるための列", &[
+        "食品番号", "クラス", "食品名", "価格", "重量", "可食量", "廃棄率", "エネルギー", "たんぱく質",
+        "脂質", "飽和脂肪酸", "多価不飽和脂肪酸", "炭水化物", "食物繊維総量",
+        "レチノール活性当量", "ビタミンD", "α-トコフェロール",
+        "ビタミンK", "ビタミンB1", "ビタミンB2", "ナイアシン",
+        "ビタミンB6", "ビタミンB12", "葉酸", "パントテン酸",
+        "ビオチン", "ビタミンC", "ナトリウム", "カリウム",
+        "カルシウム", "マグネシウム", "リン", "鉄", "亜鉛", "銅",
+        "マンガン", "ヨウ素", "セレン", "クロム", "モリブデン"
+    ]),
+];
+
+pub struct Preset {
+    pub name: String,
+    // --typeで指定するときの番号。ない場合は空の文字列
+    pub number: String,
+    pub description: String,
+    pub column_list: Vec<String>,
+    // 設定ファイルで定義されたか
+    pub is_user_defined: bool,
+}
+
+pub struct PresetList {
+    preset_list: Vec<Preset>,
+}
+
+// 設定ファイルの場所。MEAL_CONFIGがあればそれを使い、なければ~/.config/meal/config.json
+pub fn get_config_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("MEAL_CONFIG") {
+        return Some(PathBuf::from(path))
+    }
+
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".config").join("meal").join("config.json"))
+}
+
+// 設定ファイルを読む。ファイルがなければNull
+pub fn read_config() -> Result<Value, String> {
+    let path = match get_config_path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(Value::Null)
+    };
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) => return Err(format!("{}: {}", path.display(), e))
+    };
+    match serde_json::from_reader(BufReader::new(file)) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(format!("{}: {}", path.display(), e))
+    }
+}
+
+// 設定ファイルのpresetsを読む
+// {"presets": {"名前": ["列", ...]}} か {"presets": {"名前": {"description": "説明", "columns": ["列", ...]}}}
+fn parse_preset(name: &str, value: &Value) -> Result<Preset, String> {
+    let error = || format!("プリセット{}の値は文字列の配列か、columnsを持つオブジェクトにしてください", name);
+
+    let (description, columns) = match value {
+        Value::Array(_) => ("", value),
+        Value::Object(obj) => {
+            let description = match obj.get("description") {
+                Some(Value::String(description)) => description.as_str(),
+                Some(_) => return Err(error()),
+                None => ""
+            };
+            (description, obj.get("columns").ok_or_else(error)?)
+        },
+        _ => return Err(error())
+    };
+
+    let column_list = match columns {
+        Value::Array(list) => list.iter()
+            .map(|column| column.as_str().map(|c| c.to_string()).ok_or_else(error))
+            .collect::<Result<Vec<_>, String>>()?,
+        _ => return Err(error())
+    };
+
+    Ok(Preset {
+        name: name.to_string(),
+        number: String::new(),
+        description: description.to_string(),
+        column_list,
+        is_user_defined: true,
+    })
+}
+
+impl PresetList {
+    pub fn new() -> PresetList {
+        let preset_list = BUILTIN_PRESET_LIST.iter().map(|(name, number, description, column_list)| {
+            Preset {
+                name: name.to_string(),
+                number: number.to_string(),
+                description: description.to_string(),
+                column_list: column_list.iter().map(|c| c.to_string()).collect(),
+                is_user_defined: false,
+            }
+        }).collect();
+
+        PresetList { preset_list }
+    }
+
+    // 組み込みのプリセットに設定ファイルのプリセットを加える。同じ名前の場合は設定ファイルを優先する
+    pub fn load() -> Result<PresetList, String> {
+        let mut preset_list = PresetList::new();
+
+        let value = read_config()?;
+        preset_list.add_from_json(&value)?;
+        Ok(preset_list)
+    }
+
+    pub fn add_from_json(&mut self, value: &Value) -> Result<(), String> {
+        let presets = match value.get("presets") {
+            Some(Value::Object(presets)) => presets,
+            Some(_) => return Err("presetsの値はオブジェクトにしてください".to_string()),
+            None => return Ok(())
+        };
+
+        for (name, value) in presets {
+            let preset = parse_preset(name, value)?;
+            self.preset_list.retain(|p| p.name != preset.name);
+            self.preset_list.push(preset);
+        }
+
+        Ok(())
+    }
+
+    // 名前か番号でプリセットを探す
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.preset_list.iter()
+            .find(|preset| preset.name == name || (!preset.number.is_empty() && preset.number == name))
+    }
+
+    // name_listの"preset:名前"をプリセットの列に置き換える
+    pub fn expand(&self, name_list: &[String]) -> Result<Vec<String>, String> {
+        let mut expanded = Vec::new();
+
+        for name in name_list {
+            match name.strip_prefix(PRESET_PREFIX) {
+                Some(preset_name) => match self.get(preset_name.trim()) {
+                    Some(preset) => expanded.extend(preset.column_list.iter().cloned()),
+                    None => return Err(format!("{} というプリセットはありません", preset_name))
+                },
+                None => expanded.push(name.to_string())
+            }
+        }
+
+        Ok(expanded)
+    }
+
+    // プリセットの一覧の表。JSONでは列を文字列の配列にする
+    pub fn get_table(&self) -> OutputTable {
+        let mut table = OutputTable::new(
+            ["名前", "番号", "説明", "設定ファイル", "列"].iter().map(|c| c.to_string()).collect(),
+            vec![String::new(); 5],
+            false);
+        for preset in &self.preset_list {
+            let is_user_defined = if preset.is_user_defined { "○" } else { "" };
+            let column_list = preset.column_list.iter().map(|c| Value::String(c.to_string())).collect();
+            table.add_row(RowKind::Food, vec![
+                OutputCell::new(&preset.name, ""),
+                OutputCell::new(&preset.number, ""),
+                OutputCell::new(&preset.description, ""),
+                OutputCell::with_value(is_user_defined, "", Value::Bool(preset.is_user_defined)),
+                OutputCell::with_value(&wrap(&preset.column_list.join(", "), COLUMN_WRAP_WIDTH), "",
+                                       Value::Array(column_list)),
+            ]);
+        }
+
+        table
+    }
+}
+
+
+#[test]
+fn test_preset_list_get() {
+    let preset_list = PresetList::new();
+    assert_eq!(preset_list.get("0").unwrap().name, "kiso");
+    assert_eq!(preset_list.get("main").unwrap().column_list[0], "重量");
+    assert_eq!(preset_list.get("kijun").unwrap().column_list[2], "食品名");
+    assert!(preset_list.get("").is_none());
+    assert!(preset_list.get("none").is_none());
+}
+
+#[test]
+fn test_preset_list_add_from_json() {
+    let mut preset_list = PresetList::new();
+    let value = serde_json::from_str(r#"{"presets": {
+        "protein": ["食品名", "たんぱく質"],
+        "main": {"description": "上書き", "columns": ["食品名"]}
+    }}"#).unwrap();
+    preset_list.add_from_json(&value).unwrap();
+    assert_eq!(preset_list.get("protein").unwrap().column_list, vec!["食品名", "たんぱく質"]);
+    assert!(preset_list.get("protein").unwrap().is_user_defined);
+    // 同じ名前の場合は設定ファイルを優先する
+    assert_eq!(preset_list.get("main").unwrap().description, "上書き");
+    assert!(preset_list.get("1").is_none());
+
+    let value = serde_json::from_str(r#"{"presets": {"bad": [1, 2]}}"#).unwrap();
+    assert!(preset_list.add_from_json(&value).is_err());
+}
+
+#[test]
+fn test_preset_list_expand() {
+    let preset_list = PresetList::new();
+    let name_list = vec!["食品名".to_string(), "preset:main".to_string()];
+    let expanded = preset_list.expand(&name_list).unwrap();
+    assert_eq!(expanded.len(), 14);
+    assert_eq!(&expanded[..2], &["食品名", "重量"]);
+    assert!(preset_list.expand(&["preset:none".to_string()]).is_err());
+}
+
+#[test]
+fn test_preset_list_get_table() {
+    let table = PresetList::new().get_table();
+    let json = table.to_json();
+    assert_eq!(json["rows"][0]["values"]["名前"], "kiso");
+    assert_eq!(json["rows"][1]["values"]["番号"], "1");
+    assert_eq!(json["rows"][1]["values"]["設定ファイル"], false);
+    assert_eq!(json["rows"][1]["values"]["列"][0], "重量");
+}
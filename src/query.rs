@@ -0,0 +1,402 @@
+use crate::food::Food;
+use crate::food::food_data::{FoodData, TraceMode};
+use crate::food::nutrient::Nutrient;
+
+// 検索の条件式
+// 例: "食品群=12 AND たんぱく質>20 AND 脂質<5"
+//     "ビタミンC per 100kcal > 50"
+//     "(脂質<5 OR 炭水化物<10) AND NOT 食品群=17"
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Debug, PartialEq)]
+enum Literal {
+    Number(f32),
+    Text(String),
+}
+
+// 比較する値。perがある場合は、その項目の量あたりの値にする
+#[derive(Debug, PartialEq)]
+struct Operand {
+    nutrient: Nutrient,
+    per: Option<(f32, Nutrient)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Operator, Literal),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    expr: Expr,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut token_list = Vec::new();
+    let mut word = String::new();
+    let mut chars = text.chars().peekable();
+
+    macro_rules! push_word {
+        () => {
+            if !word.is_empty() {
+                token_list.push(Token::Word(word.clone()));
+                word.clear();
+            }
+        };
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            '<' | '>' | '=' | '!' | '＜' | '＞' | '＝' => {
+                push_word!();
+                let has_equal = matches!(chars.peek(), Some('=') | Some('＝'));
+                let operator = match (c, has_equal) {
+                    ('<', true) | ('＜', true) => Operator::LessEqual,
+                    ('<', false) | ('＜', false) => Operator::Less,
+                    ('>', true) | ('＞', true) => Operator::GreaterEqual,
+                    ('>', false) | ('＞', false) => Operator::Greater,
+                    ('!', true) => Operator::NotEqual,
+                    ('=', _) | ('＝', _) => Operator::Equal,
+                    _ => return Err(format!("{}の後には=が必要です", c))
+                };
+                // "=="も"="として扱う
+                if has_equal || (operator == Operator::Equal && chars.peek() == Some(&'=')) {
+                    chars.next();
+                }
+                token_list.push(Token::Operator(operator));
+            },
+            '(' => {
+                push_word!();
+                token_list.push(Token::Open);
+            },
+            ')' => {
+                push_word!();
+                token_list.push(Token::Close);
+            },
+            // 空白やカッコを含む項目名は"..."か「...」で囲む
+            '"' | '「' => {
+                push_word!();
+                let end = if c == '"' { '"' } else { '」' };
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some(c) if c == end => break,
+                        Some(c) => quoted.push(c),
+                        None => return Err(format!("{}が閉じられていません", c))
+                    }
+                }
+                token_list.push(Token::Quoted(quoted));
+            },
+            _ if c.is_whitespace() => push_word!(),
+            _ => word.push(c)
+        }
+    }
+    push_word!();
+
+    Ok(token_list)
+}
+
+fn is_keyword(token: Option<&Token>, keyword: &str) -> bool {
+    match token {
+        Some(Token::Word(word)) => word.eq_ignore_ascii_case(keyword),
+        _ => false
+    }
+}
+
+fn get_nutrient(name: &str) -> Result<Nutrient, String> {
+    match Nutrient::from_name(name) {
+        Some(nutrient) => Ok(nutrient),
+        None => Err(format!("{}という項目はありません", name))
+    }
+}
+
+// "100kcal"を"100"と"kcal"に分ける
+pub fn split_amount(text: &str) -> (&str, &str) {
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    text.split_at(split)
+}
+
+// "100kcal"のような量と単位から、何の項目あたりにするかを決める
+pub fn parse_per(amount: &str, unit: &str) -> Result<(f32, Nutrient), String> {
+    let amount = match amount.parse::<f32>() {
+        Ok(amount) if amount > 0.0 => amount,
+        _ => return Err(format!("{}は量として使えません", amount))
+    };
+    let nutrient = match unit {
+        "g" => Nutrient::Weight,
+        "kcal" => Nutrient::EnercKcal,
+        "kJ" | "kj" => Nutrient::Enerc,
+        "円" => Nutrient::Price,
+        _ => return Err(format!("{}あたりの値は計算できません", unit))
+    };
+
+    Ok((amount, nutrient))
+}
+
+struct Parser {
+    token_list: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.token_list.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.token_list.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while is_keyword(self.peek(), "OR") {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_not()?;
+        while is_keyword(self.peek(), "AND") {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if is_keyword(self.peek(), "NOT") {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.parse_not()?)))
+        }
+
+        if self.peek() == Some(&Token::Open) {
+            self.next();
+            let expr = self.parse_or()?;
+            if self.next() != Some(Token::Close) {
+                return Err(")が足りません".to_string())
+            }
+            return Ok(expr)
+        }
+
+        self.parse_compare()
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr, String> {
+        let name = match self.next() {
+            Some(Token::Word(name)) | Some(Token::Quoted(name)) => name,
+            Some(token) => return Err(format!("{:?}の位置には項目名が必要です", token)),
+            None => return Err("条件式が途中で終わっています".to_string())
+        };
+        let nutrient = get_nutrient(&name)?;
+
+        let per = if is_keyword(self.peek(), "per") {
+            self.next();
+            let amount = match self.next() {
+                Some(Token::Word(amount)) => amount,
+                _ => return Err("perの後には量が必要です".to_string())
+            };
+            // "100kcal"と"100 kcal"のどちらでもよい
+            let (amount, unit) = split_amount(&amount);
+            if unit.is_empty() {
+                match self.next() {
+                    Some(Token::Word(unit)) => Some(parse_per(amount, &unit)?),
+                    _ => return Err("perの後には単位が必要です".to_string())
+                }
+            } else {
+                Some(parse_per(amount, unit)?)
+            }
+        } else {
+            None
+        };
+
+        let operator = match self.next() {
+            Some(Token::Operator(operator)) => operator,
+            _ => return Err(format!("{}の後には比較演算子が必要です", name))
+        };
+
+        let literal = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => {
+                match value.parse::<f32>() {
+                    Ok(num) => Literal::Number(num),
+                    Err(_) => Literal::Text(value)
+                }
+            },
+            _ => return Err(format!("{}と比較する値が必要です", name))
+        };
+
+        Ok(Expr::Compare(Operand { nutrient, per }, operator, literal))
+    }
+}
+
+fn get_value(food: &Food, nutrient: Nutrient, mode: TraceMode) -> Option<Literal> {
+    let food_data = food.get_nutrient(nutrient);
+    if let Some(num) = food_data.get_number_with(mode) {
+        return Some(Literal::Number(num))
+    }
+
+    // 食品群のように文字列で保存されている番号は数値として比較する
+    match food_data {
+        FoodData::String(s) => match s.parse::<f32>() {
+            Ok(num) => Some(Literal::Number(num)),
+            Err(_) => Some(Literal::Text(s.to_string()))
+        },
+        _ => None
+    }
+}
+
+fn compare<T: PartialOrd>(left: T, operator: Operator, right: T) -> bool {
+    match operator {
+        Operator::Equal => left == right,
+        Operator::NotEqual => left != right,
+        Operator::Less => left < right,
+        Operator::LessEqual => left <= right,
+        Operator::Greater => left > right,
+        Operator::GreaterEqual => left >= right,
+    }
+}
+
+impl Expr {
+    fn evaluate(&self, food: &Food, mode: TraceMode) -> bool {
+        match self {
+            Expr::And(left, right) => left.evaluate(food, mode) && right.evaluate(food, mode),
+            Expr::Or(left, right) => left.evaluate(food, mode) || right.evaluate(food, mode),
+            Expr::Not(expr) => !expr.evaluate(food, mode),
+            Expr::Compare(operand, operator, literal) => {
+                let value = match get_value(food, operand.nutrient, mode) {
+                    Some(value) => value,
+                    None => return false
+                };
+
+                let value = match (value, operand.per) {
+                    (Literal::Number(num), Some((amount, per_nutrient))) => {
+                        match food.get_nutrient(per_nutrient).get_number_with(mode) {
+                            Some(base) if base > 0.0 => Literal::Number(num / base * amount),
+                            _ => return false
+                        }
+                    },
+                    (Literal::Text(_), Some(_)) => return false,
+                    (value, None) => value
+                };
+
+                match (value, literal) {
+                    (Literal::Number(left), Literal::Number(right)) => compare(left, *operator, *right),
+                    (Literal::Text(left), Literal::Text(right)) => {
+                        compare(left.as_str(), *operator, right.as_str())
+                    },
+                    _ => false
+                }
+            }
+        }
+    }
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, String> {
+        let token_list = tokenize(text)?;
+        if token_list.is_empty() {
+            return Err("条件式が空です".to_string())
+        }
+
+        let mut parser = Parser { token_list, position: 0 };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("{:?}以降の条件式が読み込めません", token))
+        }
+
+        Ok(Query { expr })
+    }
+
+    pub fn matches(&self, food: &Food, mode: TraceMode) -> bool {
+        self.expr.evaluate(food, mode)
+    }
+}
+
+
+#[test]
+fn test_query_tokenize() {
+    assert_eq!(tokenize("たんぱく質>=20").unwrap(), vec![
+        Token::Word("たんぱく質".to_string()),
+        Token::Operator(Operator::GreaterEqual),
+        Token::Word("20".to_string()),
+    ]);
+    assert_eq!(tokenize("(「灰   分」 != 1)").unwrap(), vec![
+        Token::Open,
+        Token::Quoted("灰   分".to_string()),
+        Token::Operator(Operator::NotEqual),
+        Token::Word("1".to_string()),
+        Token::Close,
+    ]);
+    assert!(tokenize("\"脂質 < 5").is_err());
+}
+
+#[test]
+fn test_query_parse() {
+    let query = Query::parse("ビタミンC per 100kcal > 50").unwrap();
+    assert_eq!(query.expr, Expr::Compare(
+        Operand { nutrient: Nutrient::Vitc, per: Some((100.0, Nutrient::EnercKcal)) },
+        Operator::Greater,
+        Literal::Number(50.0)));
+    assert_eq!(Query::parse("ビタミンC per 100 kcal > 50"), Ok(query));
+
+    assert!(Query::parse("食品群=12 AND たんぱく質>20 AND 脂質<5").is_ok());
+    assert!(Query::parse("(脂質<5 OR 炭水化物<10) and not 食品群=17").is_ok());
+    assert!(Query::parse("").is_err());
+    assert!(Query::parse("ないもの>1").is_err());
+    assert!(Query::parse("脂質<").is_err());
+    assert!(Query::parse("(脂質<5").is_err());
+    assert!(Query::parse("脂質 per 100個 > 1").is_err());
+    assert!(Query::parse("脂質<5 脂質>1").is_err());
+}
+
+#[test]
+fn test_query_matches() {
+    let mut food = Food::new();
+    food.set("食品群", FoodData::String("12".to_string()));
+    food.set("食品名", FoodData::String("鶏卵　全卵　生".to_string()));
+    food.set("エネルギー", FoodData::Number(200.0));
+    food.set("重量", FoodData::Number(100.0));
+    food.set("たんぱく質", FoodData::Number(25.0));
+    food.set("脂質", FoodData::Number(3.0));
+    food.set("ビタミンC", FoodData::Trace(0.5));
+
+    let matches = |text: &str| Query::parse(text).unwrap().matches(&food, TraceMode::Zero);
+    assert!(matches("食品群=12 AND たんぱく質>20 AND 脂質<5"));
+    assert!(!matches("食品群=12 AND たんぱく質>30"));
+    assert!(matches("たんぱく質>30 OR 脂質<=3"));
+    assert!(matches("NOT 食品群=1"));
+    assert!(matches("たんぱく質 per 100kcal = 12.5"));
+    assert!(matches("脂質 per 50g < 2"));
+    assert!(matches("食品名=\"鶏卵　全卵　生\""));
+    // 値がない項目との比較はすべて偽
+    assert!(!matches("食物繊維総量<1"));
+    assert!(!matches("たんぱく質 per 100円 > 1"));
+    // Trの扱いはTraceModeにしたがう
+    assert!(matches("ビタミンC=0"));
+    assert!(Query::parse("ビタミンC=0.5").unwrap().matches(&food, TraceMode::Threshold));
+}
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::food::Food;
+use crate::food::food_data::FoodData;
+use crate::food::nutrient::Nutrient;
+use crate::food_table::FoodTable;
+use crate::output::{OutputCell, OutputTable, RowKind};
+
+// 食品に含まれるアレルゲンや、食事の制限に関わる原材料
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tag {
+    // 特定原材料の7品目
+    Shrimp,
+    Crab,
+    Wheat,
+    Buckwheat,
+    Egg,
+    Milk,
+    Peanut,
+    Meat,
+    Seafood,
+    Pork,
+    Alcohol,
+    Honey,
+    HighFodmap,
+}
+
+const TAG_LIST: [Tag; 13] = [
+    Tag::Shrimp, Tag::Crab, Tag::Wheat, Tag::Buckwheat, Tag::Egg, Tag::Milk, Tag::Peanut,
+    Tag::Meat, Tag::Seafood, Tag::Pork, Tag::Alcohol, Tag::Honey, Tag::HighFodmap,
+];
+
+impl Tag {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Tag::Shrimp => "えび",
+            Tag::Crab => "かに",
+            Tag::Wheat => "小麦",
+            Tag::Buckwheat => "そば",
+            Tag::Egg => "卵",
+            Tag::Milk => "乳",
+            Tag::Peanut => "落花生",
+            Tag::Meat => "肉",
+            Tag::Seafood => "魚介",
+            Tag::Pork => "豚",
+            Tag::Alcohol => "アルコール",
+            Tag::Honey => "はちみつ",
+            Tag::HighFodmap => "高FODMAP",
+        }
+    }
+
+    pub fn from_str(text: &str) -> Option<Tag> {
+        TAG_LIST.iter().find(|tag| tag.to_str() == text).copied()
+    }
+}
+
+// 食品群と食品名からタグを付ける規則。patternのどれかを含み、exclusionをどれも含まない食品に付ける
+// patternが空の文字列なら食品群のすべての食品に付ける
+struct TagRule {
+    tag: Tag,
+    group: Option<&'static str>,
+    pattern_list: &'static [&'static str],
+    exclusion_list: &'static [&'static str],
+}
+
+const TAG_RULE_LIST: [TagRule; 25] = [
+    TagRule { tag: Tag::Shrimp, group: None, pattern_list: &["えび", "エビ"], exclusion_list: &["（かに類）"] },
+    TagRule { tag: Tag::Crab, group: None, pattern_list: &["（かに類）", "かに風味"], exclusion_list: &[] },
+    TagRule { tag: Tag::Wheat, group: None, pattern_list: &[
+        "こむぎ", "小麦", "パン", "（ルウ類）", "しょうゆ", "めんつゆ", "天ぷら用バッター",
+        "ぎょうざ", "しゅうまい", "フライ", "コロッケ", "メンチカツ", "グラタン",
+    ], exclusion_list: &["パン酵母"] },
+    TagRule { tag: Tag::Wheat, group: Some("15"), pattern_list: &[
+        "＜ケーキ・ペストリー類＞", "＜ビスケット類＞", "かりんとう", "カステラ", "どら焼",
+        "今川焼", "まんじゅう",
+    ], exclusion_list: &["芋かりんとう"] },
+    TagRule { tag: Tag::Wheat, group: Some("18"), pattern_list: &["カレー", "シチュー"], exclusion_list: &[] },
+    // 沖縄そばや焼きそばは小麦のめん
+    TagRule { tag: Tag::Buckwheat, group: None, pattern_list: &["そば"], exclusion_list: &["こむぎ"] },
+    TagRule { tag: Tag::Egg, group: Some("12"), pattern_list: &[""], exclusion_list: &[] },
+    TagRule { tag: Tag::Egg, group: None, pattern_list: &[
+        "マヨネーズ", "カステラ", "カスタード", "プリン", "ボーロ", "シュークリーム",
+        "スポンジケーキ", "ホットケーキ", "ワッフル", "ババロア", "ハンバーグ", "メンチカツ",
+    ], exclusion_list: &[] },
+    TagRule { tag: Tag::Egg, group: Some("15"), pattern_list: &["＜ケーキ・ペストリー類＞"], exclusion_list: &["パイ"] },
+    TagRule { tag: Tag::Milk, group: Some("13"), pattern_list: &[""], exclusion_list: &["人乳"] },
+    TagRule { tag: Tag::Milk, group: None, pattern_list: &[
+        "乳", "バター", "チーズ", "クリーム", "ミルク", "ヨーグルト", "グラタン",
+    ], exclusion_list: &["豆乳", "乳用", "ココナッツ", "人乳"] },
+    TagRule { tag: Tag::Milk, group: Some("18"), pattern_list: &["シチュー"], exclusion_list: &[] },
+    TagRule { tag: Tag::Milk, group: Some("15"), pattern_list: &["ホワイトチョコ"], exclusion_list: &[] },
+    TagRule { tag: Tag::Peanut, group: None, pattern_list: &["らっかせい", "落花生", "ピーナッツ", "おのろけ豆"], exclusion_list: &[] },
+    TagRule { tag: Tag::Meat, group: Some("11"), pattern_list: &[""], exclusion_list: &[] },
+    TagRule { tag: Tag::Meat, group: None, pattern_list: &[
+        "（動物脂類）", "ビーフ", "チキン", "ポーク", "ハンバーグ", "ミートボール", "メンチカツ",
+        "ぎょうざ", "しゅうまい", "鳥がら", "ブイヨン", "ゼラチン", "マシュマロ", "ババロア",
+        "＜デザート菓子類＞ゼリー", "ハム", "ベーコン", "ソーセージ",
+    ], exclusion_list: &["魚肉"] },
+    TagRule { tag: Tag::Seafood, group: Some("10"), pattern_list: &[""], exclusion_list: &[] },
+    // えびやかにの料理も魚介にする
+    TagRule { tag: Tag::Seafood, group: None, pattern_list: &[
+        "えび", "（かに類）", "かに風味", "かつお", "煮干し", "オイスターソース", "ナンプラー",
+        "（魚フライ類）", "和風だし", "だし入り",
+    ], exclusion_list: &[] },
+    TagRule { tag: Tag::Pork, group: None, pattern_list: &[
+        "ぶた", "豚", "ポーク", "いのしし", "ラード", "ベーコン", "ハム", "ソーセージ", "ゼラチン",
+        "ぎょうざ", "しゅうまい",
+    ], exclusion_list: &["魚肉"] },
+    TagRule { tag: Tag::Alcohol, group: Some("16"), pattern_list: &["＜アルコール飲料類＞"], exclusion_list: &[] },
+    TagRule { tag: Tag::Honey, group: None, pattern_list: &["はちみつ"], exclusion_list: &[] },
+    TagRule { tag: Tag::HighFodmap, group: None, pattern_list: &[
+        "こむぎ", "小麦", "ライむぎ", "（たまねぎ類）", "（にんにく類）", "＜香辛料類＞にんにく",
+        "オニオンパウダー", "（ねぎ類）", "（らっきょう類）", "わけぎ", "リーキ", "アスパラガス",
+        "カリフラワー", "マッシュルーム", "ごぼう", "はちみつ",
+    ], exclusion_list: &[] },
+    TagRule { tag: Tag::HighFodmap, group: Some("07"), pattern_list: &[
+        "りんご", "（なし類）", "（もも類）", "すいか", "マンゴー", "さくらんぼ", "いちじく",
+        "（すもも類）", "あんず",
+    ], exclusion_list: &[] },
+    // 木綿豆腐などは低FODMAP
+    TagRule { tag: Tag::HighFodmap, group: Some("04"), pattern_list: &[
+        "あずき", "いんげんまめ", "ひよこまめ", "レンズまめ", "だいず",
+    ], exclusion_list: &["木綿豆腐", "テンペ"] },
+    TagRule { tag: Tag::HighFodmap, group: Some("13"), pattern_list: &[
+        "（液状乳類）", "（練乳類）", "（発酵乳", "（アイスクリーム類）", "脱脂粉乳",
+    ], exclusion_list: &[] },
+];
+
+// 規則では判定できない食品。(食品番号, 付けるタグ, 外すタグ)
+const TAG_OVERRIDE_LIST: [(&str, &[Tag], &[Tag]); 5] = [
+    // バターを使っていない
+    ("05036", &[], &[Tag::Milk]),
+    ("05037", &[], &[Tag::Milk]),
+    // たまりしょうゆは主に大豆で作る
+    ("17009", &[], &[Tag::Wheat]),
+    // みりん風調味料はアルコールをほとんど含まない
+    ("17054", &[], &[Tag::Alcohol]),
+    ("15090", &[Tag::Alcohol], &[]),
+];
+
+// 食事の制限。アレルゲンはそのタグがある食品を、食事のタイプは合わない食品を除く
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Restriction {
+    Allergen(Tag),
+    Vegetarian,
+    Vegan,
+    // 豚肉とアルコールだけを判定する。と畜の方法はわからない
+    Halal,
+    LowFodmap,
+}
+
+impl Restriction {
+    pub fn from_str(text: &str) -> Result<Restriction, String> {
+        let restriction = match text {
+            "vegetarian" => Restriction::Vegetarian,
+            "vegan" => Restriction::Vegan,
+            "halal" => Restriction::Halal,
+            "low-fodmap" => Restriction::LowFodmap,
+            _ => match Tag::from_str(text) {
+                Some(tag) => Restriction::Allergen(tag),
+                None => return Err(format!("{} という制限はありません。\
+                                            えび、かに、小麦、そば、卵、乳、落花生などのタグか、\
+                                            vegetarian, vegan, halal, low-fodmap を指定してください", text))
+            }
+        };
+
+        Ok(restriction)
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Restriction::Allergen(tag) => tag.to_str(),
+            Restriction::Vegetarian => "vegetarian",
+            Restriction::Vegan => "vegan",
+            Restriction::Halal => "halal",
+            Restriction::LowFodmap => "low-fodmap",
+        }
+    }
+
+    // この制限で避けるタグ
+    fn get_excluded_tag_list(&self) -> &[Tag] {
+        match self {
+            Restriction::Allergen(tag) => std::slice::from_ref(tag),
+            Restriction::Vegetarian => &[Tag::Meat, Tag::Seafood],
+            Restriction::Vegan => &[Tag::Meat, Tag::Seafood, Tag::Egg, Tag::Milk, Tag::Honey],
+            Restriction::Halal => &[Tag::Pork, Tag::Alcohol],
+            Restriction::LowFodmap => &[Tag::HighFodmap],
+        }
+    }
+}
+
+pub fn parse_restriction_list(text_list: &[&str]) -> Result<Vec<Restriction>, String> {
+    text_list.iter().map(|text| Restriction::from_str(text)).collect()
+}
+
+fn get_string(food: &Food, nutrient: Nutrient) -> &str {
+    match food.get_nutrient(nutrient) {
+        FoodData::String(text) => text,
+        _ => ""
+    }
+}
+
+pub struct TagTable {
+    // 食品番号ごとの(付けるタグ, 外すタグ)
+    override_map: HashMap<String, (Vec<Tag>, Vec<Tag>)>,
+}
+
+impl TagTable {
+    pub fn new() -> TagTable {
+        let override_map = TAG_OVERRIDE_LIST.iter()
+            .map(|(number, added, removed)| (number.to_string(), (added.to_vec(), removed.to_vec())))
+            .collect();
+
+        TagTable { override_map }
+    }
+
+    // 組み込みの規則に、設定ファイルのtagsを加える
+    pub fn load() -> Result<TagTable, String> {
+        let mut tag_table = TagTable::new();
+        tag_table.add_from_json(&crate::preset::read_config()?)?;
+        Ok(tag_table)
+    }
+
+    // {"tags": {"食品番号": ["卵", "-小麦"]}}。-を付けたタグは外す
+    pub fn add_from_json(&mut self, value: &Value) -> Result<(), String> {
+        let tags = match value.get("tags") {
+            Some(Value::Object(tags)) => tags,
+            Some(_) => return Err("tagsの値はオブジェクトにしてください".to_string()),
+            None => return Ok(())
+        };
+
+        for (number, tag_list) in tags {
+            let error = || format!("tagsの{}の値はタグの文字列の配列にしてください", number);
+            let tag_list = tag_list.as_array().ok_or_else(error)?;
+            let mut added = Vec::new();
+            let mut removed = Vec::new();
+            for text in tag_list {
+                let text = text.as_str().ok_or_else(error)?;
+                let (list, name) = match text.strip_prefix('-') {
+                    Some(name) => (&mut removed, name),
+                    None => (&mut added, text)
+                };
+                match Tag::from_str(name) {
+                    Some(tag) => list.push(tag),
+                    None => return Err(format!("tagsの{}の{} というタグはありません", number, name))
+                }
+            }
+            self.override_map.insert(number.to_string(), (added, removed));
+        }
+
+        Ok(())
+    }
+
+    pub fn get_tag_list(&self, food: &Food) -> Vec<Tag> {
+        let number = get_string(food, Nutrient::Number);
+        let group = get_string(food, Nutrient::Group);
+        let name = get_string(food, Nutrient::Name);
+
+        let mut tag_list: Vec<Tag> = Vec::new();
+        let mut add = |tag: Tag| if !tag_list.contains(&tag) { tag_list.push(tag) };
+
+        for rule in TAG_RULE_LIST.iter() {
+            if rule.group.is_some_and(|g| g != group) {
+                continue
+            }
+            if rule.pattern_list.iter().any(|p| name.contains(p))
+                && !rule.exclusion_list.iter().any(|e| name.contains(e)) {
+                add(rule.tag);
+            }
+        }
+        if food.get_nutrient(Nutrient::Alc).get_number().is_some_and(|alc| *alc > 0.0) {
+            add(Tag::Alcohol);
+        }
+
+        if let Some((added, removed)) = self.override_map.get(number) {
+            added.iter().for_each(|tag| add(*tag));
+            tag_list.retain(|tag| !removed.contains(tag));
+        }
+
+        tag_list
+    }
+
+    // 食品が当てはまる制限と、その理由になったタグ
+    pub fn get_violation_list(&self, food: &Food, restriction_list: &[Restriction]) -> Vec<(Restriction, Vec<Tag>)> {
+        let tag_list = self.get_tag_list(food);
+        restriction_list.iter().filter_map(|restriction| {
+            let matched: Vec<Tag> = restriction.get_excluded_tag_list().iter()
+                .filter(|tag| tag_list.contains(tag))
+                .copied()
+                .collect();
+            if matched.is_empty() { None } else { Some((*restriction, matched)) }
+        }).collect()
+    }
+
+    pub fn is_allowed(&self, food: &Food, restriction_list: &[Restriction]) -> bool {
+        self.get_violation_list(food, restriction_list).is_empty()
+    }
+
+    // 制限に当てはまらない食品だけにする
+    pub fn filter(&self, food_table: &FoodTable, restriction_list: &[Restriction]) -> FoodTable {
+        let key_list: Vec<&str> = food_table.iter()
+            .filter(|(_, food)| self.is_allowed(food, restriction_list))
+            .map(|(key, _)| key.as_str())
+            .collect();
+        food_table.get_list(&key_list)
+    }
+
+    // 制限に当てはまる食品の表。当てはまる食品がなければNone
+    pub fn get_warning_table(&self, food_table: &FoodTable, restriction_list: &[Restriction]) -> Option<OutputTable> {
+        let mut table = OutputTable::new(
+            ["食品番号", "食品名", "制限", "原材料"].iter().map(|c| c.to_string()).collect(),
+            vec![String::new(); 4],
+            false);
+
+        for (_, food) in food_table.iter() {
+            for (restriction, tag_list) in self.get_violation_list(food, restriction_list) {
+                let tag_text: Vec<_> = tag_list.iter().map(|tag| tag.to_str()).collect();
+                table.add_row(RowKind::Food, vec![
+                    OutputCell::new(get_string(food, Nutrient::Number), ""),
+                    OutputCell::new(get_string(food, Nutrient::Name), ""),
+                    OutputCell::new(restriction.to_str(), "r"),
+                    OutputCell::new(&tag_text.join(", "), ""),
+                ]);
+            }
+        }
+
+        if table.row_list.is_empty() { None } else { Some(table) }
+    }
+}
+
+
+#[cfg(test)]
+fn get_test_food(number: &str, group: &str, name: &str) -> Food {
+    let mut food = Food::new();
+    food.set("食品番号", FoodData::String(number.to_string()));
+    food.set("食品群", FoodData::String(group.to_string()));
+    food.set("食品名", FoodData::String(name.to_string()));
+    food
+}
+
+#[test]
+fn test_restriction_get_tag_list() {
+    let tag_table = TagTable::new();
+    let get = |group, name| tag_table.get_tag_list(&get_test_food("00000", group, name));
+
+    assert_eq!(get("10", "＜えび・かに類＞（えび類）　あまえび　生"), vec![Tag::Shrimp, Tag::Seafood]);
+    assert_eq!(get("10", "＜えび・かに類＞（かに類）　毛がに　生"), vec![Tag::Crab, Tag::Seafood]);
+    assert_eq!(get("01", "こむぎ　［パン類］　食パン"), vec![Tag::Wheat, Tag::HighFodmap]);
+    assert_eq!(get("01", "こむぎ　［中華めん類］　沖縄そば　生"), vec![Tag::Wheat, Tag::HighFodmap]);
+    assert_eq!(get("01", "そば　そば　生"), vec![Tag::Buckwheat]);
+    assert_eq!(get("04", "だいず　［その他］　豆乳　豆乳"), vec![Tag::HighFodmap]);
+    assert_eq!(get("11", "＜畜肉類＞うし　［乳用肥育牛肉］　もも　赤肉　生"), vec![Tag::Meat]);
+    assert_eq!(get("11", "＜畜肉類＞ぶた　［ベーコン類］　ベーコン"), vec![Tag::Meat, Tag::Pork]);
+    assert_eq!(get("10", "＜水産練り製品＞魚肉ソーセージ"), vec![Tag::Seafood]);
+    assert_eq!(get("07", "（なし類）　日本なし　生"), vec![Tag::HighFodmap]);
+    // 果実類以外の「なし」や「もも」は果物ではない
+    assert!(get("10", "＜魚類＞さんま　皮なし、刺身").iter().all(|tag| *tag != Tag::HighFodmap));
+    assert_eq!(get("13", "＜牛乳及び乳製品＞（チーズ類）　プロセスチーズ"), vec![Tag::Milk]);
+}
+
+#[test]
+fn test_restriction_override() {
+    let mut tag_table = TagTable::new();
+    let peanut_butter = get_test_food("05037", "05", "らっかせい　ピーナッツバター");
+    assert_eq!(tag_table.get_tag_list(&peanut_butter), vec![Tag::Peanut]);
+
+    let value = serde_json::json!({"tags": {"01001": ["小麦", "-高FODMAP"]}});
+    tag_table.add_from_json(&value).unwrap();
+    let food = get_test_food("01001", "01", "アマランサス　玄穀");
+    assert_eq!(tag_table.get_tag_list(&food), vec![Tag::Wheat]);
+
+    let value = serde_json::json!({"tags": {"01001": ["none"]}});
+    assert!(tag_table.add_from_json(&value).is_err());
+}
+
+#[test]
+fn test_restriction_filter() {
+    let tag_table = TagTable::new();
+    let restriction_list = parse_restriction_list(&["卵", "vegan"]).unwrap();
+    assert!(parse_restriction_list(&["none"]).is_err());
+
+    let egg = get_test_food("12004", "12", "鶏卵　全卵　生");
+    let violation_list = tag_table.get_violation_list(&egg, &restriction_list);
+    assert_eq!(violation_list, vec![
+        (Restriction::Allergen(Tag::Egg), vec![Tag::Egg]),
+        (Restriction::Vegan, vec![Tag::Egg]),
+    ]);
+
+    let food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
+    // ミートボールとメンチカツは肉を含む
+    let filtered = tag_table.filter(&food_table, &restriction_list);
+    let number_list: Vec<_> = filtered.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(number_list, vec!["01001", "01002", "01003", "01004", "01005", "01006"]);
+
+    let warning_table = tag_table.get_warning_table(&food_table, &restriction_list).unwrap();
+    assert_eq!(warning_table.row_list.len(), 5);
+    assert!(tag_table.get_warning_table(&filtered, &restriction_list).is_none());
+}
@@ -0,0 +1,328 @@
+use crate::food::food_data::FoodData;
+use crate::food::nutrient::Nutrient;
+use crate::food_table::FoodTable;
+
+// よく使われる表記と成分表の表記の対応
+const SYNONYM_LIST: [(&str, &str); 24] = [
+    ("豚", "ぶた"),
+    ("牛", "うし"),
+    ("鶏肉", "にわとり"),
+    ("鶏", "にわとり"),
+    ("卵", "鶏卵"),
+    ("玉子", "鶏卵"),
+    ("ご飯", "めし"),
+    ("ごはん", "めし"),
+    ("米", "こめ"),
+    ("人参", "にんじん"),
+    ("ほうれん草", "ほうれんそう"),
+    ("玉ねぎ", "たまねぎ"),
+    ("玉葱", "たまねぎ"),
+    ("大根", "だいこん"),
+    ("白菜", "はくさい"),
+    ("胡瓜", "きゅうり"),
+    ("茄子", "なす"),
+    ("南瓜", "かぼちゃ"),
+    ("林檎", "りんご"),
+    ("鮭", "さけ"),
+    ("鯖", "さば"),
+    ("豆腐", "とうふ"),
+    ("納豆", "なっとう"),
+    ("牛乳", "普通牛乳"),
+];
+
+// 一致の仕方ごとの点数
+const SCORE_EXACT: u32 = 4;
+const SCORE_WITHOUT_BRACKETS: u32 = 3;
+const SCORE_ALIAS: u32 = 2;
+const SCORE_FUZZY: u32 = 1;
+
+const HALF_WIDTH_KATAKANA: &str = "｡｢｣､･ｦｧｨｩｪｫｬｭｮｯｰｱｲｳｴｵｶｷｸｹｺｻｼｽｾｿﾀﾁﾂﾃﾄﾅﾆﾇﾈﾉﾊﾋﾌﾍﾎﾏﾐﾑﾒﾓﾔﾕﾖﾗﾘﾙﾚﾛﾜﾝ";
+const FULL_WIDTH_KATAKANA: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン";
+
+// 検索のために文字を揃える
+// ひらがなはカタカナに、全角英数記号と半角カタカナはそれぞれ半角と全角にして、空白は取り除く
+pub fn normalize(text: &str) -> String {
+    let mut normalized: Vec<char> = Vec::new();
+
+    for c in text.chars() {
+        let c = match c {
+            // ひらがな
+            'ぁ'..='ゖ' => std::char::from_u32(c as u32 + 0x60).unwrap(),
+            // 全角英数記号
+            '！'..='～' => std::char::from_u32(c as u32 - 0xFEE0).unwrap(),
+            // 濁点と半濁点は前の文字とまとめる
+            'ﾞ' | 'ﾟ' => {
+                if let Some(last) = normalized.last_mut() {
+                    *last = add_voiced_mark(*last, c == 'ﾟ').unwrap_or(*last);
+                }
+                continue
+            },
+            'ｦ'..='ﾝ' | '｡'..='･' => {
+                let index = HALF_WIDTH_KATAKANA.chars().position(|h| h == c).unwrap();
+                FULL_WIDTH_KATAKANA.chars().nth(index).unwrap()
+            },
+            _ => c
+        };
+
+        if c.is_whitespace() {
+            continue
+        }
+
+        normalized.push(c.to_ascii_lowercase());
+    }
+
+    normalized.into_iter().collect()
+}
+
+fn add_voiced_mark(c: char, is_semi_voiced: bool) -> Option<char> {
+    let offset = if is_semi_voiced {
+        if !"ハヒフヘホ".contains(c) {
+            return None
+        }
+        2
+    } else if c == 'ウ' {
+        return Some('ヴ')
+    } else if "カキクケコサシスセソタチツテトハヒフヘホ".contains(c) {
+        1
+    } else {
+        return None
+    };
+
+    std::char::from_u32(c as u32 + offset)
+}
+
+// 括弧で囲まれた部分を取り除く。normalizeした後の文字列に使う
+fn remove_brackets(text: &str) -> String {
+    let mut result = String::new();
+    let mut depth = 0;
+
+    for c in text.chars() {
+        match c {
+            '[' | '<' | '(' => depth += 1,
+            ']' | '>' | ')' if depth > 0 => depth -= 1,
+            _ if depth == 0 => result.push(c),
+            _ => ()
+        }
+    }
+
+    result
+}
+
+// 備考の「別名： オート、オーツ」の行から別名を取り出す
+fn parse_alias(remark: &str) -> Vec<String> {
+    let mut alias_list = Vec::new();
+
+    for line in remark.lines() {
+        let line = line.trim();
+        let rest = match line.strip_prefix("別名") {
+            Some(rest) => rest.trim_start_matches(['：', ':', ' ', '　']),
+            None => continue
+        };
+
+        for alias in rest.split(['、', ',']) {
+            let alias = normalize(alias);
+            if !alias.is_empty() {
+                alias_list.push(alias);
+            }
+        }
+    }
+
+    alias_list
+}
+
+// patternをtextの一部に一致させるための最小の編集距離
+fn fuzzy_distance(pattern: &[char], text: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=pattern.len()).collect();
+    let mut min = pattern.len();
+
+    for t in text {
+        let mut current = vec![0; pattern.len() + 1];
+        for (i, p) in pattern.iter().enumerate() {
+            let cost = if p == t { 0 } else { 1 };
+            current[i + 1] = (prev[i] + cost)
+                .min(prev[i + 1] + 1)
+                .min(current[i] + 1);
+        }
+        min = min.min(current[pattern.len()]);
+        prev = current;
+    }
+
+    min
+}
+
+// 検索語の長さに応じて許す誤字の数
+fn allowed_typo(pattern: &[char]) -> usize {
+    match pattern.len() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2
+    }
+}
+
+struct Entry {
+    key: String,
+    name: String,
+    name_without_brackets: String,
+    alias_list: Vec<String>,
+}
+
+pub struct SearchEngine {
+    entry_list: Vec<Entry>,
+    // 正規化した語と、その語の代わりに探す語
+    synonym_list: Vec<(String, String)>,
+}
+
+impl SearchEngine {
+    pub fn new(food_table: &FoodTable) -> SearchEngine {
+        let mut entry_list = Vec::new();
+
+        for (key, food) in food_table.iter() {
+            let name = match food.get_nutrient(Nutrient::Name) {
+                FoodData::String(name) => normalize(name),
+                _ => continue
+            };
+            let alias_list = match food.get_nutrient(Nutrient::Remark) {
+                FoodData::String(remark) => parse_alias(remark),
+                _ => Vec::new()
+            };
+
+            entry_list.push(Entry {
+                key: key.to_string(),
+                name_without_brackets: remove_brackets(&name),
+                name,
+                alias_list,
+            });
+        }
+
+        let synonym_list = SYNONYM_LIST.iter()
+            .map(|(word, synonym)| (normalize(word), normalize(synonym)))
+            .collect();
+
+        SearchEngine {
+            entry_list,
+            synonym_list,
+        }
+    }
+
+    // 検索語と、同義語で置き換えた検索語
+    fn expand(&self, text: &str) -> Vec<String> {
+        let text = normalize(text);
+        let mut variant_list = vec![text.clone()];
+
+        for (word, synonym) in &self.synonym_list {
+            if text.contains(word.as_str()) {
+                variant_list.push(text.replace(word.as_str(), synonym));
+            }
+        }
+
+        variant_list
+    }
+
+    fn score(entry: &Entry, variant_list: &[String]) -> u32 {
+        let mut score = 0;
+
+        for variant in variant_list {
+            let s = if entry.name.contains(variant.as_str()) {
+                SCORE_EXACT
+            } else if entry.name_without_brackets.contains(variant.as_str()) {
+                SCORE_WITHOUT_BRACKETS
+            } else if entry.alias_list.iter().any(|alias| alias.contains(variant.as_str())) {
+                SCORE_ALIAS
+            } else {
+                let pattern: Vec<char> = variant.chars().collect();
+                let text: Vec<char> = entry.name_without_brackets.chars().collect();
+                let allowed = allowed_typo(&pattern);
+                if allowed > 0 && fuzzy_distance(&pattern, &text) <= allowed {
+                    SCORE_FUZZY
+                } else {
+                    0
+                }
+            };
+            score = score.max(s);
+        }
+
+        score
+    }
+
+    // 一致した食品の食品番号を関連度の高い順に返す
+    // is_andがtrueならすべての検索語に、falseならいずれかの検索語に一致したものを返す
+    pub fn search(&self, text_list: &[&str], is_and: bool) -> Vec<String> {
+        let variant_list: Vec<_> = text_list.iter().map(|text| self.expand(text)).collect();
+        let mut result = Vec::new();
+
+        for entry in &self.entry_list {
+            let score_list: Vec<_> = variant_list.iter()
+                .map(|variants| SearchEngine::score(entry, variants))
+                .collect();
+
+            let is_match = if is_and {
+                score_list.iter().all(|s| *s > 0)
+            } else {
+                score_list.iter().any(|s| *s > 0)
+            };
+
+            if is_match {
+                let score: u32 = score_list.iter().sum();
+                result.push((score, entry.name.chars().count(), &entry.key));
+            }
+        }
+
+        // 点数の高い順、同じ点数なら名前の短い順。それも同じなら元の順番
+        result.sort_by(|(score_a, len_a, _), (score_b, len_b, _)| {
+            score_b.cmp(score_a).then(len_a.cmp(len_b))
+        });
+
+        result.into_iter().map(|(_, _, key)| key.to_string()).collect()
+    }
+}
+
+
+#[test]
+fn test_normalize() {
+    assert_eq!(normalize("ほうれんそう"), "ホウレンソウ");
+    assert_eq!(normalize("ﾎｳﾚﾝｿｳ"), "ホウレンソウ");
+    assert_eq!(normalize("ﾊﾟﾝ　ｶﾞﾑ"), "パンガム");
+    assert_eq!(normalize("ＡＢＣ１２３　abc"), "abc123abc");
+    assert_eq!(normalize("［大型種肉］"), "[大型種肉]");
+}
+
+#[test]
+fn test_remove_brackets() {
+    let name = normalize("＜畜肉類＞ぶた　［大型種肉］　ロース　脂身つき　生");
+    assert_eq!(remove_brackets(&name), "ブタロース脂身ツキ生");
+}
+
+#[test]
+fn test_parse_alias() {
+    assert_eq!(parse_alias("別名： オート、オーツ"), vec!["オート", "オーツ"]);
+    assert_eq!(parse_alias("表層粉の一部を除いたもの\n別名： 挽きぐるみ"), vec!["挽キグルミ"]);
+    assert!(parse_alias("歩留り： 70～80 %").is_empty());
+}
+
+#[test]
+fn test_fuzzy_distance() {
+    let pattern: Vec<char> = "メンチカツ".chars().collect();
+    let text: Vec<char> = "ミートボールメンチカツフライ".chars().collect();
+    assert_eq!(fuzzy_distance(&pattern, &text), 0);
+    let text: Vec<char> = "メンチカシフライ".chars().collect();
+    assert_eq!(fuzzy_distance(&pattern, &text), 1);
+}
+
+#[test]
+fn test_search_engine() {
+    let food_table = FoodTable::from_json("./test/test_foods.json").unwrap();
+    let engine = SearchEngine::new(&food_table);
+
+    // ひらがなとカタカナ、全角と半角を区別しない
+    assert_eq!(engine.search(&["ｱﾜ"], true), vec!["01002", "01003"]);
+    // 別名で検索できる
+    assert_eq!(engine.search(&["オーツ"], true), vec!["01004"]);
+    // 誤字を許す
+    assert_eq!(engine.search(&["メンチカシ"], true), vec!["18016", "18022"]);
+    // 関連度の高い順に並ぶ
+    assert_eq!(engine.search(&["あわもち"], true), vec!["01003"]);
+    assert_eq!(engine.search(&["おおむぎ", "押麦"], false)[0], "01006");
+    assert!(engine.search(&["おおむぎ", "ミート"], true).is_empty());
+    // 同義語に置き換えた検索語も探す
+    assert_eq!(engine.expand("豚ロース"), vec!["豚ロース", "ブタロース"]);
+}
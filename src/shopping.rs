@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::food::Food;
+use crate::food::food_data::FoodData;
+use crate::food::nutrient::Nutrient;
+use crate::output::{number_to_json, OutputCell, OutputTable, RowKind};
+use crate::parse_json::PurchaseUnit;
+
+// 日本食品標準成分表の食品群
+const GROUP_NAME_LIST: [&str; 18] = [
+    "穀類", "いも及びでん粉類", "砂糖及び甘味類", "豆類", "種実類", "野菜類",
+    "果実類", "きのこ類", "藻類", "魚介類", "肉類", "卵類", "乳類", "油脂類",
+    "菓子類", "し好飲料類", "調味料及び香辛料類", "調理済み流通食品類",
+];
+
+// 食品群の番号から名前にする。食品群がない食品はユーザー定義の食品にする
+pub fn get_group_name(group: &str) -> String {
+    match group.parse::<usize>() {
+        Ok(number) if 1 <= number && number <= GROUP_NAME_LIST.len() => {
+            GROUP_NAME_LIST[number - 1].to_string()
+        },
+        _ if group.is_empty() => "ユーザー定義".to_string(),
+        _ => group.to_string()
+    }
+}
+
+fn get_string(food: &Food, nutrient: Nutrient) -> String {
+    match food.get_nutrient(nutrient) {
+        FoodData::String(text) => text.to_string(),
+        _ => String::new()
+    }
+}
+
+pub struct ShoppingItem {
+    pub number: String,
+    pub name: String,
+    pub group: String,
+    // 可食部の重量(g)
+    pub edible: f32,
+    pub refuse: f32,
+    // 廃棄部分を含めた購入する重量(g)
+    pub purchase: f32,
+    // 価格がわからない食品がある場合はNone
+    pub cost: Option<f32>,
+}
+
+impl ShoppingItem {
+    // 購入単位に切り上げた(数量, 購入量, 費用)
+    fn round(&self, unit: &PurchaseUnit) -> (f32, f32, Option<f32>) {
+        let count = (self.purchase / unit.weight).ceil();
+        let purchase = count * unit.weight;
+        let cost = match self.cost {
+            Some(cost) if self.purchase > 0.0 => Some(cost * purchase / self.purchase),
+            cost => cost
+        };
+
+        (count, purchase, cost)
+    }
+}
+
+pub struct ShoppingList {
+    item_list: Vec<ShoppingItem>,
+}
+
+impl ShoppingList {
+    pub fn new() -> ShoppingList {
+        ShoppingList { item_list: Vec::new() }
+    }
+
+    // weightは可食部の重量。is_including_refuseがtrueなら廃棄部分を含めた重量
+    // priceはJSONで指定されたその量の価格。なければ食品の価格を食品の重量あたりの価格として使う
+    pub fn add(&mut self, number: &str, food: &Food, weight: f32,
+               is_including_refuse: bool, price: Option<f32>) -> Result<(), String> {
+        let refuse = food.get_nutrient(Nutrient::Refuse).get_number().copied().unwrap_or(0.0);
+        let (edible, purchase) = if is_including_refuse {
+            (weight * (1.0 - refuse / 100.0), weight)
+        } else if refuse < 100.0 {
+            (weight, weight / (1.0 - refuse / 100.0))
+        } else {
+            return Err(format!("{}番の食材は廃棄率が100%なので購入量を計算できません", number))
+        };
+
+        let cost = match price {
+            Some(price) => Some(price),
+            None => {
+                let food_weight = food.get_nutrient(Nutrient::Weight).get_number().copied().unwrap_or(100.0);
+                match food.get_nutrient(Nutrient::Price).get_number() {
+                    Some(price) if food_weight > 0.0 => Some(price * purchase / food_weight),
+                    _ => None
+                }
+            }
+        };
+
+        match self.item_list.iter_mut().find(|item| item.number == number) {
+            Some(item) => {
+                item.edible += edible;
+                item.purchase += purchase;
+                item.cost = match (item.cost, cost) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    _ => None
+                };
+            },
+            None => self.item_list.push(ShoppingItem {
+                number: number.to_string(),
+                name: get_string(food, Nutrient::Name),
+                group: get_string(food, Nutrient::Group),
+                edible,
+                refuse,
+                purchase,
+                cost,
+            })
+        }
+
+        Ok(())
+    }
+
+    // 食品群、食品番号の順。ユーザー定義の食品は最後にする
+    fn get_sorted_item_list(&self) -> Vec<&ShoppingItem> {
+        let mut item_list: Vec<_> = self.item_list.iter().collect();
+        item_list.sort_by(|a, b| {
+            (a.group.is_empty(), &a.group, &a.number).cmp(&(b.group.is_empty(), &b.group, &b.number))
+        });
+        item_list
+    }
+
+    // is_roundedがtrueなら購入単位がある食品の購入量を切り上げる
+    pub fn get_table(&self, unit_list: &HashMap<String, PurchaseUnit>, is_rounded: bool) -> OutputTable {
+        let mut table = OutputTable::new(
+            ["食品群", "食品番号", "食品名", "可食量", "廃棄率", "購入量", "購入単位", "価格"]
+                .iter().map(|c| c.to_string()).collect(),
+            ["", "", "", "g", "%", "g", "", "円"].iter().map(|u| u.to_string()).collect(),
+            false);
+
+        let mut purchase_sum = 0.0;
+        let mut cost_sum = 0.0;
+        let mut is_cost_unknown = false;
+        for item in self.get_sorted_item_list() {
+            let (unit_text, purchase, cost) = match unit_list.get(&item.number) {
+                Some(unit) if is_rounded => {
+                    let (count, purchase, cost) = item.round(unit);
+                    (format!("{}{}({}g)", count, unit.name, unit.weight), purchase, cost)
+                },
+                Some(unit) => {
+                    (format!("{:.1}{}({}g)", item.purchase / unit.weight, unit.name, unit.weight),
+                     item.purchase, item.cost)
+                },
+                None => ("-".to_string(), item.purchase, item.cost)
+            };
+
+            purchase_sum += purchase;
+            match cost {
+                Some(cost) => cost_sum += cost,
+                None => is_cost_unknown = true
+            }
+
+            table.add_row(RowKind::Food, vec![
+                OutputCell::new(&get_group_name(&item.group), ""),
+                OutputCell::new(&item.number, ""),
+                OutputCell::new(&item.name, ""),
+                OutputCell::with_value(&format!("{:.0}", item.edible), "", number_to_json(item.edible)),
+                OutputCell::with_value(&format!("{}", item.refuse), "", number_to_json(item.refuse)),
+                OutputCell::with_value(&format!("{:.0}", purchase), "", number_to_json(purchase)),
+                OutputCell::new(&unit_text, ""),
+                match cost {
+                    Some(cost) => OutputCell::with_value(&format!("{:.0}", cost), "", number_to_json(cost)),
+                    None => OutputCell::with_value("-", "", Value::Null)
+                },
+            ]);
+        }
+
+        // 価格がわからない食品がある場合は、わかる食品だけの合計にする
+        // JSONでは下限値のオブジェクトにする
+        let cost_cell = if is_cost_unknown {
+            OutputCell::with_value(&format!("{:.0}以上", cost_sum), "y+", FoodData::LowerBound(cost_sum).to_json())
+        } else {
+            OutputCell::with_value(&format!("{:.0}", cost_sum), "y+", number_to_json(cost_sum))
+        };
+        table.add_row(RowKind::Sum, vec![
+            OutputCell::new("合計", "y+"),
+            OutputCell::new("", "y+"),
+            OutputCell::new(&format!("{}品目", self.item_list.len()), "y+"),
+            OutputCell::new("", "y+"),
+            OutputCell::new("", "y+"),
+            OutputCell::with_value(&format!("{:.0}", purchase_sum), "y+", number_to_json(purchase_sum)),
+            OutputCell::new("", "y+"),
+            cost_cell,
+        ]);
+
+        table
+    }
+}
+
+
+#[cfg(test)]
+fn get_test_food(number: &str, group: &str, refuse: f32, price: Option<f32>) -> Food {
+    let mut food = Food::new();
+    food.set("食品番号", FoodData::String(number.to_string()));
+    food.set("食品群", FoodData::String(group.to_string()));
+    food.set("食品名", FoodData::String(format!("食品{}", number)));
+    food.set("重量", FoodData::Number(100.0));
+    food.set("廃棄率", FoodData::Number(refuse));
+    if let Some(price) = price {
+        food.set("価格", FoodData::Number(price));
+    }
+    food
+}
+
+#[test]
+fn test_shopping_get_group_name() {
+    assert_eq!(get_group_name("06"), "野菜類");
+    assert_eq!(get_group_name("18"), "調理済み流通食品類");
+    assert_eq!(get_group_name(""), "ユーザー定義");
+}
+
+#[test]
+fn test_shopping_list_add() {
+    let mut shopping_list = ShoppingList::new();
+    let carrot = get_test_food("06212", "06", 20.0, Some(50.0));
+    // 可食部80gは廃棄率20%なので100g買う
+    shopping_list.add("06212", &carrot, 80.0, false, None).unwrap();
+    // 廃棄部分を含めた重量の場合はそのまま
+    shopping_list.add("06212", &carrot, 100.0, true, None).unwrap();
+    let item = &shopping_list.item_list[0];
+    assert_eq!(item.purchase, 200.0);
+    assert_eq!(item.edible, 160.0);
+    assert_eq!(item.cost, Some(100.0));
+
+    let all_refuse = get_test_food("00001", "01", 100.0, None);
+    assert!(shopping_list.add("00001", &all_refuse, 10.0, false, None).is_err());
+}
+
+#[test]
+fn test_shopping_list_get_table() {
+    let mut shopping_list = ShoppingList::new();
+    shopping_list.add("04046", &get_test_food("04046", "04", 0.0, None), 100.0, false, None).unwrap();
+    shopping_list.add("01088", &get_test_food("01088", "01", 0.0, Some(40.0)), 150.0, false, None).unwrap();
+    shopping_list.add("u1", &get_test_food("u1", "", 0.0, None), 30.0, false, Some(120.0)).unwrap();
+
+    let mut unit_list = HashMap::new();
+    unit_list.insert("04046".to_string(), PurchaseUnit { name: "パック".to_string(), weight: 45.0 });
+
+    let table = shopping_list.get_table(&unit_list, false);
+    let number_list: Vec<_> = table.row_list.iter().map(|row| row.cell_list[1].text.as_str()).collect();
+    assert_eq!(number_list, vec!["01088", "04046", "u1", ""]);
+    assert_eq!(table.row_list[1].cell_list[6].text, "2.2パック(45g)");
+    assert_eq!(table.row_list[3].cell_list[7].text, "180以上");
+
+    // 購入単位に切り上げる
+    let table = shopping_list.get_table(&unit_list, true);
+    assert_eq!(table.row_list[1].cell_list[5].text, "135");
+    assert_eq!(table.row_list[1].cell_list[6].text, "3パック(45g)");
+    assert_eq!(table.row_list[3].cell_list[5].text, "315");
+}
+
+#[test]
+fn test_shopping_list_with_price_data() {
+    use crate::food_table::FoodTable;
+
+    // 配布している価格のデータで価格を求める
+    let mut foods = FoodTable::from_json("./data/foods.json").unwrap();
+    foods.set_price_from_json("./data/price/prices.json").unwrap();
+    let mut shopping_list = ShoppingList::new();
+    shopping_list.add("01083", foods.get("01083").unwrap(), 200.0, false, None).unwrap();
+    // キャベツは廃棄率15%なので、可食部170gは200g買う
+    shopping_list.add("06061", foods.get("06061").unwrap(), 170.0, false, None).unwrap();
+
+    let table = shopping_list.get_table(&HashMap::new(), false);
+    assert_eq!(table.row_list[0].cell_list[7].text, "73");
+    assert_eq!(table.row_list[1].cell_list[5].text, "200");
+    assert_eq!(table.row_list[1].cell_list[7].text, "37");
+    assert_eq!(table.row_list[2].cell_list[7].text, "110");
+}
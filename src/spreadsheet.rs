@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use rust_xlsxwriter::{Formula, Note, Workbook, Worksheet, XlsxError};
+use rust_xlsxwriter::utility::row_col_to_cell;
+
+use crate::food::Food;
+use crate::food::nutrient::Nutrient;
+use crate::food_table::{FoodTable, get_column_label, get_column_unit, get_computed_nutrient_list};
+use crate::kijun::{Kijun, KijunValue};
+
+// シート名に使えない文字
+const INVALID_SHEET_NAME_CHARS: [char; 7] = ['[', ']', ':', '*', '?', '/', '\\'];
+// シート名の最大の長さ
+const MAX_SHEET_NAME_LENGTH: usize = 31;
+
+// 表計算ソフトに書き出す1枚のシート
+pub struct Sheet {
+    pub name: String,
+    pub food_table: FoodTable,
+    pub name_list: Vec<String>,
+    // 摂取基準の行を書き出す場合に指定する
+    pub kijun: Option<Rc<Kijun>>,
+}
+
+fn to_error(e: XlsxError) -> String {
+    e.to_string()
+}
+
+// f32をそのままf64にすると12.3が12.300000190734863のようになるので、表示する値にする
+fn to_f64(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(value as f64)
+}
+
+// シート名に使えない文字を置き換えて、長さを制限し、他のシート名と被らないようにする
+fn get_sheet_name(name: &str, used_name_list: &[String]) -> String {
+    let name: String = name.chars()
+        .map(|c| if INVALID_SHEET_NAME_CHARS.contains(&c) { '_' } else { c })
+        .collect();
+    let name = if name.trim().is_empty() { "Sheet".to_string() } else { name };
+
+    let mut count = 1;
+    loop {
+        let suffix = if count == 1 { String::new() } else { format!("({})", count) };
+        let length = MAX_SHEET_NAME_LENGTH - suffix.chars().count();
+        let candidate: String = name.chars().take(length).collect::<String>() + &suffix;
+        // Excelはシート名の大文字と小文字を区別しない
+        if !used_name_list.iter().any(|used| used.to_lowercase() == candidate.to_lowercase()) {
+            return candidate
+        }
+        count += 1;
+    }
+}
+
+// 摂取基準の値を列の単位に合わせる。単位が違って変換できない場合はNone
+fn get_kijun_value(kijun: &Kijun, name: &str) -> Option<KijunValue> {
+    let value = kijun.get(name)?;
+    match (Nutrient::from_name(name), Kijun::get_unit(name)) {
+        (Some(nutrient), Some(unit)) => value.convert(unit, nutrient.get_unit()),
+        _ => Some(*value)
+    }
+}
+
+// 摂取基準に対する割合を計算する式。KijunValue::get_percentageと同じ計算をする
+// kijun_cellは摂取基準の値（範囲の場合は下限）のセル、max_cellは範囲の上限のセル
+fn get_percentage_formula(kijun_value: &KijunValue, cell: &str, kijun_cell: &str, max_cell: &str) -> Option<String> {
+    let formula = match kijun_value {
+        KijunValue::Suisyo(value) | KijunValue::Measu(value) if *value != 0.0 => {
+            format!("={}/{}*100", cell, kijun_cell)
+        },
+        KijunValue::Less(value) if *value != 0.0 => {
+            format!("=IF({c}<={v},100,{c}/{v}*100)", c = cell, v = kijun_cell)
+        },
+        KijunValue::More(value) if *value != 0.0 => {
+            format!("=IF({c}>={v},100,{c}/{v}*100)", c = cell, v = kijun_cell)
+        },
+        KijunValue::Range((min, max)) if *min != 0.0 && *max != 0.0 => {
+            format!("=IF({c}<{min},{c}/{min}*100,IF({c}>{max},{c}/{max}*100,100))",
+                    c = cell, min = kijun_cell, max = max_cell)
+        },
+        _ => return None
+    };
+
+    Some(formula)
+}
+
+// 食品の値を重量のセルから計算する式。重量で変わらない項目や重量がない場合はNone
+fn get_weight_formula(food: &Food, name: &str, value: f32, weight_cell: Option<&str>) -> Option<String> {
+    let nutrient = Nutrient::from_name(name)?;
+    if !nutrient.is_scalable() || nutrient == Nutrient::Weight {
+        return None
+    }
+
+    let weight = *food.get_nutrient(Nutrient::Weight).get_number()?;
+    if weight <= 0.0 {
+        return None
+    }
+
+    Some(format!("={}*{}/{}", value, weight_cell?, weight))
+}
+
+// 値のセル。式にできる場合は式にして、値は計算した結果にする
+fn write_value(worksheet: &mut Worksheet, row: u32, col: u16, value: Result<f32, String>,
+               formula: Option<String>) -> Result<(), XlsxError> {
+    match (value, formula) {
+        (Ok(num), Some(formula)) => {
+            worksheet.write_formula(row, col, Formula::new(formula).set_result(num.to_string()))?
+        },
+        (Ok(num), None) => worksheet.write_number(row, col, to_f64(num))?,
+        (Err(text), _) => worksheet.write_string(row, col, text)?
+    };
+
+    Ok(())
+}
+
+fn write_sheet(worksheet: &mut Worksheet, sheet: &Sheet) -> Result<(), XlsxError> {
+    let food_table = &sheet.food_table;
+
+    // 計算式の列で使う項目が列にない場合は、隠した列に書き出して式から参照する
+    let mut name_list: Vec<_> = sheet.name_list.iter().map(|name| name.to_string()).collect();
+    let visible_count = name_list.len();
+    for name in &sheet.name_list {
+        for nutrient in get_computed_nutrient_list(name) {
+            let is_listed = name_list.iter().any(|n| Nutrient::from_name(n) == Some(nutrient));
+            if !is_listed {
+                name_list.push(nutrient.get_name().to_string());
+            }
+        }
+    }
+    let name_list: Vec<_> = name_list.iter().map(|name| name.as_str()).collect();
+
+    // 項目の値がある列
+    let mut column_list: HashMap<Nutrient, u16> = HashMap::new();
+    for (col, name) in name_list.iter().enumerate() {
+        if let Some(nutrient) = Nutrient::from_name(name) {
+            column_list.entry(nutrient).or_insert(col as u16);
+        }
+    }
+    let get_cell = |row: u32| {
+        let column_list = &column_list;
+        move |nutrient: Nutrient| row_col_to_cell(row, column_list[&nutrient])
+    };
+
+    // 見出し
+    for (col, name) in name_list.iter().enumerate() {
+        let unit = get_column_unit(name);
+        let header = if unit.is_empty() {
+            get_column_label(name)
+        } else {
+            format!("{}({})", get_column_label(name), unit)
+        };
+        worksheet.write_string(0, col as u16, header)?;
+        if *name == "食品名" {
+            worksheet.set_column_width(col as u16, 40)?;
+        }
+        if col >= visible_count {
+            worksheet.set_column_hidden(col as u16)?;
+        }
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+
+    // 食品の行。重量を編集したときに再計算されるように、重量で変わる値は式にする
+    let mut row = 1;
+    for (_, food) in food_table.iter() {
+        let weight_cell = column_list.get(&Nutrient::Weight).map(|col| row_col_to_cell(row, *col));
+        for (col, name) in name_list.iter().enumerate() {
+            let value = food_table.get_number_or_text(food, name);
+            let formula = match &value {
+                Ok(num) if Nutrient::from_name(name).is_some() => {
+                    get_weight_formula(food, name, *num, weight_cell.as_deref())
+                },
+                Ok(_) => food_table.get_formula(name, &get_cell(row)),
+                Err(_) => None
+            };
+            write_value(worksheet, row, col as u16, value, formula)?;
+        }
+        row += 1;
+    }
+    let last_food_row = row - 1;
+
+    // 合計の行。食品の値を編集したときに再計算されるように式にする
+    let sum_row = row;
+    let sum_food = food_table.get_sum();
+    for (col, name) in name_list.iter().enumerate() {
+        let col = col as u16;
+        if *name == "食品名" {
+            worksheet.write_string(sum_row, col, "合計")?;
+            continue
+        }
+
+        let value = food_table.get_number_or_text(&sum_food, name);
+        let formula = if sum_food.get(name).is_some() {
+            if last_food_row >= 1 {
+                Some(format!("=SUM({}:{})", row_col_to_cell(1, col), row_col_to_cell(last_food_row, col)))
+            } else {
+                None
+            }
+        } else {
+            // 計算式や栄養密度の列は合計の値から計算する
+            food_table.get_formula(name, &get_cell(sum_row))
+        };
+        write_value(worksheet, sum_row, col, value, formula)?;
+    }
+
+    let kijun = match &sheet.kijun {
+        Some(kijun) => kijun,
+        None => return Ok(())
+    };
+
+    // 摂取基準の行と、範囲の上限の行、摂取基準に対する割合の行
+    // 摂取基準の値を編集したときも割合が再計算されるように、値は数値で書き出して式から参照する
+    let kijun_row = sum_row + 1;
+    let max_row = sum_row + 2;
+    let percentage_row = sum_row + 3;
+    for (col, name) in name_list.iter().take(visible_count).enumerate() {
+        let col = col as u16;
+        if *name == "食品名" {
+            worksheet.write_string(kijun_row, col, format!("摂取基準値（{}日分）", kijun.days))?;
+            worksheet.write_string(max_row, col, format!("摂取基準値の上限（{}日分）", kijun.days))?;
+            worksheet.write_string(percentage_row, col, format!("摂取基準に対する割合（{}日分）", kijun.days))?;
+            continue
+        }
+
+        let kijun_value = match (kijun.get(name), get_kijun_value(kijun, name)) {
+            (Some(_), Some(kijun_value)) => kijun_value,
+            (Some(_), None) => {
+                worksheet.write_string(kijun_row, col, "単位不一致")?;
+                continue
+            },
+            (None, _) => continue
+        };
+        match kijun_value {
+            KijunValue::Range((min, max)) => {
+                worksheet.write_number(kijun_row, col, to_f64(min))?;
+                worksheet.write_number(max_row, col, to_f64(max))?;
+            },
+            _ => {
+                worksheet.write_number(kijun_row, col, to_f64(kijun_value.get_reference()))?;
+            }
+        }
+        // 食事療法などで変更した値にはメモを付ける
+        if let Some((source, original)) = kijun.get_override(name) {
+            let original = original.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+            let note = Note::new(format!("{}で変更（標準: {}）", source, original)).add_author_prefix(false);
+            worksheet.insert_note(kijun_row, col, &note)?;
+        }
+
+        let sum_cell = row_col_to_cell(sum_row, col);
+        let kijun_cell = row_col_to_cell(kijun_row, col);
+        let max_cell = row_col_to_cell(max_row, col);
+        if let Some(formula) = get_percentage_formula(&kijun_value, &sum_cell, &kijun_cell, &max_cell) {
+            let mut formula = Formula::new(formula);
+            if let Ok(num) = food_table.get_number_or_text(&sum_food, name) {
+                formula = formula.set_result(kijun_value.get_percentage(num).to_string());
+            }
+            worksheet.write_formula(percentage_row, col, formula)?;
+        }
+    }
+
+    Ok(())
+}
+
+// シートごとに書き出す。ODS形式には対応していない
+pub fn write_xlsx(path: &str, sheet_list: &[Sheet]) -> Result<(), String> {
+    if path.to_lowercase().ends_with(".ods") {
+        return Err("ODS形式には対応していません。xlsx形式で書き出してください".to_string())
+    }
+
+    let mut workbook = Workbook::new();
+    let mut used_name_list = Vec::new();
+
+    for sheet in sheet_list {
+        let name = get_sheet_name(&sheet.name, &used_name_list);
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(&name).map_err(to_error)?;
+        write_sheet(worksheet, sheet).map_err(to_error)?;
+        used_name_list.push(name);
+    }
+
+    workbook.save(path).map_err(to_error)
+}
+
+// 全体のシートと、クラスごとのシートにする
+pub fn get_sheet_list(name: &str, food_table: FoodTable, name_list: &[String], kijun: Rc<Kijun>) -> Vec<Sheet> {
+    let mut class_list: Vec<_> = food_table.split_by_class().into_iter().collect();
+    class_list.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut sheet_list = vec![Sheet {
+        name: name.to_string(),
+        food_table,
+        name_list: name_list.to_vec(),
+        kijun: Some(kijun),
+    }];
+
+    for (class_name, class_food_table) in class_list {
+        sheet_list.push(Sheet {
+            name: format!("{} {}", name, class_name),
+            food_table: class_food_table,
+            name_list: name_list.to_vec(),
+            kijun: None,
+        });
+    }
+
+    sheet_list
+}
+
+
+#[test]
+fn test_spreadsheet_get_sheet_name() {
+    let used_name_list = vec!["朝食".to_string()];
+    assert_eq!(get_sheet_name("昼食", &used_name_list), "昼食");
+    assert_eq!(get_sheet_name("朝食", &used_name_list), "朝食(2)");
+    assert_eq!(get_sheet_name("a/b:c", &used_name_list), "a_b_c");
+    assert_eq!(get_sheet_name(&"あ".repeat(40), &used_name_list).chars().count(), 31);
+}
+
+#[test]
+fn test_spreadsheet_get_percentage_formula() {
+    assert_eq!(get_percentage_formula(&KijunValue::Suisyo(100.0), "D8", "D9", "D10"),
+               Some("=D8/D9*100".to_string()));
+    assert_eq!(get_percentage_formula(&KijunValue::Less(7.5), "E8", "E9", "E10"),
+               Some("=IF(E8<=E9,100,E8/E9*100)".to_string()));
+    assert_eq!(get_percentage_formula(&KijunValue::Range((20.0, 30.0)), "F8", "F9", "F10"),
+               Some("=IF(F8<F9,F8/F9*100,IF(F8>F10,F8/F10*100,100))".to_string()));
+    assert_eq!(get_percentage_formula(&KijunValue::Suisyo(0.0), "D8", "D9", "D10"), None);
+}
+
+#[test]
+fn test_spreadsheet_get_weight_formula() {
+    use crate::food::food_data::FoodData;
+
+    let mut food = Food::new();
+    food.set("重量", FoodData::Number(200.0));
+    food.set("たんぱく質", FoodData::Number(10.0));
+    assert_eq!(get_weight_formula(&food, "たんぱく質", 10.0, Some("B2")), Some("=10*B2/200".to_string()));
+    assert_eq!(get_weight_formula(&food, "たんぱく質", 10.0, None), None);
+    assert_eq!(get_weight_formula(&food, "重量", 200.0, Some("B2")), None);
+    assert_eq!(get_weight_formula(&food, "廃棄率", 0.0, Some("B2")), None);
+}
+
+#[test]
+fn test_spreadsheet_write_xlsx() {
+    use crate::kijun::{Gender, PAL};
+
+    let food_table = FoodTable::from_json("./test/test_foods.json").unwrap()
+        .get_list(&["01001", "01002"]);
+    let kijun = Rc::new(Kijun::new(20, 50.0, 160.0, Gender::Male, PAL::Low, 1));
+    let name_list: Vec<_> = ["食品番号", "食品名", "重量", "エネルギー", "たんぱく質",
+                             "P比=たんぱく質*4/エネルギー*100", "脂質 per 100kcal"].iter()
+        .map(|name| name.to_string()).collect();
+    let sheet_list = get_sheet_list("test", food_table, &name_list, kijun);
+    assert_eq!(sheet_list.len(), 1);
+
+    let path = std::env::temp_dir().join("meal_test_spreadsheet.xlsx");
+    let path = path.to_str().unwrap();
+    write_xlsx(path, &sheet_list).unwrap();
+    let data = std::fs::read(path).unwrap();
+    assert_eq!(&data[..2], b"PK");
+    std::fs::remove_file(path).unwrap();
+
+    assert!(write_xlsx("test.ods", &sheet_list).is_err());
+}
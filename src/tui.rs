@@ -0,0 +1,615 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::rc::Rc;
+
+use clap::ArgMatches;
+use ratatui::{DefaultTerminal, Frame};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Row, Table};
+use serde_json::{Value, Map};
+
+use crate::food::food_data::FoodData;
+use crate::food::nutrient::Nutrient;
+use crate::food_table::FoodTable;
+use crate::kijun::{Kijun, KEY_LIST};
+use crate::output::{number_to_json, Format, RowKind};
+use crate::parse_json::parse_json_value;
+use crate::search::SearchEngine;
+
+// 検索結果に表示する最大の数
+const MAX_RESULT_COUNT: usize = 200;
+// 食品を追加するときの重量
+const DEFAULT_WEIGHT: f32 = 100.0;
+// +と-で変える重量
+const WEIGHT_STEP: f32 = 10.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Focus {
+    Search,
+    Meal,
+    // 重量を入力している。Someなら献立の食品の重量を変え、Noneなら検索結果の食品を追加する
+    Weight(Option<usize>),
+}
+
+// 献立の1つの食品。JSONから読み込んだ場合は元の値を残して、保存するときに使う
+struct Entry {
+    number: String,
+    weight: f32,
+    value: Map<String, Value>,
+}
+
+struct App<'a> {
+    foods: &'a FoodTable,
+    // user_definition_foodsの食品
+    udf_table: FoodTable,
+    engine: SearchEngine,
+    kijun: Rc<Kijun>,
+    // 保存するJSON。foods以外はそのまま書き出す
+    data: Map<String, Value>,
+    text: String,
+    result_list: Vec<String>,
+    result_state: ListState,
+    entry_list: Vec<Entry>,
+    entry_state: ListState,
+    focus: Focus,
+    weight_text: String,
+    message: String,
+    // 保存していない変更がある
+    is_changed: bool,
+    // 変更があるときにEscを押した。もう一度Escを押すと保存せずに終了する
+    is_quitting: bool,
+}
+
+// 選択している位置を範囲に収める
+fn clamp_state(state: &mut ListState, len: usize) {
+    match state.selected() {
+        _ if len == 0 => state.select(None),
+        Some(index) if index >= len => state.select(Some(len - 1)),
+        None => state.select(Some(0)),
+        _ => ()
+    }
+}
+
+fn move_state(state: &mut ListState, len: usize, is_up: bool) {
+    if len == 0 {
+        return
+    }
+    let index = state.selected().unwrap_or(0);
+    let index = if is_up { index.saturating_sub(1) } else { (index + 1).min(len - 1) };
+    state.select(Some(index));
+}
+
+impl<'a> App<'a> {
+    // dataはcalcのJSON。bodyを含んでいる必要がある
+    fn new(foods: &'a FoodTable, data: Map<String, Value>) -> Result<App<'a>, String> {
+        // calcと同じように読み込んで、targetsやuser_definition_foodsも反映する
+        let mut value = data.clone();
+        value.entry("foods").or_insert_with(|| Value::Array(Vec::new()));
+        let parsed_data = parse_json_value(&Value::Object(value))?;
+        let kijun = parsed_data.body.get_kijun(parsed_data.body.days.unwrap_or(1))?;
+        // 献立の食品がないなど、calcで読み込めないJSONはここでエラーにする
+        parsed_data.get_food_table(foods)?;
+
+        let mut entry_list = Vec::new();
+        if let Some(Value::Array(food_list)) = data.get("foods") {
+            for food in food_list {
+                let value = match food {
+                    Value::Object(value) => value.clone(),
+                    _ => return Err("foodsの値はオブジェクトの配列にしてください".to_string())
+                };
+                let number = match value.get("number") {
+                    Some(Value::String(number)) => number.to_string(),
+                    _ => return Err("foodsの値にはnumberが必要です".to_string())
+                };
+                let weight = value.get("weight").and_then(|w| w.as_f64()).unwrap_or(100.0) as f32;
+                entry_list.push(Entry { number, weight, value });
+            }
+        }
+
+        let mut app = App {
+            foods,
+            udf_table: parsed_data.get_user_definition_table(),
+            engine: SearchEngine::new(foods),
+            kijun: Rc::new(kijun),
+            data,
+            text: String::new(),
+            result_list: Vec::new(),
+            result_state: ListState::default(),
+            entry_list,
+            entry_state: ListState::default(),
+            focus: Focus::Search,
+            weight_text: String::new(),
+            message: String::new(),
+            is_changed: false,
+            is_quitting: false,
+        };
+        clamp_state(&mut app.entry_state, app.entry_list.len());
+
+        Ok(app)
+    }
+
+    fn get_food_name(&self, number: &str) -> String {
+        let food = self.foods.get(number).or_else(|| self.udf_table.get(number));
+        match food.map(|food| food.get_nutrient(Nutrient::Name)) {
+            Some(FoodData::String(name)) => name.to_string(),
+            _ => format!("{}（不明な食品）", number)
+        }
+    }
+
+    fn update_search(&mut self) {
+        let text_list: Vec<_> = self.text.split_whitespace().collect();
+        self.result_list = if text_list.is_empty() {
+            Vec::new()
+        } else {
+            let mut result_list = self.engine.search(&text_list, true);
+            result_list.truncate(MAX_RESULT_COUNT);
+            result_list
+        };
+        self.result_state.select(if self.result_list.is_empty() { None } else { Some(0) });
+    }
+
+    fn add_food(&mut self, number: &str, weight: f32) {
+        let mut value = Map::new();
+        value.insert("number".to_string(), Value::String(number.to_string()));
+        self.entry_list.push(Entry { number: number.to_string(), weight, value });
+        self.entry_state.select(Some(self.entry_list.len() - 1));
+        self.message = format!("{}を{}g追加しました", self.get_food_name(number), weight);
+        self.is_changed = true;
+    }
+
+    fn set_weight(&mut self, index: usize, weight: f32) {
+        if let Some(entry) = self.entry_list.get_mut(index) {
+            entry.weight = weight.max(0.0);
+            self.is_changed = true;
+        }
+    }
+
+    fn remove_food(&mut self, index: usize) {
+        if index < self.entry_list.len() {
+            let entry = self.entry_list.remove(index);
+            self.message = format!("{}を削除しました", self.get_food_name(&entry.number));
+            self.is_changed = true;
+        }
+        clamp_state(&mut self.entry_state, self.entry_list.len());
+    }
+
+    // 献立の食品の表。保存するJSONをcalcと同じ方法で読み込んで作る
+    fn get_meal_table(&self) -> Result<FoodTable, String> {
+        let mut food_table = parse_json_value(&self.to_json())?.get_food_table(self.foods)?;
+        food_table.set_kijun(Rc::clone(&self.kijun));
+
+        Ok(food_table)
+    }
+
+    // (項目, 合計, 摂取基準, 摂取基準に対する割合)
+    fn get_summary(&self) -> Result<Vec<(String, String, String, String)>, String> {
+        let mut name_list = vec!["食品名"];
+        name_list.extend(KEY_LIST.iter());
+
+        let food_table = self.get_meal_table()?;
+        let mut table = food_table.get_table(&name_list);
+        food_table.add_sum_to_table(&mut table, &name_list);
+        food_table.add_kijun_to_table(&mut table, &name_list, &self.kijun);
+        food_table.add_kijun_percentage_to_table(&mut table, &name_list, &self.kijun);
+
+        let get_text = |kind, index: usize| -> String {
+            table.row_list.iter()
+                .find(|row| row.kind == kind)
+                .and_then(|row| row.cell_list.get(index))
+                .map(|cell| cell.text.to_string())
+                .unwrap_or_default()
+        };
+
+        Ok((1..name_list.len()).map(|index| {
+            let label = match table.unit_list[index].as_str() {
+                "" => table.column_list[index].to_string(),
+                unit => format!("{}({})", table.column_list[index], unit)
+            };
+            (label,
+             get_text(RowKind::Sum, index),
+             get_text(RowKind::Kijun, index),
+             get_text(RowKind::Percentage, index))
+        }).collect())
+    }
+
+    // calcで読み込めるJSON
+    fn to_json(&self) -> Value {
+        let mut data = self.data.clone();
+        let food_list = self.entry_list.iter().map(|entry| {
+            let mut value = entry.value.clone();
+            value.insert("number".to_string(), Value::String(entry.number.to_string()));
+            value.insert("weight".to_string(), number_to_json(entry.weight));
+            Value::Object(value)
+        }).collect();
+        data.insert("foods".to_string(), Value::Array(food_list));
+        if !data.contains_key("name_list") {
+            data.insert("name_list".to_string(), Value::String("preset:kijun".to_string()));
+        }
+
+        Value::Object(data)
+    }
+
+    fn save(&mut self, path: &str) {
+        let text = serde_json::to_string_pretty(&self.to_json()).unwrap();
+        self.message = match std::fs::write(path, text + "\n") {
+            Ok(_) => {
+                self.is_changed = false;
+                format!("{}に保存しました", path)
+            },
+            Err(e) => format!("保存できませんでした: {}", e)
+        };
+    }
+
+    // falseを返したら終了する。保存していない変更があれば、もう一度Escを押すまで終了しない
+    fn quit(&mut self, is_quitting: bool) -> bool {
+        if !self.is_changed || is_quitting {
+            return false
+        }
+
+        self.is_quitting = true;
+        self.message = "保存していない変更があります。Ctrl-S: 保存  Esc: 保存せずに終了".to_string();
+        true
+    }
+
+    // falseを返したら終了する
+    fn handle_key(&mut self, key: KeyEvent, path: &str) -> bool {
+        let is_ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        // 終了の確認は次に押したキーにだけ使う
+        let is_quitting = std::mem::take(&mut self.is_quitting);
+        match key.code {
+            KeyCode::Char('c') if is_ctrl => return false,
+            KeyCode::Char('s') if is_ctrl => {
+                self.save(path);
+                return true
+            },
+            _ => ()
+        }
+
+        match self.focus {
+            Focus::Search => match key.code {
+                KeyCode::Esc => return self.quit(is_quitting),
+                KeyCode::Tab => self.focus = Focus::Meal,
+                KeyCode::Up => move_state(&mut self.result_state, self.result_list.len(), true),
+                KeyCode::Down => move_state(&mut self.result_state, self.result_list.len(), false),
+                KeyCode::Enter if self.result_state.selected().is_some() => {
+                    self.weight_text = DEFAULT_WEIGHT.to_string();
+                    self.focus = Focus::Weight(None);
+                },
+                KeyCode::Backspace => {
+                    self.text.pop();
+                    self.update_search();
+                },
+                KeyCode::Char(c) => {
+                    self.text.push(c);
+                    self.update_search();
+                },
+                _ => ()
+            },
+            Focus::Meal => {
+                let selected = self.entry_state.selected();
+                match (key.code, selected) {
+                    (KeyCode::Esc, _) => return self.quit(is_quitting),
+                    (KeyCode::Tab, _) => self.focus = Focus::Search,
+                    (KeyCode::Up, _) => move_state(&mut self.entry_state, self.entry_list.len(), true),
+                    (KeyCode::Down, _) => move_state(&mut self.entry_state, self.entry_list.len(), false),
+                    (KeyCode::Char('+'), Some(index)) => {
+                        let weight = self.entry_list[index].weight + WEIGHT_STEP;
+                        self.set_weight(index, weight);
+                    },
+                    (KeyCode::Char('-'), Some(index)) => {
+                        let weight = self.entry_list[index].weight - WEIGHT_STEP;
+                        self.set_weight(index, weight);
+                    },
+                    (KeyCode::Delete, Some(index)) | (KeyCode::Char('d'), Some(index)) => {
+                        self.remove_food(index);
+                    },
+                    (KeyCode::Enter, Some(index)) => {
+                        self.weight_text = self.entry_list[index].weight.to_string();
+                        self.focus = Focus::Weight(Some(index));
+                    },
+                    _ => ()
+                }
+            },
+            Focus::Weight(target) => match key.code {
+                KeyCode::Esc => {
+                    self.focus = if target.is_some() { Focus::Meal } else { Focus::Search };
+                },
+                KeyCode::Enter => {
+                    let weight = match self.weight_text.parse::<f32>() {
+                        Ok(weight) if weight >= 0.0 => weight,
+                        _ => {
+                            self.message = format!("{} は重量にできません", self.weight_text);
+                            return true
+                        }
+                    };
+                    match target {
+                        Some(index) => {
+                            self.set_weight(index, weight);
+                            self.focus = Focus::Meal;
+                        },
+                        None => {
+                            if let Some(number) = self.result_state.selected()
+                                .and_then(|index| self.result_list.get(index)).cloned() {
+                                self.add_food(&number, weight);
+                            }
+                            self.focus = Focus::Search;
+                        }
+                    }
+                },
+                KeyCode::Backspace => {
+                    self.weight_text.pop();
+                },
+                KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => self.weight_text.push(c),
+                _ => ()
+            }
+        }
+
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let focused = |is_focused: bool| {
+            if is_focused { Style::default().fg(Color::Yellow) } else { Style::default() }
+        };
+
+        let vertical = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(5), Constraint::Length(1)])
+            .split(frame.area());
+        let horizontal = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(30), Constraint::Percentage(35)])
+            .split(vertical[1]);
+
+        let (title, input) = match self.focus {
+            Focus::Weight(_) => ("重量(g)", self.weight_text.as_str()),
+            _ => ("検索", self.text.as_str()),
+        };
+        let input = Paragraph::new(input)
+            .block(Block::default().borders(Borders::ALL).title(title)
+                .border_style(focused(self.focus != Focus::Meal)));
+        frame.render_widget(input, vertical[0]);
+
+        let result_list: Vec<_> = self.result_list.iter()
+            .map(|number| ListItem::new(format!("{} {}", number, self.get_food_name(number))))
+            .collect();
+        let result_list = List::new(result_list)
+            .block(Block::default().borders(Borders::ALL)
+                .title(format!("検索結果（{}件）", self.result_list.len()))
+                .border_style(focused(self.focus == Focus::Search)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(result_list, horizontal[0], &mut self.result_state);
+
+        let entry_list: Vec<_> = self.entry_list.iter()
+            .map(|entry| ListItem::new(format!("{:>6}g {}", entry.weight, self.get_food_name(&entry.number))))
+            .collect();
+        let entry_list = List::new(entry_list)
+            .block(Block::default().borders(Borders::ALL).title("献立")
+                .border_style(focused(self.focus == Focus::Meal)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(entry_list, horizontal[1], &mut self.entry_state);
+
+        // 読み込めない場合は表の代わりにエラーを表示する
+        let (summary, error) = match self.get_summary() {
+            Ok(summary) => (summary, None),
+            Err(e) => (Vec::new(), Some(e))
+        };
+        let row_list: Vec<_> = summary.into_iter().map(|(label, sum, kijun, percentage)| {
+            // 摂取基準に達していない項目は赤くする
+            let style = match percentage.trim_end_matches('%').parse::<f32>() {
+                Ok(p) if p >= 100.0 => Style::default().fg(Color::Green),
+                Ok(_) => Style::default().fg(Color::Red),
+                Err(_) => Style::default()
+            };
+            Row::new(vec![label, sum, kijun, percentage]).style(style)
+        }).collect();
+        let summary = Table::new(row_list, [
+                Constraint::Percentage(40), Constraint::Percentage(20),
+                Constraint::Percentage(25), Constraint::Percentage(15)])
+            .header(Row::new(vec!["項目", "合計", "摂取基準", "割合"])
+                .style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(Block::default().borders(Borders::ALL)
+                .title(match error {
+                    Some(e) => format!("摂取基準: {}", e),
+                    None => format!("摂取基準（{}日分）", self.kijun.days)
+                }));
+        frame.render_widget(summary, horizontal[2]);
+
+        let help = match self.focus {
+            Focus::Search => "Enter: 追加  Tab: 献立へ  Ctrl-S: 保存  Esc: 終了",
+            Focus::Meal => "Enter: 重量  +/-: 10g  d: 削除  Tab: 検索へ  Ctrl-S: 保存  Esc: 終了",
+            Focus::Weight(_) => "Enter: 決定  Esc: 取消",
+        };
+        let status = if self.message.is_empty() {
+            help.to_string()
+        } else {
+            format!("{}  |  {}", self.message, help)
+        };
+        frame.render_widget(Paragraph::new(Line::from(status)), vertical[2]);
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal, path: &str) -> Result<(), String> {
+        loop {
+            terminal.draw(|frame| self.draw(frame)).map_err(|e| e.to_string())?;
+            if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key, path) {
+                    return Ok(())
+                }
+            }
+        }
+    }
+}
+
+fn read_json(path: &str) -> Result<Value, String> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => return Err(format!("{}: {}", path, e))
+    };
+    serde_json::from_reader(BufReader::new(file)).map_err(|e| format!("{}: {}", path, e))
+}
+
+// fileがあれば読み込んで続きから編集し、Ctrl-Sでfileに保存する
+// formatを指定した場合は、終了したときの献立の結果をcalcと同じように表示する
+pub fn tui(matches: &ArgMatches, foods: &FoodTable) -> Result<(), String> {
+    let path = matches.value_of("file").unwrap();
+    let format = match matches.value_of("format") {
+        Some(format) => Some(Format::from_str(format)?),
+        None => None
+    };
+
+    let mut data = match std::path::Path::new(path).exists() {
+        true => match read_json(path)? {
+            Value::Object(data) => data,
+            _ => return Err(format!("{}: JSONのオブジェクトにしてください", path))
+        },
+        false => Map::new()
+    };
+
+    if let Some(body_path) = matches.value_of("body") {
+        let body = read_json(body_path)?;
+        let body = match body.get("body") {
+            Some(body) => body.clone(),
+            None => body
+        };
+        data.insert("body".to_string(), body);
+    }
+
+    let mut app = App::new(foods, data)?;
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal, path);
+    ratatui::restore();
+    result?;
+
+    if let Some(format) = format {
+        let input = crate::get_calc_input(parse_json_value(&app.to_json())?, foods)?;
+        let mut report = crate::get_calc_report(input)?;
+        report.set_title(path);
+        report.print(format);
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+fn get_test_data() -> Map<String, Value> {
+    let data = serde_json::json!({
+        "foods": [{"number": "01001", "weight": 50, "class": "朝食"}],
+        "body": {"age": 20, "weight": 50, "height": 160, "gender": "male", "pal": "low"}
+    });
+    match data {
+        Value::Object(data) => data,
+        _ => unreachable!()
+    }
+}
+
+#[test]
+fn test_tui_app_search_and_add() {
+    let foods = FoodTable::from_json("./test/test_foods.json").unwrap();
+    let mut app = App::new(&foods, get_test_data()).unwrap();
+    assert_eq!(app.entry_list.len(), 1);
+
+    for c in "ｱﾜ".chars() {
+        app.handle_key(KeyEvent::from(KeyCode::Char(c)), "");
+    }
+    assert_eq!(app.result_list, vec!["01002", "01003"]);
+
+    app.handle_key(KeyEvent::from(KeyCode::Down), "");
+    app.handle_key(KeyEvent::from(KeyCode::Enter), "");
+    assert_eq!(app.focus, Focus::Weight(None));
+    app.handle_key(KeyEvent::from(KeyCode::Backspace), "");
+    app.handle_key(KeyEvent::from(KeyCode::Backspace), "");
+    app.handle_key(KeyEvent::from(KeyCode::Char('5')), "");
+    app.handle_key(KeyEvent::from(KeyCode::Enter), "");
+    assert_eq!(app.focus, Focus::Search);
+    assert_eq!(app.entry_list[1].number, "01003");
+    assert_eq!(app.entry_list[1].weight, 15.0);
+
+    // 献立の重量を変えて削除する
+    app.handle_key(KeyEvent::from(KeyCode::Tab), "");
+    app.handle_key(KeyEvent::from(KeyCode::Char('+')), "");
+    assert_eq!(app.entry_list[1].weight, 25.0);
+    app.handle_key(KeyEvent::from(KeyCode::Char('d')), "");
+    assert_eq!(app.entry_list.len(), 1);
+
+    // 変更があれば2回目のEscで終了する
+    assert!(app.handle_key(KeyEvent::from(KeyCode::Esc), ""));
+    app.handle_key(KeyEvent::from(KeyCode::Up), "");
+    assert!(app.handle_key(KeyEvent::from(KeyCode::Esc), ""));
+    assert!(!app.handle_key(KeyEvent::from(KeyCode::Esc), ""));
+
+    let mut app = App::new(&foods, get_test_data()).unwrap();
+    assert!(!app.handle_key(KeyEvent::from(KeyCode::Esc), ""));
+}
+
+#[test]
+fn test_tui_app_summary_and_json() {
+    let foods = FoodTable::from_json("./test/test_foods.json").unwrap();
+    let mut app = App::new(&foods, get_test_data()).unwrap();
+    app.add_food("01002", 100.0);
+    app.set_weight(1, 12.3);
+
+    let summary = app.get_summary().unwrap();
+    assert_eq!(summary.len(), KEY_LIST.len());
+    assert_eq!(summary[0].0, "エネルギー(kcal)");
+    assert!(summary[0].3.ends_with('%'));
+
+    let json = app.to_json();
+    assert_eq!(json["foods"][0]["class"], "朝食");
+    assert_eq!(json["foods"][0]["weight"].as_f64(), Some(50.0));
+    assert_eq!(json["foods"][1]["number"], "01002");
+    assert_eq!(json["foods"][1]["weight"].to_string(), "12.3");
+    assert_eq!(json["name_list"], "preset:kijun");
+    assert_eq!(json["body"]["age"], 20);
+
+    let data = match json {
+        Value::Object(data) => data,
+        _ => unreachable!()
+    };
+    assert!(App::new(&foods, data).is_ok());
+}
+
+#[test]
+fn test_tui_app_same_meal_as_calc() {
+    let foods = FoodTable::from_json("./test/test_foods.json").unwrap();
+    let mut data = get_test_data();
+    data.insert("foods".to_string(), serde_json::json!([{"number": "u1", "weight": 50}]));
+    data.insert("user_definition_foods".to_string(), serde_json::json!({
+        "1": {"weight": 100, "data": {"食品名": "定義した食品", "エネルギー": "200"}}
+    }));
+    data.insert("targets".to_string(), serde_json::json!({"ビタミンC": {"kind": "more", "amount": 1234}}));
+    let app = App::new(&foods, data.clone()).unwrap();
+
+    // user_definition_foodsの食品も合計に含め、トップレベルのtargetsを摂取基準にする
+    assert_eq!(app.get_food_name("u1"), "定義した食品");
+    let summary = app.get_summary().unwrap();
+    assert_eq!(summary[0].1, "100");
+    let vitamin_c = summary.iter().find(|row| row.0.starts_with("ビタミンC")).unwrap();
+    assert!(vitamin_c.2.contains("1234"));
+
+    // calcで読み込めないJSONはエラーにする
+    data.insert("foods".to_string(), serde_json::json!([{"number": "u9"}]));
+    assert!(App::new(&foods, data).is_err());
+}
+
+#[test]
+fn test_tui_app_draw() {
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    let foods = FoodTable::from_json("./test/test_foods.json").unwrap();
+    let mut app = App::new(&foods, get_test_data()).unwrap();
+    app.text = "あわ".to_string();
+    app.update_search();
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+    // 全角文字の次のセルは空白になる
+    let text: String = terminal.backend().buffer().content().iter()
+        .map(|cell| cell.symbol()).filter(|symbol| *symbol != " ").collect();
+    assert!(text.contains("検索結果（2件）"));
+    assert!(text.contains("摂取基準（1日分）"));
+}
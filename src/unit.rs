@@ -0,0 +1,87 @@
+// 栄養素の単位
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Unit {
+    Gram,
+    Milligram,
+    Microgram,
+    Kcal,
+    Kj,
+    Percent,
+    Yen,
+    None,
+}
+
+// 単位の種類。同じ種類の単位同士なら変換できる
+#[derive(PartialEq)]
+enum Dimension {
+    Mass,
+    Energy,
+    Percent,
+    Yen,
+    None,
+}
+
+impl Unit {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Unit::Gram => "g",
+            Unit::Milligram => "mg",
+            Unit::Microgram => "µg",
+            Unit::Kcal => "kcal",
+            Unit::Kj => "kJ",
+            Unit::Percent => "%",
+            Unit::Yen => "円",
+            Unit::None => "",
+        }
+    }
+
+    // 単位の種類と、その種類の基本の単位（µg、kJ）に対する倍率
+    fn get_base(self) -> (Dimension, f32) {
+        match self {
+            Unit::Gram => (Dimension::Mass, 1_000_000.0),
+            Unit::Milligram => (Dimension::Mass, 1000.0),
+            Unit::Microgram => (Dimension::Mass, 1.0),
+            // 成分表のエネルギー換算係数 1kcal = 4.184kJ
+            Unit::Kcal => (Dimension::Energy, 4.184),
+            Unit::Kj => (Dimension::Energy, 1.0),
+            Unit::Percent => (Dimension::Percent, 1.0),
+            Unit::Yen => (Dimension::Yen, 1.0),
+            Unit::None => (Dimension::None, 1.0),
+        }
+    }
+
+    // この単位の値をtoの単位にするときの倍率。変換できない場合はNone
+    pub fn get_rate(self, to: Unit) -> Option<f32> {
+        let (dimension, base) = self.get_base();
+        let (to_dimension, to_base) = to.get_base();
+        if dimension != to_dimension {
+            return None
+        }
+
+        Some(base / to_base)
+    }
+
+    pub fn convert(self, value: f32, to: Unit) -> Option<f32> {
+        Some(value * self.get_rate(to)?)
+    }
+}
+
+
+#[test]
+fn test_unit_to_str() {
+    assert_eq!(Unit::Microgram.to_str(), "µg");
+    assert_eq!(Unit::Kj.to_str(), "kJ");
+    assert_eq!(Unit::None.to_str(), "");
+}
+
+#[test]
+fn test_unit_convert() {
+    assert_eq!(Unit::Milligram.convert(1.0, Unit::Microgram), Some(1000.0));
+    assert!((Unit::Microgram.convert(500.0, Unit::Milligram).unwrap() - 0.5).abs() < 0.0001);
+    assert!((Unit::Kj.convert(418.4, Unit::Kcal).unwrap() - 100.0).abs() < 0.0001);
+    assert_eq!(Unit::Gram.convert(2.0, Unit::Milligram), Some(2000.0));
+    assert_eq!(Unit::Kcal.convert(100.0, Unit::Kj), Some(418.4));
+    assert_eq!(Unit::Kcal.convert(100.0, Unit::Kcal), Some(100.0));
+    assert_eq!(Unit::Kcal.convert(100.0, Unit::Gram), None);
+    assert_eq!(Unit::Percent.convert(10.0, Unit::Gram), None);
+}